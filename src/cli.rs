@@ -1,10 +1,81 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::fmt;
 
 #[derive(Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, long_version = crate::version::LONG_VERSION, about, long_about = None)]
 #[command(name = "farm-manager")]
 #[command(about = "A CLI tool for managing farm infrastructure")]
 pub struct Cli {
+    /// Path to the config file (default: ~/.config/farm-manager/config.toml)
+    #[arg(long, global = true)]
+    pub config: Option<String>,
+    
+    /// Write command output to a file instead of stdout; supports {hostname}, {serial}, {date}, {command}
+    #[arg(long, global = true)]
+    pub output: Option<String>,
+    
+    /// Print extra detail (e.g. full response bodies on failed FarmCore requests)
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+    
+    /// Suppress progress and status lines (and the running-without-root warning); data and errors still print. Structured --format output implies it for status lines
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+    
+    /// Color errors and warnings: auto (only on a terminal, unless NO_COLOR is set), always, or never
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+    
+    /// Fail instead of warning when a command that needs root runs without it
+    #[arg(long, global = true)]
+    pub require_root: bool,
+    
+    /// Client certificate (PEM) for mutual TLS with FarmCore; needs --client-key
+    #[arg(long, global = true, requires = "client_key")]
+    pub client_cert: Option<String>,
+    
+    /// Private key (PKCS#8 PEM) for --client-cert
+    #[arg(long, global = true, requires = "client_cert")]
+    pub client_key: Option<String>,
+    
+    /// Extra CA certificate bundle (PEM) to trust for FarmCore
+    #[arg(long, global = true)]
+    pub ca_cert: Option<String>,
+    
+    /// Proxy URL for FarmCore requests; overrides the config file and HTTPS_PROXY/HTTP_PROXY (NO_PROXY still applies)
+    #[arg(long, global = true)]
+    pub proxy: Option<String>,
+    
+    /// Skip TLS certificate verification for FarmCore requests (self-signed lab setups only)
+    #[arg(long, global = true)]
+    pub insecure: bool,
+    
+    /// Remote BMC to read over the network instead of in-band; IPMI-backed hardware commands then report only what it provides
+    #[arg(long, global = true)]
+    pub bmc_host: Option<String>,
+    
+    /// Remote BMC user name
+    #[arg(long, global = true, requires = "bmc_host")]
+    pub bmc_user: Option<String>,
+    
+    /// Remote BMC password (defaults to $IPMI_PASSWORD)
+    #[arg(long, global = true, requires = "bmc_host")]
+    pub bmc_pass: Option<String>,
+    
+    /// ipmitool interface, e.g. lan or lanplus (default: lanplus with --bmc-host)
+    #[arg(long, global = true)]
+    pub bmc_interface: Option<String>,
+    
+    /// Print only what this JSONPath expression selects from the result, e.g. '$.gpus[*].model'
+    ///
+    /// Uses RFC 9535 JSONPath: `$` is the whole result, `.name` or `['name']` a
+    /// member, `[0]`/`[-1]` an index, `[1:3]` a slice, `[*]` every entry, `..name`
+    /// a member at any depth, and `[?@.vram_mb > 40000]` a filter (comparisons,
+    /// `&&`, `||`, `!`, and length(), count(), match(), search(), value()).
+    /// The matches are printed as a list in the chosen --format.
+    #[arg(long, global = true)]
+    pub query: Option<String>,
+    
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -16,22 +87,109 @@ pub enum Commands {
     Hardware(HardwareCommands),
     
     /// Testing and diagnostics commands (GPU, NCCL, MPI)
-    #[command(subcommand)]
-    Test(TestCommands),
+    Test(TestArgs),
     
     /// Virtual machine management commands
     #[command(subcommand)]
     Vm(VmCommands),
     
     /// Kubernetes cluster management commands
-    #[command(subcommand)]
-    K8s(K8sCommands),
+    K8s(K8sArgs),
+    
+    /// Collect hardware and VM inventory and post both to FarmCore (cron entrypoint)
+    Agent {
+        /// FarmCore API base URL
+        #[arg(short, long, default_value = crate::config::DEFAULT_URL)]
+        url: String,
+        
+        /// Hypervisor type
+        #[arg(short = 'H', long, value_enum, default_value_t = Hypervisor::Kvm)]
+        hypervisor: Hypervisor,
+        
+        /// Only post hardware inventory (for nodes that are not hypervisors)
+        #[arg(long)]
+        skip_vms: bool,
+        
+        /// API path for hardware inventory (default: server_inventory_path from the config file, else /api/v1/servers/inventory)
+        #[arg(long)]
+        path: Option<String>,
+        
+        /// API path for VM inventory (default: vm_inventory_path from the config file, else /api/v1/vms/inventory)
+        #[arg(long, conflicts_with = "skip_vms")]
+        vm_path: Option<String>,
+    },
+    
+    /// Merge per-node inventory files into one array or a fleet summary
+    Merge {
+        /// Inventory files, directories (*.json, *.ndjson, *.jsonl) or glob patterns
+        #[arg(required = true)]
+        inputs: Vec<String>,
+        
+        /// Print cross-node counts (GPU models, firmware, kernels) instead of the merged array
+        #[arg(long)]
+        summary: bool,
+        
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+    
+    /// Show what changed between two inventory snapshots (disks, firmware, RAM, GPUs, ...)
+    Diff {
+        /// Earlier inventory JSON file
+        before: String,
+        
+        /// Later inventory JSON file
+        after: String,
+        
+        /// Exit non-zero when the snapshots differ
+        #[arg(long)]
+        fail_on_change: bool,
+        
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+    
+    /// Generate man pages for farm-manager and its subcommands
+    Man {
+        /// Write a page per command into this directory instead of printing the top-level page
+        #[arg(short, long)]
+        dir: Option<String>,
+    },
+    
+    /// Print the JSON Schema of the inventory payload (for backend integrations)
+    #[cfg(feature = "schema")]
+    #[command(hide = true)]
+    Schema,
+    
+    /// Show agent version and build metadata
+    Version {
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
 }
 
 #[derive(Subcommand)]
 pub enum HardwareCommands {
     /// Collect full hardware inventory
     Inventory {
+        /// Include per-collector timings (ms) in the output
+        #[arg(long)]
+        timings: bool,
+        
+        /// Check the inventory against expected counts instead of printing it, e.g.
+        /// "gpus=8,dimms=16,disks>=4" (keys: gpus, dimms, disks, nics, psus, fans,
+        /// sockets, cores, threads, memory_gb); exits non-zero if any rule fails
+        #[arg(long, value_delimiter = ',')]
+        expect: Vec<String>,
+        
+        /// Only output these dotted paths, e.g. "node.serial_number,gpus.model,memory.total_bytes";
+        /// a path through a list applies to every entry
+        #[arg(long, value_delimiter = ',', conflicts_with = "expect")]
+        fields: Vec<String>,
+        
         /// Output format (json, yaml, or pretty)
         #[arg(short, long, default_value = "pretty")]
         format: String,
@@ -42,6 +200,12 @@ pub enum HardwareCommands {
         #[arg(short, long, default_value = "pretty")]
         format: String,
     },
+    /// Collect per-core CPU frequency and governor settings
+    CpuFreq {
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
     /// Collect memory information
     Memory {
         /// Output format (json, yaml, or pretty)
@@ -54,6 +218,124 @@ pub enum HardwareCommands {
         #[arg(short, long, default_value = "pretty")]
         format: String,
     },
+    /// Collect mounted filesystem usage (space and inodes)
+    Filesystems {
+        /// Include RAM-backed filesystems (tmpfs, devtmpfs)
+        #[arg(long)]
+        include_tmpfs: bool,
+        
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+    /// Turn a drive bay's locate/identify LED on or off (requires ledmon/ledctl)
+    LocateDisk {
+        /// Disk device (e.g., sda or /dev/sda)
+        #[arg(short, long)]
+        device: String,
+        
+        /// Turn the locate LED off instead of on
+        #[arg(long)]
+        off: bool,
+    },
+    /// Start a SMART self-test on a disk (requires smartmontools)
+    DiskTest {
+        /// Disk device (e.g., sda or /dev/sda)
+        #[arg(short, long)]
+        device: String,
+        
+        /// Self-test type
+        #[arg(short, long, default_value = "short", value_parser = ["short", "long", "conveyance"])]
+        test_type: String,
+        
+        /// Wait for the test to finish and report pass/fail
+        #[arg(short, long)]
+        wait: bool,
+        
+        /// Stop waiting after this many minutes (default: twice the drive's estimate plus 10, or 24 hours)
+        #[arg(long, requires = "wait")]
+        max_wait: Option<u64>,
+    },
+    /// List all PCI devices with class, driver and link speed
+    Pci {
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+    /// List attached USB devices (license dongles, security keys, ...)
+    Usb {
+        /// Include hubs and root hubs
+        #[arg(long)]
+        include_hubs: bool,
+        
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+    /// Collect fan speeds (hwmon and IPMI)
+    Fans {
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+    /// Collect temperature readings from all hwmon sensors
+    Temps {
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+    /// Dump decoded SMBIOS/DMI structures
+    Dmi {
+        /// Only show structures of this DMI type (e.g. 17 for memory devices)
+        #[arg(short = 't', long = "type")]
+        dmi_type: Option<u8>,
+        
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+    /// Read the IPMI System Event Log (SEL)
+    Sel {
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+        
+        /// Clear the SEL after reading it
+        #[arg(long)]
+        clear: bool,
+        
+        /// Skip confirmation prompt when clearing
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+    /// Control chassis power or blink the identify LED through the BMC (requires ipmitool)
+    Chassis {
+        /// Power action, or identify to blink the chassis locate LED
+        #[arg(value_parser = ["status", "on", "off", "cycle", "reset", "identify"])]
+        action: String,
+        
+        /// How long to blink the identify LED in seconds (0 turns it off)
+        #[arg(long)]
+        duration: Option<u32>,
+        
+        /// Skip confirmation prompt for off, cycle and reset
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+    /// Show or set the next boot device through the BMC (requires ipmitool)
+    BootDevice {
+        /// Boot device to use on the next boot (none clears the override)
+        #[arg(long, value_parser = ["pxe", "disk", "bios", "cdrom", "none"])]
+        set: Option<String>,
+        
+        /// Keep the override for all future boots instead of only the next one
+        #[arg(long, requires = "set")]
+        persistent: bool,
+        
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
     /// Collect network interface information
     Network {
         /// Output format (json, yaml, or pretty)
@@ -66,8 +348,34 @@ pub enum HardwareCommands {
         #[arg(short, long, default_value = "pretty")]
         format: String,
     },
-    /// Collect power supply information
+    /// Collect power supply information and total node power draw
+    ///
+    /// json/yaml output is an object with `total_power_watts` and
+    /// `power_supplies`; scripts written for the older bare array of power
+    /// supplies should read `.power_supplies`.
     Power {
+        /// Output format (json, ndjson, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+        
+        /// Re-sample every N seconds until interrupted (one ndjson record per sample unless redrawing a terminal)
+        #[arg(short, long)]
+        watch: Option<u64>,
+        
+        /// Stop after this many samples (with --watch)
+        #[arg(short = 'n', long, requires = "watch")]
+        count: Option<u32>,
+    },
+    /// Show or set the BMC's DCMI power cap (requires ipmitool)
+    PowerCap {
+        /// Cap node power at this many watts and activate the limit
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        set: Option<u32>,
+        
+        /// Skip confirmation prompt when setting the cap
+        #[arg(short = 'y', long)]
+        yes: bool,
+        
         /// Output format (json, yaml, or pretty)
         #[arg(short, long, default_value = "pretty")]
         format: String,
@@ -75,21 +383,103 @@ pub enum HardwareCommands {
     /// Post inventory data to FarmCore API
     PostInventory {
         /// FarmCore API base URL
-        #[arg(short, long, default_value = "http://localhost:6183")]
+        #[arg(short, long, default_value = crate::config::DEFAULT_URL)]
         url: String,
+        
+        /// Include per-collector timings (ms) in the posted inventory
+        #[arg(long)]
+        timings: bool,
+        
+        /// API path to post to (default: server_inventory_path from the config file, else /api/v1/servers/inventory)
+        #[arg(long)]
+        path: Option<String>,
     },
 }
 
+#[derive(Args)]
+pub struct TestArgs {
+    /// Also save each result, or the error a test failed with, as timestamped JSON in this directory (with hostname and command), building a local history
+    #[arg(long, global = true)]
+    pub results_dir: Option<String>,
+    
+    #[command(subcommand)]
+    pub command: TestCommands,
+}
+
 #[derive(Subcommand)]
 pub enum TestCommands {
+    /// Run every non-destructive health and info check (gpu-health, dcgm-health, nccl-info,
+    /// mpi-info, hashcat-info) and report an overall status; exits non-zero if any is critical
+    All {
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+    /// Burn in the CPUs with stress-ng or sysbench (built-in loop if neither is installed),
+    /// reporting throughput and peak CPU temperature
+    Cpu {
+        /// How long to load the CPUs, in seconds
+        #[arg(short = 't', long, default_value = "60", value_parser = clap::value_parser!(u64).range(1..))]
+        duration: u64,
+        
+        /// Worker threads (default: one per logical CPU)
+        #[arg(short = 'j', long, value_parser = clap::value_parser!(u32).range(1..))]
+        threads: Option<u32>,
+        
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
     /// Check for GPU errors (NVIDIA GPUs only, requires NVML)
     GpuErrors {
+        #[command(flatten)]
+        select: GpuSelectArgs,
+        
         /// Output format (json, yaml, or pretty)
         #[arg(short, long, default_value = "pretty")]
         format: String,
     },
     /// Check GPU health status (NVIDIA GPUs only, requires NVML)
     GpuHealth {
+        #[command(flatten)]
+        select: GpuSelectArgs,
+        
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+    /// Show or change GPU power limit, application clocks and persistence mode (requires NVML)
+    GpuConfig {
+        /// GPU indices to configure (comma-separated, e.g. "0,1"; default: all)
+        #[arg(short, long, value_delimiter = ',')]
+        devices: Option<Vec<u32>>,
+        
+        /// Power limit in watts
+        #[arg(long)]
+        power_limit: Option<u32>,
+        
+        /// Application memory clock in MHz
+        #[arg(long, requires = "graphics_clock")]
+        memory_clock: Option<u32>,
+        
+        /// Application graphics clock in MHz
+        #[arg(long, requires = "memory_clock")]
+        graphics_clock: Option<u32>,
+        
+        /// Persistence mode (on or off)
+        #[arg(long, value_parser = ["on", "off"])]
+        persistence: Option<String>,
+        
+        /// Skip confirmation prompt when changing settings
+        #[arg(short = 'y', long)]
+        yes: bool,
+        
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+    /// Show the GPU interconnect matrix (NVLink/PCIe) and CPU affinity (requires NVML)
+    GpuTopo {
         /// Output format (json, yaml, or pretty)
         #[arg(short, long, default_value = "pretty")]
         format: String,
@@ -140,6 +530,72 @@ pub enum TestCommands {
         #[arg(short = 'f', long, default_value = "pretty")]
         format: String,
     },
+    /// Measure raw InfiniBand/RoCE bandwidth with perftest (ib_write_bw / ib_read_bw).
+    /// Start it without --server on one node, then with --server on the other
+    IbBw {
+        /// Test type: write or read
+        #[arg(short, long, default_value = "write", value_parser = ["write", "read"])]
+        test_type: String,
+        
+        /// Host running the listening side; without it, wait for a client to connect
+        #[arg(long)]
+        server: Option<String>,
+        
+        /// RDMA device to use, e.g. mlx5_0 (default: the first one perftest finds)
+        #[arg(short, long)]
+        device: Option<String>,
+        
+        /// Message size in bytes (supports K, M, G suffixes)
+        #[arg(short, long, default_value = "64K")]
+        size: String,
+        
+        /// Number of iterations
+        #[arg(short, long, default_value = "5000")]
+        iterations: u32,
+        
+        /// TCP port used to set up the connection (default: perftest's 18515)
+        #[arg(short, long)]
+        port: Option<u16>,
+        
+        /// Output format (json, yaml, or pretty)
+        #[arg(short = 'f', long, default_value = "pretty")]
+        format: String,
+    },
+    /// Measure TCP/UDP throughput between nodes with iperf3.
+    /// Start it with --listen on one node, then with --server on the other
+    NetBw {
+        /// Host running the listening side
+        #[arg(long, required_unless_present = "listen")]
+        server: Option<String>,
+        
+        /// Wait for one client to connect and report what it measured
+        #[arg(long, conflicts_with = "server")]
+        listen: bool,
+        
+        /// Number of parallel streams
+        #[arg(short = 'P', long, default_value = "1", value_parser = clap::value_parser!(u32).range(1..=128), conflicts_with = "listen")]
+        parallel: u32,
+        
+        /// Test UDP instead of TCP (reports jitter and loss)
+        #[arg(short, long, conflicts_with = "listen")]
+        udp: bool,
+        
+        /// Target bitrate, e.g. "10G" (iperf3 -b; UDP defaults to only 1M without it)
+        #[arg(short, long, conflicts_with = "listen")]
+        bitrate: Option<String>,
+        
+        /// Test length in seconds
+        #[arg(short = 't', long, default_value = "10", value_parser = clap::value_parser!(u32).range(1..), conflicts_with = "listen")]
+        duration: u32,
+        
+        /// Server port (default: iperf3's 5201)
+        #[arg(short, long)]
+        port: Option<u16>,
+        
+        /// Output format (json, yaml, or pretty)
+        #[arg(short = 'f', long, default_value = "pretty")]
+        format: String,
+    },
     /// Check MPI installation and version
     MpiInfo {
         /// Output format (json, yaml, or pretty)
@@ -156,6 +612,34 @@ pub enum TestCommands {
         #[arg(short, long, value_delimiter = ',')]
         devices: Option<Vec<u32>>,
         
+        #[command(flatten)]
+        hashcat: HashcatArgs,
+        
+        /// Output format (json, yaml, or pretty)
+        #[arg(short = 'f', long, default_value = "pretty")]
+        format: String,
+    },
+    /// Run hashcat benchmarks back to back while monitoring GPU temperature and power
+    HashcatStress {
+        /// Hash type mode to benchmark (e.g., 1000 for NTLM)
+        #[arg(short = 'm', long, default_value = "1000")]
+        hash_type: String,
+        
+        /// How long to keep the GPUs loaded, in seconds
+        #[arg(short = 't', long, default_value = "300")]
+        duration: u64,
+        
+        /// Seconds between GPU temperature/power samples
+        #[arg(long, default_value = "5")]
+        sample_interval: u64,
+        
+        /// Device IDs to use (comma-separated, e.g., "1,2")
+        #[arg(short, long, value_delimiter = ',')]
+        devices: Option<Vec<u32>>,
+        
+        #[command(flatten)]
+        hashcat: HashcatArgs,
+        
         /// Output format (json, yaml, or pretty)
         #[arg(short = 'f', long, default_value = "pretty")]
         format: String,
@@ -167,17 +651,36 @@ pub enum TestCommands {
         hash_type: String,
         
         /// Path to file containing hashes
-        #[arg(short = 'H', long)]
-        hash_file: String,
+        #[arg(short = 'H', long, required_unless_present = "restore")]
+        hash_file: Option<String>,
         
         /// Path to wordlist file
-        #[arg(short, long)]
-        wordlist: String,
+        #[arg(short, long, required_unless_present = "restore")]
+        wordlist: Option<String>,
         
         /// Device IDs to use (comma-separated, e.g., "1,2")
         #[arg(short, long, value_delimiter = ',')]
         devices: Option<Vec<u32>>,
         
+        /// Name the hashcat session so an interrupted run can be resumed
+        #[arg(long)]
+        session: Option<String>,
+        
+        /// Resume the named session instead of starting a new run (its options come from the restore file)
+        #[arg(long, requires = "session", conflicts_with_all = ["hash_file", "wordlist", "devices", "workload", "force", "max_runtime"])]
+        restore: bool,
+        
+        /// Directory for session restore files (default: ~/.local/share/farm-manager/hashcat-sessions)
+        #[arg(long)]
+        session_dir: Option<String>,
+        
+        /// Stop the attack after this many seconds (hashcat --runtime)
+        #[arg(long)]
+        max_runtime: Option<u64>,
+        
+        #[command(flatten)]
+        hashcat: HashcatArgs,
+        
         /// Output format (json, yaml, or pretty)
         #[arg(short = 'f', long, default_value = "pretty")]
         format: String,
@@ -198,6 +701,10 @@ pub enum TestCommands {
         #[arg(short, long, value_delimiter = ',')]
         gpus: Option<Vec<u32>>,
         
+        /// Kill the diagnostic if it runs longer than this many seconds
+        #[arg(long)]
+        max_runtime: Option<u64>,
+        
         /// Output format (json, yaml, or pretty)
         #[arg(short = 'f', long, default_value = "pretty")]
         format: String,
@@ -216,13 +723,41 @@ pub enum TestCommands {
     },
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Hypervisor {
+    Kvm,
+    Qemu,
+    #[value(name = "virtualbox")]
+    VirtualBox,
+    Vmware,
+}
+
+impl fmt::Display for Hypervisor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Hypervisor::Kvm => "kvm",
+            Hypervisor::Qemu => "qemu",
+            Hypervisor::VirtualBox => "virtualbox",
+            Hypervisor::Vmware => "vmware",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 #[derive(Subcommand)]
 pub enum VmCommands {
     /// List all virtual machines
     List {
-        /// Hypervisor type (kvm, qemu, vmware, virtualbox)
-        #[arg(short = 'H', long, default_value = "kvm")]
-        hypervisor: String,
+        /// Hypervisor type
+        #[arg(short = 'H', long, value_enum, default_value_t = Hypervisor::Kvm)]
+        hypervisor: Hypervisor,
         
         /// Output format (json, yaml, or pretty)
         #[arg(short, long, default_value = "pretty")]
@@ -231,28 +766,68 @@ pub enum VmCommands {
     
     /// Start a virtual machine
     Start {
+        #[command(flatten)]
+        target: VmTargetArgs,
+        
+        /// Hypervisor type
+        #[arg(short = 'H', long, value_enum, default_value_t = Hypervisor::Kvm)]
+        hypervisor: Hypervisor,
+    },
+    
+    /// Stop a virtual machine
+    Stop {
+        #[command(flatten)]
+        target: VmTargetArgs,
+        
+        /// Hypervisor type
+        #[arg(short = 'H', long, value_enum, default_value_t = Hypervisor::Kvm)]
+        hypervisor: Hypervisor,
+        
+        /// Force shutdown (don't wait for graceful shutdown)
+        #[arg(short, long)]
+        force: bool,
+    },
+    
+    /// Suspend (pause) a running virtual machine
+    Suspend {
         /// VM name or ID
         #[arg(short, long)]
         name: String,
         
-        /// Hypervisor type (kvm, qemu, vmware, virtualbox)
-        #[arg(short = 'H', long, default_value = "kvm")]
-        hypervisor: String,
+        /// Hypervisor type
+        #[arg(short = 'H', long, value_enum, default_value_t = Hypervisor::Kvm)]
+        hypervisor: Hypervisor,
     },
     
-    /// Stop a virtual machine
-    Stop {
+    /// Resume a suspended virtual machine
+    Resume {
         /// VM name or ID
         #[arg(short, long)]
         name: String,
         
-        /// Hypervisor type (kvm, qemu, vmware, virtualbox)
-        #[arg(short = 'H', long, default_value = "kvm")]
-        hypervisor: String,
+        /// Hypervisor type
+        #[arg(short = 'H', long, value_enum, default_value_t = Hypervisor::Kvm)]
+        hypervisor: Hypervisor,
+    },
+    
+    /// Configure whether a VM starts automatically on host boot
+    #[command(group(clap::ArgGroup::new("autostart_toggle").required(true).args(["enable", "disable"])))]
+    Autostart {
+        /// VM name or ID
+        #[arg(short, long)]
+        name: String,
         
-        /// Force shutdown (don't wait for graceful shutdown)
+        /// Hypervisor type
+        #[arg(short = 'H', long, value_enum, default_value_t = Hypervisor::Kvm)]
+        hypervisor: Hypervisor,
+        
+        /// Start the VM on host boot
         #[arg(short, long)]
-        force: bool,
+        enable: bool,
+        
+        /// Don't start the VM on host boot
+        #[arg(short, long)]
+        disable: bool,
     },
     
     /// Create a new virtual machine
@@ -261,9 +836,9 @@ pub enum VmCommands {
         #[arg(short, long)]
         name: String,
         
-        /// Hypervisor type (kvm, qemu, vmware, virtualbox)
-        #[arg(short = 'H', long, default_value = "kvm")]
-        hypervisor: String,
+        /// Hypervisor type
+        #[arg(short = 'H', long, value_enum, default_value_t = Hypervisor::Kvm)]
+        hypervisor: Hypervisor,
         
         /// Number of virtual CPUs
         #[arg(short = 'c', long, default_value = "2")]
@@ -277,6 +852,18 @@ pub enum VmCommands {
         #[arg(short, long, default_value = "20")]
         disk: u32,
         
+        /// Disk image path (defaults to the hypervisor's standard location)
+        #[arg(long)]
+        disk_path: Option<String>,
+        
+        /// libvirt storage pool to create the disk in (KVM only)
+        #[arg(long, conflicts_with = "disk_path")]
+        pool: Option<String>,
+        
+        /// Disk image format (qcow2 or raw for KVM; vdi or vmdk for VirtualBox)
+        #[arg(long)]
+        disk_format: Option<String>,
+        
         /// OS variant (e.g., ubuntu22.04, centos9, win10)
         #[arg(short, long)]
         os_variant: Option<String>,
@@ -288,6 +875,14 @@ pub enum VmCommands {
         /// Network (default, bridge name, or none)
         #[arg(long, default_value = "default")]
         network: String,
+        
+        /// cloud-init user-data file to attach as a NoCloud seed ISO (KVM only)
+        #[arg(long)]
+        cloud_init: Option<String>,
+        
+        /// Output format for the created VM (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
     },
     
     /// Delete a virtual machine
@@ -296,9 +891,9 @@ pub enum VmCommands {
         #[arg(short, long)]
         name: String,
         
-        /// Hypervisor type (kvm, qemu, vmware, virtualbox)
-        #[arg(short = 'H', long, default_value = "kvm")]
-        hypervisor: String,
+        /// Hypervisor type
+        #[arg(short = 'H', long, value_enum, default_value_t = Hypervisor::Kvm)]
+        hypervisor: Hypervisor,
         
         /// Remove storage volumes
         #[arg(long)]
@@ -315,9 +910,9 @@ pub enum VmCommands {
         #[arg(short, long)]
         name: String,
         
-        /// Hypervisor type (kvm, qemu, vmware, virtualbox)
-        #[arg(short = 'H', long, default_value = "kvm")]
-        hypervisor: String,
+        /// Hypervisor type
+        #[arg(short = 'H', long, value_enum, default_value_t = Hypervisor::Kvm)]
+        hypervisor: Hypervisor,
         
         /// Output format (json, yaml, or pretty)
         #[arg(short, long, default_value = "pretty")]
@@ -326,36 +921,149 @@ pub enum VmCommands {
     
     /// Reboot a virtual machine
     Reboot {
+        #[command(flatten)]
+        target: VmTargetArgs,
+        
+        /// Hypervisor type
+        #[arg(short = 'H', long, value_enum, default_value_t = Hypervisor::Kvm)]
+        hypervisor: Hypervisor,
+        
+        /// Force reboot
+        #[arg(short, long)]
+        force: bool,
+    },
+    
+    /// Attach to a VM's serial console
+    Console {
+        /// VM name or ID
+        #[arg(short, long)]
+        name: String,
+        
+        /// Hypervisor type
+        #[arg(short = 'H', long, value_enum, default_value_t = Hypervisor::Kvm)]
+        hypervisor: Hypervisor,
+    },
+    
+    /// List libvirt storage pools with capacity and free space (KVM)
+    Pools {
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+    
+    /// List libvirt virtual networks with bridge, forward mode, and DHCP range (KVM)
+    Networks {
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+    
+    /// Show live CPU, memory, and I/O statistics for a running VM
+    Metrics {
         /// VM name or ID
         #[arg(short, long)]
         name: String,
         
-        /// Hypervisor type (kvm, qemu, vmware, virtualbox)
-        #[arg(short = 'H', long, default_value = "kvm")]
-        hypervisor: String,
+        /// Hypervisor type
+        #[arg(short = 'H', long, value_enum, default_value_t = Hypervisor::Kvm)]
+        hypervisor: Hypervisor,
         
-        /// Force reboot
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+        
+        /// Re-sample every N seconds until interrupted
         #[arg(short, long)]
-        force: bool,
+        watch: Option<u64>,
     },
     
     /// Post VM inventory data to FarmCore API
     PostInventory {
         /// FarmCore API base URL
-        #[arg(short, long, default_value = "http://localhost:6183")]
+        #[arg(short, long, default_value = crate::config::DEFAULT_URL)]
         url: String,
         
-        /// Hypervisor type (kvm, qemu, vmware, virtualbox)
-        #[arg(short = 'H', long, default_value = "kvm")]
-        hypervisor: String,
+        /// Hypervisor type
+        #[arg(short = 'H', long, value_enum, default_value_t = Hypervisor::Kvm)]
+        hypervisor: Hypervisor,
+        
+        /// API path to post to (default: vm_inventory_path from the config file, else /api/v1/vms/inventory)
+        #[arg(long)]
+        path: Option<String>,
     },
 }
 
+#[derive(Args)]
+#[group(skip)]
+#[command(group(clap::ArgGroup::new("vm_target").required(true).args(["name", "all", "match_pattern"])))]
+pub struct VmTargetArgs {
+    /// VM name or ID
+    #[arg(short, long)]
+    pub name: Option<String>,
+    
+    /// Act on every VM defined on this host
+    #[arg(long)]
+    pub all: bool,
+    
+    /// Act on every VM whose name matches this glob (e.g. 'worker-*')
+    #[arg(long = "match")]
+    pub match_pattern: Option<String>,
+    
+    /// Skip confirmation prompt when --all/--match selects many VMs
+    #[arg(short = 'y', long)]
+    pub yes: bool,
+}
+
+/// Which GPUs an NVML test looks at; with neither option, all of them.
+#[derive(Args)]
+pub struct GpuSelectArgs {
+    /// GPU indices to check (comma-separated, e.g. "0,3")
+    #[arg(short, long, value_delimiter = ',')]
+    pub gpus: Option<Vec<u32>>,
+    
+    /// GPU UUIDs to check (comma-separated, as shown by nvidia-smi -L)
+    #[arg(long, value_delimiter = ',')]
+    pub uuid: Option<Vec<String>>,
+}
+
+#[derive(Args)]
+pub struct HashcatArgs {
+    /// Hashcat workload profile: 1=low, 2=default, 3=high, 4=nightmare
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=4))]
+    pub workload: Option<u8>,
+    
+    /// Pass --force to hashcat, ignoring its driver and device warnings
+    #[arg(long)]
+    pub force: bool,
+    
+    /// Don't read or write hashcat's potfile, so reruns crack the same hashes again
+    #[arg(long)]
+    pub no_potfile: bool,
+    
+    /// Use this potfile instead of hashcat's default
+    #[arg(long, conflicts_with = "no_potfile")]
+    pub potfile_path: Option<String>,
+}
+
+#[derive(Args)]
+pub struct K8sArgs {
+    /// Path to the kubeconfig file (defaults to kubectl's own lookup)
+    #[arg(long, global = true)]
+    pub kubeconfig: Option<String>,
+    
+    /// Kubeconfig context to use (defaults to the current context)
+    #[arg(long, global = true)]
+    pub context: Option<String>,
+    
+    #[command(subcommand)]
+    pub command: K8sCommands,
+}
+
 #[derive(Subcommand)]
 pub enum K8sCommands {
     /// List pods in namespace
     Pods {
-        /// Namespace (default: all namespaces)
+        /// Namespace (default: the current context's namespace)
         #[arg(short, long)]
         namespace: Option<String>,
         
@@ -363,14 +1071,14 @@ pub enum K8sCommands {
         #[arg(short = 'A', long)]
         all_namespaces: bool,
         
-        /// Output format (json, yaml, wide, or pretty)
+        /// Output format (json, yaml, wide, csv, or pretty)
         #[arg(short, long, default_value = "pretty")]
         format: String,
     },
     
     /// List deployments
     Deployments {
-        /// Namespace (default: all namespaces)
+        /// Namespace (default: the current context's namespace)
         #[arg(short, long)]
         namespace: Option<String>,
         
@@ -378,14 +1086,14 @@ pub enum K8sCommands {
         #[arg(short = 'A', long)]
         all_namespaces: bool,
         
-        /// Output format (json, yaml, wide, or pretty)
+        /// Output format (json, yaml, wide, csv, or pretty)
         #[arg(short, long, default_value = "pretty")]
         format: String,
     },
     
     /// List services
     Services {
-        /// Namespace (default: all namespaces)
+        /// Namespace (default: the current context's namespace)
         #[arg(short, long)]
         namespace: Option<String>,
         
@@ -412,15 +1120,30 @@ pub enum K8sCommands {
         format: String,
     },
     
-    /// Apply a configuration file
+    /// Show GPU capacity, allocatable, and requested counts per node
+    GpuNodes {
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+    
+    /// Apply a manifest file, directory, URL, or kustomization
     Apply {
-        /// Path to YAML/JSON manifest file
+        /// Path to YAML/JSON manifest file, directory of manifests, or URL
         #[arg(short, long)]
         file: String,
         
         /// Namespace to apply to
         #[arg(short, long)]
         namespace: Option<String>,
+        
+        /// Treat the path as a kustomize directory (kubectl apply -k)
+        #[arg(short, long)]
+        kustomize: bool,
+        
+        /// Validate without persisting changes (client or server)
+        #[arg(long, value_parser = ["client", "server"])]
+        dry_run: Option<String>,
     },
     
     /// Delete a resource
@@ -519,5 +1242,9 @@ pub enum K8sCommands {
         /// Namespace (not applicable for cluster-scoped resources)
         #[arg(short = 'N', long)]
         namespace: Option<String>,
+        
+        /// Output format (json, yaml, or pretty)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
     },
 }
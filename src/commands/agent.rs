@@ -0,0 +1,75 @@
+use crate::cli::Hypervisor;
+use crate::commands::vm::collect_vm_inventory;
+use crate::config::Config;
+use crate::hardware::collect_full_inventory;
+use crate::http::{api_url, check_response, inventory_idempotency_key, post_json};
+use crate::output::status;
+use serde::Serialize;
+
+/// Collect hardware (and optionally VM) inventory and post both to FarmCore.
+/// A failure in one stage is reported but does not stop the others.
+pub fn handle_agent_command(
+    url: &str,
+    hypervisor: Hypervisor,
+    skip_vms: bool,
+    path: Option<&str>,
+    vm_path: Option<&str>,
+    config: &Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut failures: Vec<String> = Vec::new();
+
+    status!("Collecting hardware inventory...");
+    let inventory = collect_full_inventory(false);
+    if let Err(e) = post_inventory(
+        config,
+        &api_url(url, &config.server_inventory_path, path),
+        &inventory,
+        &inventory_idempotency_key(&inventory),
+    ) {
+        eprintln!("✗ Hardware inventory: {}", e);
+        failures.push(format!("hardware inventory: {}", e));
+    }
+
+    if skip_vms {
+        status!("Skipping VM inventory (--skip-vms)");
+    } else {
+        status!("Collecting VM inventory...");
+        match collect_vm_inventory(hypervisor) {
+            Ok(vm_inventory) => {
+                if let Err(e) = post_inventory(
+                    config,
+                    &api_url(url, &config.vm_inventory_path, vm_path),
+                    &vm_inventory,
+                    &vm_inventory.idempotency_key(),
+                ) {
+                    eprintln!("✗ VM inventory: {}", e);
+                    failures.push(format!("VM inventory: {}", e));
+                }
+            }
+            Err(e) => {
+                eprintln!("✗ VM inventory collection failed: {}", e);
+                failures.push(format!("VM inventory collection: {}", e));
+            }
+        }
+    }
+
+    if failures.is_empty() {
+        status!("✓ All inventory posted");
+        Ok(())
+    } else {
+        Err(format!("{} stage(s) failed: {}", failures.len(), failures.join("; ")).into())
+    }
+}
+
+fn post_inventory<T: Serialize>(
+    config: &Config,
+    api_url: &str,
+    body: &T,
+    idempotency_key: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    status!("Posting to: {}", api_url);
+    let response = post_json(config, api_url, body, Some(idempotency_key))?;
+    check_response(response, config)?;
+    status!("✓ Posted to {}", api_url);
+    Ok(())
+}
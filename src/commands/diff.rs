@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::Path;
+
+use crate::commands::merge::read_inventories;
+use crate::hardware::types::{Inventory, InventoryChange, InventoryDiff};
+use crate::output::output_data;
+
+pub fn handle_diff_command(before: &str, after: &str, fail_on_change: bool, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let before = read_snapshot(before)?;
+    let after = read_snapshot(after)?;
+
+    let diff = diff_inventories(&before, &after);
+    output_data(&diff, format)?;
+
+    if fail_on_change && !diff.changes.is_empty() {
+        return Err(format!("{} change(s) between snapshots", diff.changes.len()).into());
+    }
+    Ok(())
+}
+
+fn read_snapshot(path: &str) -> Result<Inventory, Box<dyn std::error::Error>> {
+    let mut inventories = read_inventories(Path::new(path))?;
+    if inventories.len() != 1 {
+        return Err(format!("{}: expected one inventory, found {}", path, inventories.len()).into());
+    }
+    Ok(inventories.remove(0))
+}
+
+fn diff_inventories(before: &Inventory, after: &Inventory) -> InventoryDiff {
+    let mut diff = Differ::default();
+
+    let (b, a) = (&before.node, &after.node);
+    diff.field("node", "hostname", Some(&b.hostname), Some(&a.hostname));
+    diff.field("node", "serial_number", b.serial_number.as_ref(), a.serial_number.as_ref());
+    diff.field("node", "kernel_version", b.kernel_version.as_ref(), a.kernel_version.as_ref());
+    diff.field("node", "virtualization", b.virtualization.as_ref(), a.virtualization.as_ref());
+    diff.field("node", "bios.version", b.bios.as_ref().and_then(|x| x.version.as_ref()), a.bios.as_ref().and_then(|x| x.version.as_ref()));
+    diff.field("node", "bmc.firmware_version", b.bmc.as_ref().and_then(|x| x.firmware_version.as_ref()), a.bmc.as_ref().and_then(|x| x.firmware_version.as_ref()));
+
+    diff.field("cpu", "sockets", before.cpu.sockets, after.cpu.sockets);
+    diff.field("cpu", "cores", before.cpu.cores, after.cpu.cores);
+    diff.field("cpu", "threads", before.cpu.threads, after.cpu.threads);
+    diff.items("cpu", &before.cpu.cpus, &after.cpu.cpus, |c| Some(format!("socket {}", c.socket)), |d, key, b, a| {
+        d.field("cpu", &format!("{}.model_name", key), b.model_name.as_ref(), a.model_name.as_ref());
+    });
+
+    diff.field("memory", "total_bytes", before.memory.total_bytes, after.memory.total_bytes);
+    diff.items("memory", &before.memory.dimms, &after.memory.dimms, |m| m.slot.clone(), |d, key, b, a| {
+        d.field("memory", &format!("{}.size_bytes", key), b.size_bytes, a.size_bytes);
+        d.field("memory", &format!("{}.serial_number", key), b.serial_number.as_ref(), a.serial_number.as_ref());
+        d.field("memory", &format!("{}.speed_mt_s", key), b.speed_mt_s, a.speed_mt_s);
+    });
+
+    // Disks are matched by serial so a drive moved to another slot is not "new"
+    diff.items("disks", &before.disks, &after.disks, |d| d.serial.clone().or_else(|| Some(d.name.clone())), |d, key, b, a| {
+        d.field("disks", &format!("{}.name", key), Some(&b.name), Some(&a.name));
+        d.field("disks", &format!("{}.firmware_version", key), b.firmware_version.as_ref(), a.firmware_version.as_ref());
+        d.field("disks", &format!("{}.size_bytes", key), b.size_bytes, a.size_bytes);
+    });
+
+    diff.items("gpus", &before.gpus, &after.gpus, |g| g.uuid.clone().or_else(|| g.pci_address.clone()), |d, key, b, a| {
+        d.field("gpus", &format!("{}.model", key), b.model.as_ref(), a.model.as_ref());
+        d.field("gpus", &format!("{}.driver_version", key), b.driver_version.as_ref(), a.driver_version.as_ref());
+        d.field("gpus", &format!("{}.vram_mb", key), b.vram_mb, a.vram_mb);
+    });
+
+    diff.items("network", &before.network.interfaces, &after.network.interfaces, |n| Some(n.name.clone()), |d, key, b, a| {
+        d.field("network", &format!("{}.mac_address", key), b.mac_address.as_ref(), a.mac_address.as_ref());
+        d.field("network", &format!("{}.firmware_version", key), b.firmware_version.as_ref(), a.firmware_version.as_ref());
+        d.field("network", &format!("{}.driver_version", key), b.driver_version.as_ref(), a.driver_version.as_ref());
+        d.field("network", &format!("{}.speed_mbps", key), b.speed_mbps, a.speed_mbps);
+    });
+
+    diff.items("power_supplies", &before.power_supplies, &after.power_supplies, |p| p.serial_number.clone().or_else(|| p.name.clone()), |d, key, b, a| {
+        d.field("power_supplies", &format!("{}.model", key), b.model.as_ref(), a.model.as_ref());
+        d.field("power_supplies", &format!("{}.status", key), b.status.as_ref(), a.status.as_ref());
+    });
+
+    InventoryDiff {
+        before_collected_at: before.collected_at.clone(),
+        after_collected_at: after.collected_at.clone(),
+        changes: diff.changes,
+    }
+}
+
+#[derive(Default)]
+struct Differ {
+    changes: Vec<InventoryChange>,
+}
+
+impl Differ {
+    fn push(&mut self, subsystem: &str, change: &str, item: &str, before: Option<String>, after: Option<String>) {
+        self.changes.push(InventoryChange {
+            subsystem: subsystem.to_string(),
+            change: change.to_string(),
+            item: item.to_string(),
+            before,
+            after,
+        });
+    }
+
+    /// Record a scalar that differs between the snapshots.
+    fn field<T: Display + PartialEq>(&mut self, subsystem: &str, item: &str, before: Option<T>, after: Option<T>) {
+        if before != after {
+            self.push(subsystem, "changed", item, before.map(|v| v.to_string()), after.map(|v| v.to_string()));
+        }
+    }
+
+    /// Match devices by `key`, reporting added/removed ones and letting
+    /// `compare` look inside the ones present in both. Unkeyed items are skipped.
+    fn items<T>(
+        &mut self,
+        subsystem: &str,
+        before: &[T],
+        after: &[T],
+        key: impl Fn(&T) -> Option<String>,
+        compare: impl Fn(&mut Self, &str, &T, &T),
+    ) {
+        let before: BTreeMap<String, &T> = before.iter().filter_map(|item| Some((key(item)?, item))).collect();
+        let after: BTreeMap<String, &T> = after.iter().filter_map(|item| Some((key(item)?, item))).collect();
+
+        for (name, old) in &before {
+            match after.get(name) {
+                Some(new) => compare(self, name, old, new),
+                None => self.push(subsystem, "removed", name, Some(name.clone()), None),
+            }
+        }
+        for name in after.keys().filter(|name| !before.contains_key(*name)) {
+            self.push(subsystem, "added", name, None, Some(name.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::types::{DiskInfo, GpuInfo};
+
+    fn disk(name: &str, serial: &str, firmware: &str) -> DiskInfo {
+        DiskInfo {
+            name: name.to_string(),
+            serial: Some(serial.to_string()),
+            firmware_version: Some(firmware.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_identical_snapshots_have_no_changes() {
+        let mut inventory = Inventory::default();
+        inventory.disks.push(disk("nvme0n1", "S1", "1.0"));
+
+        assert!(diff_inventories(&inventory, &inventory).changes.is_empty());
+    }
+
+    #[test]
+    fn test_reports_added_removed_and_changed_devices() {
+        let mut before = Inventory::default();
+        before.memory.total_bytes = Some(256 << 30);
+        before.disks.push(disk("nvme0n1", "S1", "1.0"));
+        before.disks.push(disk("nvme1n1", "S2", "1.0"));
+        before.gpus.push(GpuInfo { uuid: Some("GPU-a".to_string()), ..Default::default() });
+
+        let mut after = Inventory::default();
+        after.memory.total_bytes = Some(512 << 30);
+        after.disks.push(disk("nvme0n1", "S1", "1.1"));
+        after.disks.push(disk("nvme1n1", "S3", "1.0"));
+        after.gpus.push(GpuInfo { uuid: Some("GPU-a".to_string()), ..Default::default() });
+
+        let changes = diff_inventories(&before, &after).changes;
+        let summary: Vec<(&str, &str, &str)> = changes
+            .iter()
+            .map(|c| (c.subsystem.as_str(), c.change.as_str(), c.item.as_str()))
+            .collect();
+
+        assert_eq!(summary, vec![
+            ("memory", "changed", "total_bytes"),
+            ("disks", "changed", "S1.firmware_version"),
+            ("disks", "removed", "S2"),
+            ("disks", "added", "S3"),
+        ]);
+        assert_eq!(changes[1].before.as_deref(), Some("1.0"));
+        assert_eq!(changes[1].after.as_deref(), Some("1.1"));
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt;
+
+use crate::hardware::types::{ExpectationResult, Inventory, InventoryValidation};
+
+/// One rule from `hardware inventory --expect`, e.g. "gpus=8" or "disks>=4".
+#[derive(Debug, PartialEq)]
+pub(crate) struct Expectation {
+    key: String,
+    op: Op,
+    value: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ge,
+    Le,
+    Gt,
+    Lt,
+}
+
+impl Op {
+    fn holds(self, actual: u64, expected: u64) -> bool {
+        match self {
+            Op::Eq => actual == expected,
+            Op::Ge => actual >= expected,
+            Op::Le => actual <= expected,
+            Op::Gt => actual > expected,
+            Op::Lt => actual < expected,
+        }
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Op::Eq => "=",
+            Op::Ge => ">=",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Lt => "<",
+        })
+    }
+}
+
+/// Countable parts of an inventory that a rule can refer to.
+const KEYS: &[&str] = &["gpus", "dimms", "disks", "nics", "psus", "fans", "sockets", "cores", "threads", "memory_gb"];
+
+/// Parse rules of the form `<key><op><count>` where op is =, ==, >=, <=, > or <.
+pub(crate) fn parse_expectations(rules: &[String]) -> Result<Vec<Expectation>, String> {
+    rules
+        .iter()
+        .map(|rule| rule.trim())
+        .filter(|rule| !rule.is_empty())
+        .map(parse_expectation)
+        .collect()
+}
+
+fn parse_expectation(rule: &str) -> Result<Expectation, String> {
+    let split = rule
+        .find(['=', '<', '>'])
+        .ok_or_else(|| format!("invalid expectation '{}': expected <key><op><count>, e.g. gpus=8", rule))?;
+    let (key, rest) = rule.split_at(split);
+    let key = key.trim().to_lowercase();
+
+    let (op, value) = [("==", Op::Eq), (">=", Op::Ge), ("<=", Op::Le), ("=", Op::Eq), (">", Op::Gt), ("<", Op::Lt)]
+        .iter()
+        .find_map(|(token, op)| rest.strip_prefix(token).map(|value| (*op, value.trim())))
+        .ok_or_else(|| format!("invalid operator in expectation '{}'", rule))?;
+
+    if !KEYS.contains(&key.as_str()) {
+        return Err(format!("unknown key '{}' in expectation '{}' (known: {})", key, rule, KEYS.join(", ")));
+    }
+    let value = value
+        .parse()
+        .map_err(|_| format!("invalid count '{}' in expectation '{}'", value, rule))?;
+
+    Ok(Expectation { key, op, value })
+}
+
+/// Check every rule against the inventory.
+pub(crate) fn check_expectations(inventory: &Inventory, expectations: &[Expectation]) -> InventoryValidation {
+    let results: Vec<ExpectationResult> = expectations
+        .iter()
+        .map(|expectation| {
+            let actual = count(inventory, &expectation.key);
+            ExpectationResult {
+                rule: format!("{}{}{}", expectation.key, expectation.op, expectation.value),
+                actual,
+                passed: expectation.op.holds(actual, expectation.value),
+            }
+        })
+        .collect();
+
+    InventoryValidation {
+        hostname: inventory.node.hostname.clone(),
+        passed: results.iter().all(|r| r.passed),
+        results,
+    }
+}
+
+fn count(inventory: &Inventory, key: &str) -> u64 {
+    match key {
+        "gpus" => inventory.gpus.len() as u64,
+        "dimms" => inventory.memory.dimms.iter().filter(|d| d.size_bytes.is_some()).count() as u64,
+        "disks" => inventory.disks.len() as u64,
+        // Physical NICs only; loopback, bridges and veths have no PCI address
+        "nics" => inventory.network.interfaces.iter().filter(|n| n.pci_address.is_some()).count() as u64,
+        "psus" => inventory.power_supplies.len() as u64,
+        "fans" => inventory.fans.len() as u64,
+        "sockets" => inventory.cpu.sockets.unwrap_or(0) as u64,
+        "cores" => inventory.cpu.cores.unwrap_or(0) as u64,
+        "threads" => inventory.cpu.threads.unwrap_or(0) as u64,
+        "memory_gb" => inventory.memory.total_bytes.unwrap_or(0) >> 30,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::types::{DimmInfo, DiskInfo, GpuInfo, MemoryInfo};
+
+    fn rules(spec: &str) -> Vec<String> {
+        spec.split(',').map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_expectations() {
+        let parsed = parse_expectations(&rules("gpus=8, disks>=4,dimms==16,fans<10")).unwrap();
+        assert_eq!(parsed, vec![
+            Expectation { key: "gpus".to_string(), op: Op::Eq, value: 8 },
+            Expectation { key: "disks".to_string(), op: Op::Ge, value: 4 },
+            Expectation { key: "dimms".to_string(), op: Op::Eq, value: 16 },
+            Expectation { key: "fans".to_string(), op: Op::Lt, value: 10 },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_expectations_rejects_bad_rules() {
+        assert!(parse_expectations(&rules("gpus8")).is_err());
+        assert!(parse_expectations(&rules("widgets=2")).is_err());
+        assert!(parse_expectations(&rules("gpus=eight")).is_err());
+        assert!(parse_expectations(&rules("gpus=>8")).is_err());
+    }
+
+    #[test]
+    fn test_check_expectations_reports_each_rule() {
+        let inventory = Inventory {
+            gpus: (0..7).map(|_| GpuInfo::default()).collect(),
+            disks: (0..4).map(|_| DiskInfo::default()).collect(),
+            memory: MemoryInfo {
+                dimms: vec![
+                    DimmInfo { size_bytes: Some(32 << 30), ..Default::default() },
+                    DimmInfo::default(), // empty slot
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let validation = check_expectations(&inventory, &parse_expectations(&rules("gpus=8,disks>=4,dimms=1")).unwrap());
+
+        assert!(!validation.passed);
+        let outcome: Vec<(&str, u64, bool)> = validation.results.iter().map(|r| (r.rule.as_str(), r.actual, r.passed)).collect();
+        assert_eq!(outcome, vec![("gpus=8", 7, false), ("disks>=4", 4, true), ("dimms=1", 1, true)]);
+    }
+}
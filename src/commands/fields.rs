@@ -0,0 +1,113 @@
+use serde_json::{Map, Value};
+
+/// Keep only the dotted `paths` of `value` (`hardware inventory --fields`).
+/// A path through an array applies to every element, so "gpus.model" yields
+/// one model per GPU. Returns the projection and the paths that matched nothing.
+pub(crate) fn project_fields(value: &Value, paths: &[String]) -> (Value, Vec<String>) {
+    let mut projected = Value::Object(Map::new());
+    let mut unknown = Vec::new();
+
+    for path in paths.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        let segments: Vec<&str> = path.split('.').collect();
+        match project(value, &segments) {
+            Some(subtree) => merge(&mut projected, subtree),
+            None => unknown.push(path.to_string()),
+        }
+    }
+
+    (projected, unknown)
+}
+
+fn project(value: &Value, path: &[&str]) -> Option<Value> {
+    let Some((key, rest)) = path.split_first() else {
+        return Some(value.clone());
+    };
+
+    match value {
+        Value::Object(map) => {
+            let subtree = project(map.get(*key)?, rest)?;
+            Some(Value::Object(Map::from_iter([(key.to_string(), subtree)])))
+        }
+        Value::Array(items) => {
+            let projected: Vec<Option<Value>> = items.iter().map(|item| project(item, path)).collect();
+            if !items.is_empty() && projected.iter().all(Option::is_none) {
+                return None;
+            }
+            Some(Value::Array(projected
+                .into_iter()
+                .map(|p| p.unwrap_or_else(|| Value::Object(Map::new())))
+                .collect()))
+        }
+        // A field that exists but is unset, e.g. "node.bmc.firmware_version" with no BMC
+        Value::Null => Some(Value::Null),
+        _ => None,
+    }
+}
+
+/// Fold `src` into `dst`, so "gpus.model" and "gpus.uuid" land in the same objects.
+fn merge(dst: &mut Value, src: Value) {
+    match (dst, src) {
+        (Value::Object(dst), Value::Object(src)) => {
+            for (key, value) in src {
+                match dst.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        dst.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Array(dst), Value::Array(src)) => {
+            for (existing, value) in dst.iter_mut().zip(src) {
+                merge(existing, value);
+            }
+        }
+        (dst, src) => *dst = src,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn paths(spec: &str) -> Vec<String> {
+        spec.split(',').map(|s| s.to_string()).collect()
+    }
+
+    fn inventory() -> Value {
+        json!({
+            "node": { "hostname": "gpu01", "serial_number": "SN1", "bmc": null },
+            "memory": { "total_bytes": 1024, "dimms": [] },
+            "gpus": [
+                { "model": "H100", "uuid": "GPU-a", "vram_mb": 81559 },
+                { "model": "H100", "uuid": "GPU-b", "vram_mb": 81559 }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_project_fields_keeps_requested_subtrees() {
+        let (projected, unknown) = project_fields(&inventory(), &paths("node.serial_number,gpus.model,gpus.uuid,memory.total_bytes"));
+        assert!(unknown.is_empty());
+        assert_eq!(projected, json!({
+            "node": { "serial_number": "SN1" },
+            "memory": { "total_bytes": 1024 },
+            "gpus": [
+                { "model": "H100", "uuid": "GPU-a" },
+                { "model": "H100", "uuid": "GPU-b" }
+            ]
+        }));
+    }
+
+    #[test]
+    fn test_project_fields_reports_unknown_paths() {
+        let (projected, unknown) = project_fields(&inventory(), &paths("node.serial,gpus.vendor_id,node.bmc.firmware_version,memory.dimms.size"));
+        assert_eq!(unknown, vec!["node.serial", "gpus.vendor_id"]);
+        // Unset and empty parents are not typos
+        assert_eq!(projected, json!({
+            "node": { "bmc": null },
+            "memory": { "dimms": [] }
+        }));
+    }
+}
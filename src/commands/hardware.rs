@@ -3,23 +3,74 @@ use crate::hardware::{
     collect_full_inventory,
     collect_memory_info,
     collect_cpu_info,
+    collect_cpu_freq_info,
     collect_network_info,
     collect_disks,
+    collect_filesystems,
     collect_node_info,
     collect_power_supplies,
+    collect_total_power,
+    collect_power_cap,
+    set_power_cap,
+    collect_pci_devices,
+    collect_usb_devices,
+    collect_fans,
+    collect_temperatures,
+    collect_dmi_structures,
+    collect_sel,
+    clear_sel,
+    chassis_identify,
+    chassis_power,
+    boot_device,
+    set_boot_device,
+    ipmi_host,
 };
-use crate::output::output_data;
+use crate::commands::expect::{check_expectations, parse_expectations};
+use crate::commands::fields::project_fields;
+use crate::config::Config;
+use crate::http::{api_url, check_response, inventory_idempotency_key, post_json};
+use crate::hardware::types::{PowerReport, PowerSample};
+use crate::output::{confirm_action, output_data, output_is_terminal, print_warning, status};
+use std::process::Command;
+use std::time::{Duration, Instant};
 
-pub fn handle_hardware_command(cmd: &HardwareCommands) -> Result<(), Box<dyn std::error::Error>> {
+/// How long `disk-test --wait` waits when the drive gives no estimate.
+const SELF_TEST_DEFAULT_MAX_WAIT_MINUTES: u64 = 24 * 60;
+
+pub fn handle_hardware_command(cmd: &HardwareCommands, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
     match cmd {
-        HardwareCommands::Inventory { format } => {
-            let inventory = collect_full_inventory();
-            output_data(&inventory, format)?;
+        HardwareCommands::Inventory { timings, expect, fields, format } => {
+            // Reject a bad spec before spending time on collection
+            let expectations = parse_expectations(expect)?;
+            let inventory = collect_full_inventory(*timings);
+            if !fields.is_empty() {
+                let (projected, unknown) = project_fields(&serde_json::to_value(&inventory)?, fields);
+                for path in unknown {
+                    print_warning(&format!("--fields: '{}' does not match anything in the inventory", path));
+                }
+                output_data(&projected, format)?;
+                return Ok(());
+            }
+            if expectations.is_empty() {
+                output_data(&inventory, format)?;
+                return Ok(());
+            }
+
+            let validation = check_expectations(&inventory, &expectations);
+            output_data(&validation, format)?;
+            let failed = validation.results.iter().filter(|r| !r.passed).count();
+            if failed > 0 {
+                return Err(format!("{} of {} hardware expectation(s) not met", failed, validation.results.len()).into());
+            }
         }
         HardwareCommands::Cpu { format } => {
             let cpu_info = collect_cpu_info();
             output_data(&cpu_info, format)?;
         }
+        HardwareCommands::CpuFreq { format } => {
+            let cpu_freq_info = collect_cpu_freq_info();
+            output_data(&cpu_freq_info, format)?;
+        }
         HardwareCommands::Memory { format } => {
             let memory_info = collect_memory_info();
             output_data(&memory_info, format)?;
@@ -28,6 +79,80 @@ pub fn handle_hardware_command(cmd: &HardwareCommands) -> Result<(), Box<dyn std
             let storage_info = collect_disks();
             output_data(&storage_info, format)?;
         }
+        HardwareCommands::Filesystems { include_tmpfs, format } => {
+            let filesystems = collect_filesystems(*include_tmpfs);
+            output_data(&filesystems, format)?;
+        }
+        HardwareCommands::LocateDisk { device, off } => {
+            locate_disk(device, !*off)?;
+        }
+        HardwareCommands::DiskTest { device, test_type, wait, max_wait } => {
+            run_disk_self_test(device, test_type, *wait, *max_wait)?;
+        }
+        HardwareCommands::Pci { format } => {
+            let devices = collect_pci_devices();
+            output_data(&devices, format)?;
+        }
+        HardwareCommands::Usb { include_hubs, format } => {
+            let devices = collect_usb_devices(*include_hubs);
+            output_data(&devices, format)?;
+        }
+        HardwareCommands::Fans { format } => {
+            let fans = collect_fans();
+            output_data(&fans, format)?;
+        }
+        HardwareCommands::Temps { format } => {
+            let temps = collect_temperatures();
+            output_data(&temps, format)?;
+        }
+        HardwareCommands::Dmi { dmi_type, format } => {
+            let structures = collect_dmi_structures(*dmi_type)?;
+            output_data(&structures, format)?;
+        }
+        HardwareCommands::Sel { format, clear, yes } => {
+            let entries = collect_sel()?;
+            output_data(&entries, format)?;
+            
+            if *clear {
+                if !*yes && !confirm_action(&format!("This will clear all {} SEL entries from the BMC.", entries.len())) {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+                clear_sel()?;
+                status!("✓ SEL cleared");
+            }
+        }
+        HardwareCommands::Chassis { action, duration, yes } => {
+            let bmc_name = ipmi_host().unwrap_or("this node");
+            if action == "identify" {
+                chassis_identify(*duration)?;
+                match duration {
+                    Some(0) => status!("✓ Identify LED off on {}", bmc_name),
+                    Some(seconds) => status!("✓ Identify LED on for {}s on {}", seconds, bmc_name),
+                    None => status!("✓ Identify LED on for 15s on {}", bmc_name),
+                }
+                return Ok(());
+            }
+            
+            if matches!(action.as_str(), "off" | "cycle" | "reset")
+                && !*yes
+                && !confirm_action(&format!("This will power {} {} without a clean OS shutdown.", action, bmc_name))
+            {
+                println!("Cancelled.");
+                return Ok(());
+            }
+            let reply = chassis_power(action)?;
+            status!("✓ {}", reply);
+        }
+        HardwareCommands::BootDevice { set, persistent, format } => {
+            if let Some(device) = set {
+                set_boot_device(device, *persistent)?;
+                let scope = if *persistent { "all future boots" } else { "the next boot" };
+                status!("✓ Boot device set to {} for {}", device, scope);
+            }
+            let info = boot_device()?;
+            output_data(&info, format)?;
+        }
         HardwareCommands::Network { format } => {
             let network_info = collect_network_info();
             output_data(&network_info, format)?;
@@ -36,35 +161,309 @@ pub fn handle_hardware_command(cmd: &HardwareCommands) -> Result<(), Box<dyn std
             let node_info = collect_node_info();
             output_data(&node_info, format)?;
         }
-        HardwareCommands::Power { format } => {
-            let power_info = collect_power_supplies();
-            output_data(&power_info, format)?;
+        HardwareCommands::Power { format, watch: Some(interval), count } => {
+            watch_power(*interval, *count, format)?;
         }
-        HardwareCommands::PostInventory { url } => {
-            println!("Collecting hardware inventory...");
-            let inventory = collect_full_inventory();
-            
-            let api_url = format!("{}/api/v1/servers/inventory", url.trim_end_matches('/'));
-            println!("Posting inventory to: {}", api_url);
+        HardwareCommands::Power { format, .. } => {
+            let power_supplies = collect_power_supplies();
+            let report = PowerReport {
+                total_power_watts: collect_total_power(&power_supplies),
+                power_supplies,
+            };
+            output_data(&report, format)?;
+        }
+        HardwareCommands::PowerCap { set, yes, format } => {
+            if let Some(watts) = set {
+                let current = collect_power_cap()?;
+                let current = match (current.active, current.limit_watts) {
+                    (true, Some(limit)) => format!("currently {} W", limit),
+                    _ => "currently uncapped".to_string(),
+                };
+                if !*yes && !confirm_action(&format!("This will cap node power at {} W ({}); the BMC may throttle or power off the node to hold it.", watts, current)) {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+                set_power_cap(*watts)?;
+                status!("✓ Power cap set to {} W", watts);
+            }
+            let cap = collect_power_cap()?;
+            output_data(&cap, format)?;
+        }
+        HardwareCommands::PostInventory { url, timings, path } => {
+            status!("Collecting hardware inventory...");
+            let inventory = collect_full_inventory(*timings);
             
-            let client = reqwest::blocking::Client::new();
-            let response = client
-                .post(&api_url)
-                .json(&inventory)
-                .send()?;
+            let api_url = api_url(url, &config.server_inventory_path, path.as_deref());
+            status!("Posting inventory to: {}", api_url);
             
-            if response.status().is_success() {
-                let result: serde_json::Value = response.json()?;
-                println!("✓ Success!");
-                println!("{}", serde_json::to_string_pretty(&result)?);
-            } else {
-                let status = response.status();
-                let error_text = response.text()?;
-                eprintln!("✗ Error: HTTP {}", status);
-                eprintln!("{}", error_text);
-                return Err(format!("Failed to post inventory: HTTP {}", status).into());
-            }
+            let response = post_json(config, &api_url, &inventory, Some(&inventory_idempotency_key(&inventory)))?;
+            let result = check_response(response, config)
+                .map_err(|e| format!("Failed to post inventory: {}", e))?;
+            status!("✓ Success!");
+            println!("{}", serde_json::to_string_pretty(&result)?);
         }
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Print a power reading every `interval` seconds, redrawing the screen in
+/// pretty mode on a terminal and appending timestamped records otherwise.
+/// Appended records are always ndjson, so the stream stays parseable.
+fn watch_power(interval: u64, count: Option<u32>, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if format == "yaml" {
+        return Err("--watch streams one JSON record per sample; use -f json or ndjson".into());
+    }
+    let redraw = format == "pretty" && output_is_terminal();
+    let format = if redraw { format } else { "ndjson" };
+    let mut sample = 0;
+    loop {
+        sample += 1;
+        let power_supplies = collect_power_supplies();
+        let reading = PowerSample {
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            sample,
+            total_power_watts: collect_total_power(&power_supplies),
+            power_supplies,
+        };
+
+        if redraw {
+            print!("\x1b[2J\x1b[H");
+            status!("Power supplies every {}s (Ctrl-C to stop)\n", interval);
+        }
+        output_data(&reading, format)?;
+
+        if count.is_some_and(|n| sample >= n) {
+            return Ok(());
+        }
+        std::thread::sleep(std::time::Duration::from_secs(interval.max(1)));
+    }
+}
+
+fn locate_disk(device: &str, on: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if !Command::new("which")
+        .arg("ledctl")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+    {
+        return Err("ledctl not found. Please install ledmon (e.g., apt-get install ledmon).".into());
+    }
+
+    let dev_path = if device.starts_with("/dev/") {
+        device.to_string()
+    } else {
+        format!("/dev/{}", device)
+    };
+
+    if !std::path::Path::new(&dev_path).exists() {
+        return Err(format!("Disk device not found: {}", dev_path).into());
+    }
+
+    let pattern = if on { "locate" } else { "locate_off" };
+    status!("{} locate LED for {}...", if on { "Enabling" } else { "Disabling" }, dev_path);
+
+    let output = Command::new("ledctl")
+        .arg(format!("{}={}", pattern, dev_path))
+        .output()?;
+
+    if output.status.success() {
+        status!("✓ Locate LED {} for {}", if on { "on" } else { "off" }, dev_path);
+    } else {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to set locate LED: {}", error.trim()).into());
+    }
+
+    Ok(())
+}
+
+fn run_disk_self_test(device: &str, test_type: &str, wait: bool, max_wait: Option<u64>) -> Result<(), Box<dyn std::error::Error>> {
+    if !Command::new("which")
+        .arg("smartctl")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+    {
+        return Err("smartctl not found. Please install smartmontools (e.g., apt-get install smartmontools).".into());
+    }
+
+    let dev_path = if device.starts_with("/dev/") {
+        device.to_string()
+    } else {
+        format!("/dev/{}", device)
+    };
+
+    if !std::path::Path::new(&dev_path).exists() {
+        return Err(format!("Disk device not found: {}", dev_path).into());
+    }
+
+    status!("Starting {} SMART self-test on {}...", test_type, dev_path);
+
+    let output = Command::new("smartctl")
+        .args(["-t", test_type, &dev_path])
+        .output()?;
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    if !text.contains("Testing has begun") && !text.contains("Self-test has begun") && !text.contains("test has begun") {
+        let detail = text
+            .lines()
+            .chain(String::from_utf8_lossy(&output.stderr).lines())
+            .rfind(|l| !l.trim().is_empty())
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        return Err(format!("Failed to start self-test: {}", detail).into());
+    }
+
+    status!("✓ {} self-test started on {}", test_type, dev_path);
+
+    // e.g. "Please wait 2 minutes for test to complete." / "Test will complete after Tue Oct 14 10:22:41 2025"
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with("Please wait") || line.starts_with("Test will complete after") {
+            println!("  {}", line);
+        }
+    }
+
+    if !wait {
+        status!("Check progress with: smartctl -c -l selftest {}", dev_path);
+        return Ok(());
+    }
+
+    let max_wait = max_wait.unwrap_or_else(|| {
+        self_test_estimate_minutes(&text).map_or(SELF_TEST_DEFAULT_MAX_WAIT_MINUTES, |minutes| minutes * 2 + 10)
+    });
+    let deadline = Instant::now() + Duration::from_secs(max_wait * 60);
+
+    status!("Waiting up to {} minutes for self-test to complete...", max_wait);
+    loop {
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Gave up waiting after {} minutes; the self-test keeps running on the drive. Check with: smartctl -l selftest {}",
+                max_wait, dev_path
+            )
+            .into());
+        }
+        std::thread::sleep(Duration::from_secs(30));
+
+        let output = Command::new("smartctl")
+            .args(["-c", "-l", "selftest", "--json=c", &dev_path])
+            .output()?;
+        // A drive busy testing can answer with a truncated or empty document;
+        // treat that as "still running" and ask again next round
+        let Ok(json) = serde_json::from_slice::<serde_json::Value>(&output.stdout) else {
+            continue;
+        };
+
+        match self_test_progress(&json) {
+            SelfTestProgress::Running(remaining) => {
+                if let Some(remaining) = remaining {
+                    status!("  {}% remaining", remaining);
+                }
+            }
+            SelfTestProgress::Passed => {
+                status!("✓ Self-test PASSED on {}", dev_path);
+                return Ok(());
+            }
+            SelfTestProgress::Failed(status) => {
+                return Err(format!("Self-test FAILED on {}: {}", dev_path, status).into());
+            }
+            SelfTestProgress::Unknown => {
+                println!("Self-test finished; result could not be determined. Check with: smartctl -l selftest {}", dev_path);
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// The drive's own estimate from `smartctl -t`: "Please wait 2 minutes for test to complete."
+fn self_test_estimate_minutes(text: &str) -> Option<u64> {
+    text.lines()
+        .find_map(|line| line.trim().strip_prefix("Please wait "))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|minutes| minutes.parse().ok())
+}
+
+#[derive(Debug, PartialEq)]
+enum SelfTestProgress {
+    Running(Option<u64>), // percent remaining, when reported
+    Passed,
+    Failed(String),
+    Unknown,
+}
+
+/// Interpret `smartctl -c -l selftest --json` for ATA and NVMe drives.
+fn self_test_progress(json: &serde_json::Value) -> SelfTestProgress {
+    // ATA: capabilities report the running test, the log holds finished ones
+    if let Some(remaining) = json.pointer("/ata_smart_data/self_test/status/remaining_percent") {
+        return SelfTestProgress::Running(remaining.as_u64());
+    }
+    if let Some(latest) = json.pointer("/ata_smart_self_test_log/standard/table/0") {
+        let status = latest.pointer("/status/string").and_then(|v| v.as_str()).unwrap_or("unknown").to_string();
+        return match latest.pointer("/status/passed").and_then(|v| v.as_bool()) {
+            Some(true) => SelfTestProgress::Passed,
+            Some(false) => SelfTestProgress::Failed(status),
+            None => SelfTestProgress::Unknown,
+        };
+    }
+
+    // NVMe: a non-zero current operation means a test is still running
+    if let Some(log) = json.get("nvme_self_test_log") {
+        if log.pointer("/current_self_test_operation/value").and_then(|v| v.as_u64()).unwrap_or(0) != 0 {
+            return SelfTestProgress::Running(log.get("current_self_test_completion_percent").and_then(|v| v.as_u64()).map(|done| 100u64.saturating_sub(done)));
+        }
+        if let Some(latest) = log.pointer("/table/0/self_test_result") {
+            let status = latest.get("string").and_then(|v| v.as_str()).unwrap_or("unknown").to_string();
+            return match latest.get("value").and_then(|v| v.as_u64()) {
+                Some(0) => SelfTestProgress::Passed,
+                Some(_) => SelfTestProgress::Failed(status),
+                None => SelfTestProgress::Unknown,
+            };
+        }
+    }
+
+    SelfTestProgress::Unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_estimate_minutes() {
+        let text = "=== START OF OFFLINE IMMEDIATE AND SELF-TEST SECTION ===\nSending command: \"Execute SMART Extended self-test routine immediately in off-line mode\".\nDrive command \"Execute SMART Extended self-test routine immediately in off-line mode\" successful.\nTesting has begun.\nPlease wait 255 minutes for test to complete.\nTest will complete after Tue Oct 14 14:37:41 2025\n";
+
+        assert_eq!(self_test_estimate_minutes(text), Some(255));
+        assert_eq!(self_test_estimate_minutes("Self-test has begun\n"), None);
+    }
+
+    #[test]
+    fn test_self_test_progress() {
+        let ata_running = serde_json::json!({
+            "ata_smart_data": {"self_test": {"status": {"value": 249, "string": "in progress, 90% remaining", "remaining_percent": 90}}},
+            "ata_smart_self_test_log": {"standard": {"table": [{"status": {"value": 0, "string": "Completed without error", "passed": true}}]}}
+        });
+        assert_eq!(self_test_progress(&ata_running), SelfTestProgress::Running(Some(90)));
+
+        let ata_passed = serde_json::json!({
+            "ata_smart_data": {"self_test": {"status": {"value": 0, "string": "completed without error", "passed": true}}},
+            "ata_smart_self_test_log": {"standard": {"table": [{"status": {"value": 0, "string": "Completed without error", "passed": true}}]}}
+        });
+        assert_eq!(self_test_progress(&ata_passed), SelfTestProgress::Passed);
+
+        let nvme_running = serde_json::json!({
+            "nvme_self_test_log": {
+                "current_self_test_operation": {"value": 2, "string": "Extended self-test in progress"},
+                "current_self_test_completion_percent": 35
+            }
+        });
+        assert_eq!(self_test_progress(&nvme_running), SelfTestProgress::Running(Some(65)));
+
+        let nvme_failed = serde_json::json!({
+            "nvme_self_test_log": {
+                "current_self_test_operation": {"value": 0, "string": "No self-test in progress"},
+                "table": [{"self_test_code": {"value": 1}, "self_test_result": {"value": 7, "string": "Completed: failed segments"}}]
+            }
+        });
+        assert_eq!(self_test_progress(&nvme_failed), SelfTestProgress::Failed("Completed: failed segments".to_string()));
+    }
+}
@@ -1,117 +1,274 @@
-use crate::cli::K8sCommands;
-use crate::output::output_data;
+use crate::cli::{K8sArgs, K8sCommands};
+use crate::output::{output_data, require_structured_output, status};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::io::{self, Write};
+use std::path::Path;
 use std::process::Command;
 
-pub fn handle_k8s_command(cmd: &K8sCommands) -> Result<(), Box<dyn std::error::Error>> {
-    match cmd {
+// kubectl invocation with the cluster-selection flags applied to every call
+struct Kubectl {
+    global_args: Vec<String>,
+}
+
+impl Kubectl {
+    fn new(kubeconfig: Option<&str>, context: Option<&str>) -> Self {
+        let mut global_args = Vec::new();
+        if let Some(path) = kubeconfig {
+            global_args.push(format!("--kubeconfig={}", path));
+        }
+        if let Some(name) = context {
+            global_args.push(format!("--context={}", name));
+        }
+        Kubectl { global_args }
+    }
+    
+    fn command(&self) -> Command {
+        let mut cmd = Command::new("kubectl");
+        cmd.args(&self.global_args);
+        cmd
+    }
+}
+
+pub fn handle_k8s_command(args: &K8sArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let kubectl = Kubectl::new(args.kubeconfig.as_deref(), args.context.as_deref());
+    
+    match &args.command {
         K8sCommands::Pods { namespace, all_namespaces, format } => {
-            list_pods(namespace.as_deref(), *all_namespaces, format)?;
+            list_pods(&kubectl, namespace.as_deref(), *all_namespaces, format)?;
         }
         
         K8sCommands::Deployments { namespace, all_namespaces, format } => {
-            list_deployments(namespace.as_deref(), *all_namespaces, format)?;
+            list_deployments(&kubectl, namespace.as_deref(), *all_namespaces, format)?;
         }
         
         K8sCommands::Services { namespace, all_namespaces, format } => {
-            list_services(namespace.as_deref(), *all_namespaces, format)?;
+            list_services(&kubectl, namespace.as_deref(), *all_namespaces, format)?;
         }
         
         K8sCommands::Nodes { format } => {
-            list_nodes(format)?;
+            list_nodes(&kubectl, format)?;
         }
         
         K8sCommands::Namespaces { format } => {
-            list_namespaces(format)?;
+            list_namespaces(&kubectl, format)?;
         }
         
-        K8sCommands::Apply { file, namespace } => {
-            apply_manifest(file, namespace.as_deref())?;
+        K8sCommands::GpuNodes { format } => {
+            let nodes = collect_gpu_nodes(&kubectl)?;
+            output_data(&nodes, format)?;
+        }
+        
+        K8sCommands::Apply { file, namespace, kustomize, dry_run } => {
+            apply_manifest(&kubectl, file, namespace.as_deref(), *kustomize, dry_run.as_deref())?;
         }
         
         K8sCommands::Delete { resource_type, name, namespace, yes } => {
-            delete_resource(resource_type, name, namespace.as_deref(), *yes)?;
+            delete_resource(&kubectl, resource_type, name, namespace.as_deref(), *yes)?;
         }
         
         K8sCommands::Scale { name, replicas, namespace } => {
-            scale_deployment(name, *replicas, namespace.as_deref())?;
+            scale_deployment(&kubectl, name, *replicas, namespace.as_deref())?;
         }
         
         K8sCommands::Logs { name, namespace, container, follow, tail } => {
-            get_logs(name, namespace.as_deref(), container.as_deref(), *follow, *tail)?;
+            get_logs(&kubectl, name, namespace.as_deref(), container.as_deref(), *follow, *tail)?;
         }
         
         K8sCommands::Exec { name, namespace, container, command } => {
-            exec_in_pod(name, namespace.as_deref(), container.as_deref(), command)?;
+            exec_in_pod(&kubectl, name, namespace.as_deref(), container.as_deref(), command)?;
         }
         
         K8sCommands::ClusterInfo { format } => {
-            cluster_info(format)?;
+            cluster_info(&kubectl, format)?;
         }
         
-        K8sCommands::Describe { resource_type, name, namespace } => {
-            describe_resource(resource_type, name, namespace.as_deref())?;
+        K8sCommands::Describe { resource_type, name, namespace, format } => {
+            if format == "pretty" {
+                require_structured_output()?;
+                describe_resource(&kubectl, resource_type, name, namespace.as_deref())?;
+            } else {
+                let summary = describe_resource_structured(&kubectl, resource_type, name, namespace.as_deref())?;
+                output_data(&summary, format)?;
+            }
         }
     }
     Ok(())
 }
 
-fn list_pods(namespace: Option<&str>, all_namespaces: bool, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+fn list_pods(kubectl: &Kubectl, namespace: Option<&str>, all_namespaces: bool, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let ns_args = namespace_args(namespace, all_namespaces, || current_namespace(kubectl));
     let mut args = vec!["get", "pods"];
-    
-    if all_namespaces {
-        args.push("--all-namespaces");
-    } else if let Some(ns) = namespace {
-        args.push("-n");
-        args.push(ns);
-    } else {
-        args.push("--all-namespaces");
-    }
+    args.extend(ns_args.iter().map(String::as_str));
     
     match format {
         "json" => args.push("-o=json"),
         "yaml" => args.push("-o=yaml"),
         "wide" => args.push("-o=wide"),
-        _ => {} // default table format
+        _ => return print_resource_table(kubectl, &args, all_namespaces, format, POD_COLUMNS, pod_row),
     }
     
-    execute_kubectl(&args, format)
+    execute_kubectl(kubectl, &args, format)
 }
 
-fn list_deployments(namespace: Option<&str>, all_namespaces: bool, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+fn list_deployments(kubectl: &Kubectl, namespace: Option<&str>, all_namespaces: bool, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let ns_args = namespace_args(namespace, all_namespaces, || current_namespace(kubectl));
     let mut args = vec!["get", "deployments"];
-    
-    if all_namespaces {
-        args.push("--all-namespaces");
-    } else if let Some(ns) = namespace {
-        args.push("-n");
-        args.push(ns);
-    } else {
-        args.push("--all-namespaces");
-    }
+    args.extend(ns_args.iter().map(String::as_str));
     
     match format {
         "json" => args.push("-o=json"),
         "yaml" => args.push("-o=yaml"),
         "wide" => args.push("-o=wide"),
-        _ => {}
+        _ => return print_resource_table(kubectl, &args, all_namespaces, format, DEPLOYMENT_COLUMNS, deployment_row),
     }
     
-    execute_kubectl(&args, format)
+    execute_kubectl(kubectl, &args, format)
 }
 
-fn list_services(namespace: Option<&str>, all_namespaces: bool, format: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut args = vec!["get", "services"];
+type RowRenderer = fn(&Value) -> Vec<String>;
+
+// Render a list as farm-manager's own table (or CSV) from `kubectl get -o json`,
+// falling back to kubectl's table if the JSON can't be parsed
+fn print_resource_table(kubectl: &Kubectl, args: &[&str], all_namespaces: bool, format: &str, columns: &[&str], render_row: RowRenderer) -> Result<(), Box<dyn std::error::Error>> {
+    require_structured_output()?;
+    let mut json_args = args.to_vec();
+    json_args.push("-o=json");
     
+    let output = kubectl.command()
+        .args(&json_args)
+        .output()?;
+    
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("kubectl command failed: {}", error).into());
+    }
+    
+    let list: Value = match serde_json::from_slice(&output.stdout) {
+        Ok(list) => list,
+        Err(_) => return execute_kubectl(kubectl, args, "pretty"),
+    };
+    
+    let mut headers: Vec<&str> = Vec::new();
     if all_namespaces {
-        args.push("--all-namespaces");
-    } else if let Some(ns) = namespace {
-        args.push("-n");
-        args.push(ns);
+        headers.push("NAMESPACE");
+    }
+    headers.extend_from_slice(columns);
+    
+    let rows: Vec<Vec<String>> = list["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|item| {
+            let mut row = Vec::new();
+            if all_namespaces {
+                row.push(item["metadata"]["namespace"].as_str().unwrap_or("").to_string());
+            }
+            row.extend(render_row(item));
+            row
+        })
+        .collect();
+    
+    if format == "csv" {
+        println!("{}", headers.join(","));
+        for row in &rows {
+            let cells: Vec<String> = row.iter().map(|c| csv_escape(c)).collect();
+            println!("{}", cells.join(","));
+        }
+    } else if rows.is_empty() {
+        println!("No resources found.");
     } else {
-        args.push("--all-namespaces");
+        let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
+            }
+        }
+        let header_cells: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+        for row in std::iter::once(&header_cells).chain(rows.iter()) {
+            let line: Vec<String> = row.iter().zip(&widths).map(|(cell, w)| format!("{:<w$}", cell, w = *w)).collect();
+            println!("{}", line.join("   ").trim_end());
+        }
     }
     
+    Ok(())
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+const POD_COLUMNS: &[&str] = &["NAME", "READY", "STATUS", "RESTARTS", "AGE"];
+
+fn pod_row(pod: &Value) -> Vec<String> {
+    let statuses: Vec<&Value> = pod["status"]["containerStatuses"].as_array().into_iter().flatten().collect();
+    let total = pod["spec"]["containers"].as_array().map(|c| c.len()).unwrap_or(statuses.len());
+    let ready = statuses.iter().filter(|s| s["ready"].as_bool().unwrap_or(false)).count();
+    let restarts: u64 = statuses.iter().filter_map(|s| s["restartCount"].as_u64()).sum();
+    
+    // Like kubectl, surface a container's waiting/terminated reason (e.g. CrashLoopBackOff) over the phase
+    let reason = statuses.iter().find_map(|s| {
+        s["state"]["waiting"]["reason"].as_str().or_else(|| s["state"]["terminated"]["reason"].as_str())
+    });
+    let status = if !pod["metadata"]["deletionTimestamp"].is_null() {
+        "Terminating"
+    } else {
+        reason.or_else(|| pod["status"]["phase"].as_str()).unwrap_or("Unknown")
+    };
+    
+    vec![
+        pod["metadata"]["name"].as_str().unwrap_or("").to_string(),
+        format!("{}/{}", ready, total),
+        status.to_string(),
+        restarts.to_string(),
+        format_age(pod["metadata"]["creationTimestamp"].as_str()),
+    ]
+}
+
+const DEPLOYMENT_COLUMNS: &[&str] = &["NAME", "READY", "UP-TO-DATE", "AVAILABLE", "AGE"];
+
+fn deployment_row(deployment: &Value) -> Vec<String> {
+    let status = &deployment["status"];
+    let count = |v: &Value| v.as_u64().unwrap_or(0);
+    
+    vec![
+        deployment["metadata"]["name"].as_str().unwrap_or("").to_string(),
+        format!("{}/{}", count(&status["readyReplicas"]), count(&deployment["spec"]["replicas"])),
+        count(&status["updatedReplicas"]).to_string(),
+        count(&status["availableReplicas"]).to_string(),
+        format_age(deployment["metadata"]["creationTimestamp"].as_str()),
+    ]
+}
+
+// Human-readable age from an RFC 3339 timestamp, in kubectl's style (45s, 12m, 3h, 20d)
+fn format_age(timestamp: Option<&str>) -> String {
+    let created = match timestamp.and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok()) {
+        Some(created) => created,
+        None => return "<unknown>".to_string(),
+    };
+    
+    format_age_seconds((chrono::Utc::now() - created.with_timezone(&chrono::Utc)).num_seconds())
+}
+
+fn format_age_seconds(seconds: i64) -> String {
+    match seconds.max(0) {
+        s if s < 120 => format!("{}s", s),
+        s if s < 3600 * 2 => format!("{}m", s / 60),
+        s if s < 86400 * 2 => format!("{}h", s / 3600),
+        s => format!("{}d", s / 86400),
+    }
+}
+
+fn list_services(kubectl: &Kubectl, namespace: Option<&str>, all_namespaces: bool, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let ns_args = namespace_args(namespace, all_namespaces, || current_namespace(kubectl));
+    let mut args = vec!["get", "services"];
+    args.extend(ns_args.iter().map(String::as_str));
+    
     match format {
         "json" => args.push("-o=json"),
         "yaml" => args.push("-o=yaml"),
@@ -119,10 +276,32 @@ fn list_services(namespace: Option<&str>, all_namespaces: bool, format: &str) ->
         _ => {}
     }
     
-    execute_kubectl(&args, format)
+    execute_kubectl(kubectl, &args, format)
 }
 
-fn list_nodes(format: &str) -> Result<(), Box<dyn std::error::Error>> {
+// Namespace flags for a list command: every namespace, the one given, or else
+// the current context's (`current` is only asked when needed)
+fn namespace_args(namespace: Option<&str>, all_namespaces: bool, current: impl FnOnce() -> String) -> Vec<String> {
+    if all_namespaces {
+        vec!["--all-namespaces".to_string()]
+    } else {
+        vec!["-n".to_string(), namespace.map(str::to_string).unwrap_or_else(current)]
+    }
+}
+
+// Namespace of the active kubeconfig context, matching kubectl's own default
+fn current_namespace(kubectl: &Kubectl) -> String {
+    kubectl.command()
+        .args(["config", "view", "--minify", "-o", "jsonpath={..namespace}"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|ns| !ns.is_empty())
+        .unwrap_or_else(|| "default".to_string())
+}
+
+fn list_nodes(kubectl: &Kubectl, format: &str) -> Result<(), Box<dyn std::error::Error>> {
     let mut args = vec!["get", "nodes"];
     
     match format {
@@ -132,10 +311,10 @@ fn list_nodes(format: &str) -> Result<(), Box<dyn std::error::Error>> {
         _ => {}
     }
     
-    execute_kubectl(&args, format)
+    execute_kubectl(kubectl, &args, format)
 }
 
-fn list_namespaces(format: &str) -> Result<(), Box<dyn std::error::Error>> {
+fn list_namespaces(kubectl: &Kubectl, format: &str) -> Result<(), Box<dyn std::error::Error>> {
     let mut args = vec!["get", "namespaces"];
     
     match format {
@@ -144,25 +323,111 @@ fn list_namespaces(format: &str) -> Result<(), Box<dyn std::error::Error>> {
         _ => {}
     }
     
-    execute_kubectl(&args, format)
+    execute_kubectl(kubectl, &args, format)
+}
+
+// Extended resource name advertised by the NVIDIA device plugin
+const GPU_RESOURCE: &str = "nvidia.com/gpu";
+
+#[derive(Debug, Serialize)]
+struct GpuNodeInfo {
+    node: String,
+    gpu_product: Option<String>,
+    gpu_capacity: u64,
+    gpu_allocatable: u64,
+    gpu_requested: u64,
+    gpu_available: u64,
 }
 
-fn apply_manifest(file: &str, namespace: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
-    let mut args = vec!["apply", "-f", file];
+fn collect_gpu_nodes(kubectl: &Kubectl) -> Result<Vec<GpuNodeInfo>, Box<dyn std::error::Error>> {
+    let nodes = kubectl_json(kubectl, &["get", "nodes", "-o", "json"])?;
+    // Completed pods no longer hold their GPUs
+    let pods = kubectl_json(kubectl, &[
+        "get", "pods", "--all-namespaces", "-o", "json",
+        "--field-selector", "status.phase!=Succeeded,status.phase!=Failed",
+    ])?;
+    Ok(gpu_nodes(&nodes, &pods))
+}
+
+// Per-node GPU capacity from `kubectl get nodes -o json`, less what the running pods request
+fn gpu_nodes(nodes: &Value, pods: &Value) -> Vec<GpuNodeInfo> {
+    let mut requested: HashMap<String, u64> = HashMap::new();
+    for pod in pods["items"].as_array().into_iter().flatten() {
+        if let Some(node) = pod["spec"]["nodeName"].as_str() {
+            *requested.entry(node.to_string()).or_default() += pod_gpu_request(pod);
+        }
+    }
     
-    if let Some(ns) = namespace {
-        args.push("-n");
-        args.push(ns);
+    let mut result = Vec::new();
+    for node in nodes["items"].as_array().into_iter().flatten() {
+        let name = node["metadata"]["name"].as_str().unwrap_or("").to_string();
+        let gpu_capacity = parse_quantity(&node["status"]["capacity"][GPU_RESOURCE]);
+        let gpu_allocatable = parse_quantity(&node["status"]["allocatable"][GPU_RESOURCE]);
+        let gpu_requested = requested.get(&name).copied().unwrap_or(0);
+        
+        result.push(GpuNodeInfo {
+            gpu_product: node["metadata"]["labels"]["nvidia.com/gpu.product"].as_str().map(|s| s.to_string()),
+            gpu_capacity,
+            gpu_allocatable,
+            gpu_requested,
+            gpu_available: gpu_allocatable.saturating_sub(gpu_requested),
+            node: name,
+        });
+    }
+    
+    result
+}
+
+// Effective GPU request of a pod: the sum over containers, or the largest init
+// container request if that is higher (init containers run one at a time)
+fn pod_gpu_request(pod: &Value) -> u64 {
+    let container_request = |c: &Value| {
+        let resources = &c["resources"];
+        // Extended resources may be given as limits only; the request then defaults to the limit
+        if resources["requests"][GPU_RESOURCE].is_null() {
+            parse_quantity(&resources["limits"][GPU_RESOURCE])
+        } else {
+            parse_quantity(&resources["requests"][GPU_RESOURCE])
+        }
+    };
+    
+    let containers: u64 = pod["spec"]["containers"].as_array().into_iter().flatten().map(container_request).sum();
+    let init: u64 = pod["spec"]["initContainers"].as_array().into_iter().flatten().map(container_request).max().unwrap_or(0);
+    containers.max(init)
+}
+
+fn parse_quantity(value: &Value) -> u64 {
+    match value {
+        Value::String(s) => s.parse().unwrap_or(0),
+        Value::Number(n) => n.as_u64().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+fn kubectl_json(kubectl: &Kubectl, args: &[&str]) -> Result<Value, Box<dyn std::error::Error>> {
+    let output = kubectl.command()
+        .args(args)
+        .output()?;
+    
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("kubectl command failed: {}", error).into());
     }
     
-    println!("Applying manifest from: {}", file);
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+fn apply_manifest(kubectl: &Kubectl, file: &str, namespace: Option<&str>, kustomize: bool, dry_run: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let args = apply_args(file, namespace, kustomize, dry_run)?;
     
-    let output = Command::new("kubectl")
+    status!("Applying manifest from: {}{}", file, if dry_run.is_some() { " (dry run)" } else { "" });
+    
+    let output = kubectl.command()
         .args(&args)
         .output()?;
     
     if output.status.success() {
-        println!("✓ Manifest applied successfully");
+        status!("✓ Manifest applied successfully");
         println!("{}", String::from_utf8_lossy(&output.stdout));
     } else {
         let error = String::from_utf8_lossy(&output.stderr);
@@ -172,7 +437,45 @@ fn apply_manifest(file: &str, namespace: Option<&str>) -> Result<(), Box<dyn std
     Ok(())
 }
 
-fn delete_resource(resource_type: &str, name: &str, namespace: Option<&str>, yes: bool) -> Result<(), Box<dyn std::error::Error>> {
+// Check the manifest source exists and build the `kubectl apply` arguments for it
+fn apply_args(file: &str, namespace: Option<&str>, kustomize: bool, dry_run: Option<&str>) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let is_url = file.starts_with("http://") || file.starts_with("https://");
+    let path = Path::new(file);
+    
+    if kustomize {
+        if !path.is_dir() {
+            return Err(format!("Kustomize path is not a directory: {}", file).into());
+        }
+        let has_kustomization = ["kustomization.yaml", "kustomization.yml", "Kustomization"]
+            .iter()
+            .any(|f| path.join(f).exists());
+        if !has_kustomization {
+            return Err(format!("No kustomization.yaml found in: {}", file).into());
+        }
+    } else if !is_url && !path.exists() {
+        return Err(format!("Manifest path does not exist: {}", file).into());
+    }
+    
+    let mut args = vec!["apply".to_string(), if kustomize { "-k" } else { "-f" }.to_string(), file.to_string()];
+    
+    // kubectl only descends into subdirectories with -R
+    if !kustomize && path.is_dir() {
+        args.push("-R".to_string());
+    }
+    
+    if let Some(ns) = namespace {
+        args.push("-n".to_string());
+        args.push(ns.to_string());
+    }
+    
+    if let Some(mode) = dry_run {
+        args.push(format!("--dry-run={}", mode));
+    }
+    
+    Ok(args)
+}
+
+fn delete_resource(kubectl: &Kubectl, resource_type: &str, name: &str, namespace: Option<&str>, yes: bool) -> Result<(), Box<dyn std::error::Error>> {
     if !yes {
         print!("Are you sure you want to delete {} '{}'? [y/N]: ", resource_type, name);
         io::stdout().flush()?;
@@ -193,14 +496,14 @@ fn delete_resource(resource_type: &str, name: &str, namespace: Option<&str>, yes
         args.push(ns);
     }
     
-    println!("Deleting {} '{}'...", resource_type, name);
+    status!("Deleting {} '{}'...", resource_type, name);
     
-    let output = Command::new("kubectl")
+    let output = kubectl.command()
         .args(&args)
         .output()?;
     
     if output.status.success() {
-        println!("✓ {} '{}' deleted successfully", resource_type, name);
+        status!("✓ {} '{}' deleted successfully", resource_type, name);
         println!("{}", String::from_utf8_lossy(&output.stdout));
     } else {
         let error = String::from_utf8_lossy(&output.stderr);
@@ -210,7 +513,7 @@ fn delete_resource(resource_type: &str, name: &str, namespace: Option<&str>, yes
     Ok(())
 }
 
-fn scale_deployment(name: &str, replicas: u32, namespace: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+fn scale_deployment(kubectl: &Kubectl, name: &str, replicas: u32, namespace: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
     let replicas_str = replicas.to_string();
     let mut args = vec!["scale", "deployment", name, "--replicas", &replicas_str];
     
@@ -219,14 +522,14 @@ fn scale_deployment(name: &str, replicas: u32, namespace: Option<&str>) -> Resul
         args.push(ns);
     }
     
-    println!("Scaling deployment '{}' to {} replicas...", name, replicas);
+    status!("Scaling deployment '{}' to {} replicas...", name, replicas);
     
-    let output = Command::new("kubectl")
+    let output = kubectl.command()
         .args(&args)
         .output()?;
     
     if output.status.success() {
-        println!("✓ Deployment '{}' scaled successfully", name);
+        status!("✓ Deployment '{}' scaled successfully", name);
         println!("{}", String::from_utf8_lossy(&output.stdout));
     } else {
         let error = String::from_utf8_lossy(&output.stderr);
@@ -236,7 +539,7 @@ fn scale_deployment(name: &str, replicas: u32, namespace: Option<&str>) -> Resul
     Ok(())
 }
 
-fn get_logs(name: &str, namespace: Option<&str>, container: Option<&str>, follow: bool, tail: Option<u32>) -> Result<(), Box<dyn std::error::Error>> {
+fn get_logs(kubectl: &Kubectl, name: &str, namespace: Option<&str>, container: Option<&str>, follow: bool, tail: Option<u32>) -> Result<(), Box<dyn std::error::Error>> {
     let mut args = vec!["logs", name];
     
     if let Some(ns) = namespace {
@@ -260,9 +563,9 @@ fn get_logs(name: &str, namespace: Option<&str>, container: Option<&str>, follow
         args.push(&tail_str);
     }
     
-    println!("Getting logs for pod '{}'...", name);
+    status!("Getting logs for pod '{}'...", name);
     
-    let output = Command::new("kubectl")
+    let output = kubectl.command()
         .args(&args)
         .output()?;
     
@@ -276,7 +579,7 @@ fn get_logs(name: &str, namespace: Option<&str>, container: Option<&str>, follow
     Ok(())
 }
 
-fn exec_in_pod(name: &str, namespace: Option<&str>, container: Option<&str>, command: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+fn exec_in_pod(kubectl: &Kubectl, name: &str, namespace: Option<&str>, container: Option<&str>, command: &[String]) -> Result<(), Box<dyn std::error::Error>> {
     let mut args = vec!["exec", "-it", name];
     
     if let Some(ns) = namespace {
@@ -295,9 +598,9 @@ fn exec_in_pod(name: &str, namespace: Option<&str>, container: Option<&str>, com
     let cmd_refs: Vec<&str> = command.iter().map(|s| s.as_str()).collect();
     args.extend(cmd_refs);
     
-    println!("Executing command in pod '{}'...", name);
+    status!("Executing command in pod '{}'...", name);
     
-    let status = Command::new("kubectl")
+    let status = kubectl.command()
         .args(&args)
         .status()?;
     
@@ -308,17 +611,17 @@ fn exec_in_pod(name: &str, namespace: Option<&str>, container: Option<&str>, com
     Ok(())
 }
 
-fn cluster_info(format: &str) -> Result<(), Box<dyn std::error::Error>> {
+fn cluster_info(kubectl: &Kubectl, format: &str) -> Result<(), Box<dyn std::error::Error>> {
     let args = if format == "json" || format == "yaml" {
         vec!["cluster-info", "dump"]
     } else {
         vec!["cluster-info"]
     };
     
-    execute_kubectl(&args, format)
+    execute_kubectl(kubectl, &args, format)
 }
 
-fn describe_resource(resource_type: &str, name: &str, namespace: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+fn describe_resource(kubectl: &Kubectl, resource_type: &str, name: &str, namespace: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
     let mut args = vec!["describe", resource_type, name];
     
     if let Some(ns) = namespace {
@@ -326,9 +629,9 @@ fn describe_resource(resource_type: &str, name: &str, namespace: Option<&str>) -
         args.push(ns);
     }
     
-    println!("Describing {} '{}'...", resource_type, name);
+    status!("Describing {} '{}'...", resource_type, name);
     
-    let output = Command::new("kubectl")
+    let output = kubectl.command()
         .args(&args)
         .output()?;
     
@@ -342,8 +645,146 @@ fn describe_resource(resource_type: &str, name: &str, namespace: Option<&str>) -
     Ok(())
 }
 
-fn execute_kubectl(args: &[&str], format: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let output = Command::new("kubectl")
+#[derive(Debug, Serialize)]
+struct ResourceSummary {
+    kind: String,
+    name: String,
+    namespace: Option<String>,
+    uid: Option<String>,
+    creation_timestamp: Option<String>,
+    labels: Value,
+    phase: Option<String>,
+    conditions: Vec<ConditionSummary>,
+    events: Vec<EventSummary>,
+}
+
+#[derive(Debug, Serialize)]
+struct ConditionSummary {
+    condition_type: String,
+    status: String,
+    reason: Option<String>,
+    message: Option<String>,
+    last_transition_time: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct EventSummary {
+    event_type: Option<String>,
+    reason: Option<String>,
+    message: Option<String>,
+    count: Option<u64>,
+    last_timestamp: Option<String>,
+}
+
+// Number of most recent events to include in a structured describe
+const DESCRIBE_EVENT_LIMIT: usize = 10;
+
+// `kubectl describe` has no machine-readable output, so build an equivalent
+// condensed view from the object JSON and its events
+fn describe_resource_structured(kubectl: &Kubectl, resource_type: &str, name: &str, namespace: Option<&str>) -> Result<ResourceSummary, Box<dyn std::error::Error>> {
+    let mut args = vec!["get", resource_type, name, "-o", "json"];
+    
+    if let Some(ns) = namespace {
+        args.push("-n");
+        args.push(ns);
+    }
+    
+    let output = kubectl.command()
+        .args(&args)
+        .output()?;
+    
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to describe resource: {}", error).into());
+    }
+    
+    let object: Value = serde_json::from_slice(&output.stdout)?;
+    let mut summary = summarize_resource(&object, resource_type, name);
+    summary.events = collect_resource_events(kubectl, &summary.kind, name, summary.namespace.as_deref()).unwrap_or_default();
+    Ok(summary)
+}
+
+// Condensed view of an object's JSON, without its events
+fn summarize_resource(object: &Value, resource_type: &str, name: &str) -> ResourceSummary {
+    let metadata = &object["metadata"];
+    let as_string = |v: &Value| v.as_str().map(|s| s.to_string());
+    
+    let conditions = object["status"]["conditions"]
+        .as_array()
+        .map(|conditions| {
+            conditions
+                .iter()
+                .map(|c| ConditionSummary {
+                    condition_type: c["type"].as_str().unwrap_or("").to_string(),
+                    status: c["status"].as_str().unwrap_or("").to_string(),
+                    reason: as_string(&c["reason"]),
+                    message: as_string(&c["message"]),
+                    last_transition_time: as_string(&c["lastTransitionTime"]),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    
+    ResourceSummary {
+        kind: object["kind"].as_str().unwrap_or(resource_type).to_string(),
+        name: metadata["name"].as_str().unwrap_or(name).to_string(),
+        namespace: as_string(&metadata["namespace"]),
+        uid: as_string(&metadata["uid"]),
+        creation_timestamp: as_string(&metadata["creationTimestamp"]),
+        labels: metadata["labels"].clone(),
+        phase: as_string(&object["status"]["phase"]),
+        conditions,
+        events: Vec::new(),
+    }
+}
+
+fn collect_resource_events(kubectl: &Kubectl, kind: &str, name: &str, namespace: Option<&str>) -> Result<Vec<EventSummary>, Box<dyn std::error::Error>> {
+    let selector = format!("involvedObject.kind={},involvedObject.name={}", kind, name);
+    let mut args = vec!["get", "events", "--field-selector", &selector, "-o", "json"];
+    
+    if let Some(ns) = namespace {
+        args.push("-n");
+        args.push(ns);
+    }
+    
+    let output = kubectl.command()
+        .args(&args)
+        .output()?;
+    
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+    
+    let list: Value = serde_json::from_slice(&output.stdout)?;
+    Ok(summarize_events(&list))
+}
+
+// The most recent DESCRIBE_EVENT_LIMIT events of a `kubectl get events -o json` list, oldest first
+fn summarize_events(list: &Value) -> Vec<EventSummary> {
+    let mut items: Vec<&Value> = list["items"].as_array().map(|a| a.iter().collect()).unwrap_or_default();
+    
+    // Newer events may only carry eventTime; fall back to it for ordering
+    let timestamp = |e: &Value| -> String {
+        e["lastTimestamp"].as_str().or_else(|| e["eventTime"].as_str()).unwrap_or("").to_string()
+    };
+    items.sort_by_key(|e| timestamp(e));
+    
+    let skip = items.len().saturating_sub(DESCRIBE_EVENT_LIMIT);
+    items
+        .into_iter()
+        .skip(skip)
+        .map(|e| EventSummary {
+            event_type: e["type"].as_str().map(|s| s.to_string()),
+            reason: e["reason"].as_str().map(|s| s.to_string()),
+            message: e["message"].as_str().map(|s| s.to_string()),
+            count: e["count"].as_u64(),
+            last_timestamp: Some(timestamp(e)).filter(|t| !t.is_empty()),
+        })
+        .collect()
+}
+
+fn execute_kubectl(kubectl: &Kubectl, args: &[&str], format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let output = kubectl.command()
         .args(args)
         .output()?;
     
@@ -359,12 +800,234 @@ fn execute_kubectl(args: &[&str], format: &str) -> Result<(), Box<dyn std::error
         let json_value: serde_json::Value = serde_json::from_str(&stdout)?;
         output_data(&json_value, format)?;
     } else if format == "yaml" {
-        // Just print YAML as-is
-        println!("{}", stdout);
+        let yaml_value: serde_json::Value = serde_yaml::from_str(&stdout)?;
+        output_data(&yaml_value, format)?;
     } else {
         // Pretty/table format
+        require_structured_output()?;
         println!("{}", stdout);
     }
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_csv_escape() {
+        assert_eq!(csv_escape("trainer-0"), "trainer-0");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_escape("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn test_pod_row() {
+        let pod = json!({
+            "metadata": {"name": "trainer-0", "creationTimestamp": "not a timestamp"},
+            "spec": {"containers": [{"name": "trainer"}, {"name": "sidecar"}]},
+            "status": {
+                "phase": "Running",
+                "containerStatuses": [
+                    {"name": "trainer", "ready": false, "restartCount": 5, "state": {"waiting": {"reason": "CrashLoopBackOff"}}},
+                    {"name": "sidecar", "ready": true, "restartCount": 1, "state": {"running": {}}}
+                ]
+            }
+        });
+        assert_eq!(pod_row(&pod), vec!["trainer-0", "1/2", "CrashLoopBackOff", "6", "<unknown>"]);
+
+        let pending = json!({"metadata": {"name": "job-1"}, "spec": {"containers": [{"name": "job"}]}, "status": {"phase": "Pending"}});
+        assert_eq!(pod_row(&pending)[1..3], ["0/1", "Pending"]);
+
+        let deleting = json!({"metadata": {"name": "web-1", "deletionTimestamp": "2025-10-14T09:12:33Z"}, "status": {"phase": "Running"}});
+        assert_eq!(pod_row(&deleting)[2], "Terminating");
+    }
+
+    #[test]
+    fn test_deployment_row() {
+        let deployment = json!({
+            "metadata": {"name": "inference"},
+            "spec": {"replicas": 3},
+            "status": {"readyReplicas": 2, "updatedReplicas": 3, "availableReplicas": 2}
+        });
+        assert_eq!(deployment_row(&deployment), vec!["inference", "2/3", "3", "2", "<unknown>"]);
+
+        // Scaled to zero: kubectl omits the replica counts from status
+        let idle = json!({"metadata": {"name": "batch"}, "spec": {"replicas": 0}, "status": {}});
+        assert_eq!(deployment_row(&idle)[1..4], ["0/0", "0", "0"]);
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age_seconds(45), "45s");
+        assert_eq!(format_age_seconds(119), "119s");
+        assert_eq!(format_age_seconds(600), "10m");
+        assert_eq!(format_age_seconds(3 * 3600), "3h");
+        assert_eq!(format_age_seconds(20 * 86400), "20d");
+        // Clock skew can put creation in the future
+        assert_eq!(format_age_seconds(-5), "0s");
+        assert_eq!(format_age(None), "<unknown>");
+        assert!(format_age(Some("2020-01-01T00:00:00Z")).ends_with('d'));
+    }
+
+    #[test]
+    fn test_kubectl_global_args() {
+        let args = |kubectl: Kubectl| kubectl.command().arg("version").get_args().map(|a| a.to_string_lossy().to_string()).collect::<Vec<_>>();
+
+        assert_eq!(args(Kubectl::new(None, None)), vec!["version"]);
+        assert_eq!(
+            args(Kubectl::new(Some("/etc/farm/lab.kubeconfig"), Some("lab-admin"))),
+            vec!["--kubeconfig=/etc/farm/lab.kubeconfig", "--context=lab-admin", "version"]
+        );
+        assert_eq!(args(Kubectl::new(None, Some("prod"))), vec!["--context=prod", "version"]);
+    }
+
+    #[test]
+    fn test_namespace_args() {
+        let no_lookup = || -> String { panic!("current namespace looked up") };
+
+        assert_eq!(namespace_args(None, true, no_lookup), vec!["--all-namespaces"]);
+        assert_eq!(namespace_args(Some("ml"), true, no_lookup), vec!["--all-namespaces"]);
+        assert_eq!(namespace_args(Some("ml"), false, no_lookup), vec!["-n", "ml"]);
+        assert_eq!(namespace_args(None, false, || "team-a".to_string()), vec!["-n", "team-a"]);
+    }
+
+    #[test]
+    fn test_pod_gpu_request() {
+        let container = |requests: Value, limits: Value| json!({"resources": {"requests": requests, "limits": limits}});
+        let pod = |containers: Vec<Value>, init: Vec<Value>| json!({"spec": {"containers": containers, "initContainers": init}});
+
+        assert_eq!(pod_gpu_request(&pod(vec![container(json!({GPU_RESOURCE: "2"}), json!({GPU_RESOURCE: "2"}))], vec![])), 2);
+        // Limits only: the request defaults to the limit
+        assert_eq!(pod_gpu_request(&pod(vec![container(json!({"cpu": "4"}), json!({GPU_RESOURCE: 1}))], vec![])), 1);
+        assert_eq!(pod_gpu_request(&pod(vec![container(json!({GPU_RESOURCE: "1"}), json!(null)), container(json!({GPU_RESOURCE: "3"}), json!(null))], vec![])), 4);
+        // Init containers run one at a time, so only the largest counts
+        assert_eq!(pod_gpu_request(&pod(vec![container(json!({GPU_RESOURCE: "1"}), json!(null))], vec![container(json!({GPU_RESOURCE: "8"}), json!(null))])), 8);
+        assert_eq!(pod_gpu_request(&json!({"spec": {"containers": [{"name": "web"}]}})), 0);
+    }
+
+    #[test]
+    fn test_parse_quantity() {
+        assert_eq!(parse_quantity(&json!("8")), 8);
+        assert_eq!(parse_quantity(&json!(4)), 4);
+        assert_eq!(parse_quantity(&json!("500m")), 0);
+        assert_eq!(parse_quantity(&Value::Null), 0);
+    }
+
+    #[test]
+    fn test_gpu_nodes() {
+        let nodes = json!({"items": [
+            {
+                "metadata": {"name": "gpu-a", "labels": {"nvidia.com/gpu.product": "NVIDIA-H100-80GB-HBM3"}},
+                "status": {"capacity": {GPU_RESOURCE: "8"}, "allocatable": {GPU_RESOURCE: "7"}}
+            },
+            {"metadata": {"name": "cpu-a", "labels": {}}, "status": {"capacity": {"cpu": "64"}, "allocatable": {"cpu": "63"}}}
+        ]});
+        let pods = json!({"items": [
+            {"spec": {"nodeName": "gpu-a", "containers": [{"resources": {"limits": {GPU_RESOURCE: "4"}}}]}},
+            {"spec": {"nodeName": "gpu-a", "containers": [{"resources": {"requests": {GPU_RESOURCE: "2"}}}]}},
+            {"spec": {"containers": [{"resources": {"requests": {GPU_RESOURCE: "1"}}}]}}
+        ]});
+
+        let result = gpu_nodes(&nodes, &pods);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].node, "gpu-a");
+        assert_eq!(result[0].gpu_product.as_deref(), Some("NVIDIA-H100-80GB-HBM3"));
+        assert_eq!((result[0].gpu_capacity, result[0].gpu_allocatable, result[0].gpu_requested, result[0].gpu_available), (8, 7, 6, 1));
+        assert_eq!((result[1].gpu_capacity, result[1].gpu_available, result[1].gpu_product.as_deref()), (0, 0, None));
+    }
+
+    #[test]
+    fn test_apply_args() {
+        let dir = std::env::temp_dir().join(format!("farm-manager-k8s-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("overlay")).unwrap();
+        std::fs::write(dir.join("deploy.yaml"), "").unwrap();
+        std::fs::write(dir.join("overlay/kustomization.yaml"), "").unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+
+        let file = path("deploy.yaml");
+        assert_eq!(apply_args(&file, Some("ml"), false, Some("server")).unwrap(), vec!["apply", "-f", &file, "-n", "ml", "--dry-run=server"]);
+        let manifests = dir.to_string_lossy().to_string();
+        assert_eq!(apply_args(&manifests, None, false, None).unwrap(), vec!["apply", "-f", &manifests, "-R"]);
+        let overlay = path("overlay");
+        assert_eq!(apply_args(&overlay, None, true, None).unwrap(), vec!["apply", "-k", &overlay]);
+        let url = "https://example.com/manifests/gpu-operator.yaml";
+        assert_eq!(apply_args(url, None, false, Some("client")).unwrap(), vec!["apply", "-f", url, "--dry-run=client"]);
+
+        assert!(apply_args(&path("missing.yaml"), None, false, None).unwrap_err().to_string().starts_with("Manifest path does not exist"));
+        assert!(apply_args(&manifests, None, true, None).unwrap_err().to_string().starts_with("No kustomization.yaml found"));
+        assert!(apply_args(&file, None, true, None).unwrap_err().to_string().starts_with("Kustomize path is not a directory"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_summarize_resource() {
+        let pod = json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": {
+                "name": "trainer-0",
+                "namespace": "ml",
+                "uid": "6f1c2a9e-3d4b-4e5f-8a7b-9c0d1e2f3a4b",
+                "creationTimestamp": "2025-10-14T09:12:33Z",
+                "labels": {"app": "trainer"}
+            },
+            "status": {
+                "phase": "Pending",
+                "conditions": [{
+                    "type": "PodScheduled",
+                    "status": "False",
+                    "reason": "Unschedulable",
+                    "message": "0/4 nodes are available: 4 Insufficient nvidia.com/gpu.",
+                    "lastTransitionTime": "2025-10-14T09:12:33Z"
+                }]
+            }
+        });
+
+        let summary = summarize_resource(&pod, "pod", "trainer-0");
+        assert_eq!(summary.kind, "Pod");
+        assert_eq!(summary.namespace.as_deref(), Some("ml"));
+        assert_eq!(summary.phase.as_deref(), Some("Pending"));
+        assert_eq!(summary.labels["app"], "trainer");
+        assert_eq!(summary.conditions.len(), 1);
+        assert_eq!(summary.conditions[0].condition_type, "PodScheduled");
+        assert_eq!(summary.conditions[0].reason.as_deref(), Some("Unschedulable"));
+
+        // Cluster-scoped objects have no namespace or phase
+        let summary = summarize_resource(&json!({"kind": "Node", "metadata": {"name": "gpu-a"}, "status": {}}), "node", "gpu-a");
+        assert_eq!((summary.namespace, summary.phase), (None, None));
+        assert!(summary.conditions.is_empty());
+    }
+
+    #[test]
+    fn test_summarize_events() {
+        let event = |reason: &str, last: Option<&str>, event_time: Option<&str>| {
+            json!({"type": "Warning", "reason": reason, "message": "...", "count": 3, "lastTimestamp": last, "eventTime": event_time})
+        };
+        let mut items = vec![
+            event("FailedScheduling", Some("2025-10-14T09:20:00Z"), None),
+            event("Scheduled", None, Some("2025-10-14T09:30:00.000000Z")),
+            event("Pulling", Some("2025-10-14T09:25:00Z"), None),
+        ];
+
+        let events = summarize_events(&json!({"items": items}));
+        let reasons: Vec<_> = events.iter().map(|e| e.reason.as_deref().unwrap()).collect();
+        assert_eq!(reasons, vec!["FailedScheduling", "Pulling", "Scheduled"]);
+        assert_eq!(events[2].last_timestamp.as_deref(), Some("2025-10-14T09:30:00.000000Z"));
+        assert_eq!(events[0].count, Some(3));
+
+        // Only the newest events are kept
+        for minute in 0..DESCRIBE_EVENT_LIMIT {
+            items.push(event("BackOff", Some(&format!("2025-10-14T10:{:02}:00Z", minute)), None));
+        }
+        let events = summarize_events(&json!({"items": items}));
+        assert_eq!(events.len(), DESCRIBE_EVENT_LIMIT);
+        assert!(events.iter().all(|e| e.reason.as_deref() == Some("BackOff")));
+
+        assert!(summarize_events(&json!({"items": []})).is_empty());
+    }
+}
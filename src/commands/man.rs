@@ -0,0 +1,21 @@
+use std::path::Path;
+
+use clap::CommandFactory;
+
+use crate::cli::Cli;
+use crate::output::status;
+
+/// Render roff man pages from the CLI definition: every page into `dir`
+/// (farm-manager.1, farm-manager-hardware.1, ...), or the top-level page to stdout.
+pub fn handle_man_command(dir: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = Cli::command();
+    match dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir, e))?;
+            clap_mangen::generate_to(cmd, Path::new(dir))?;
+            status!("✓ Man pages written to {}", dir);
+        }
+        None => clap_mangen::Man::new(cmd).render(&mut std::io::stdout())?,
+    }
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::hardware::types::{FleetSummary, Inventory};
+use crate::output::output_data;
+
+const INVENTORY_EXTENSIONS: [&str; 3] = ["json", "ndjson", "jsonl"];
+
+pub fn handle_merge_command(inputs: &[String], summary: bool, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut inventories = Vec::new();
+    for path in expand_inputs(inputs)? {
+        inventories.extend(read_inventories(&path)?);
+    }
+
+    if summary {
+        output_data(&summarize(&inventories), format)
+    } else {
+        output_data(&inventories, format)
+    }
+}
+
+/// Resolve each input to files: directories contribute their inventory files,
+/// anything with glob characters is expanded, and plain paths are used as-is.
+fn expand_inputs(inputs: &[String]) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut paths = Vec::new();
+
+    for input in inputs {
+        let path = Path::new(input);
+        if path.is_dir() {
+            let mut files: Vec<PathBuf> = fs::read_dir(path)
+                .map_err(|e| format!("Failed to read directory {}: {}", input, e))?
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|p| p.is_file() && has_inventory_extension(p))
+                .collect();
+            files.sort();
+            paths.extend(files);
+        } else if input.contains(['*', '?', '[']) {
+            let mut files = Vec::new();
+            for entry in glob::glob(input).map_err(|e| format!("Invalid pattern {}: {}", input, e))? {
+                files.push(entry?);
+            }
+            if files.is_empty() {
+                return Err(format!("No files match {}", input).into());
+            }
+            files.sort();
+            paths.extend(files);
+        } else {
+            paths.push(path.to_path_buf());
+        }
+    }
+
+    Ok(paths)
+}
+
+fn has_inventory_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| INVENTORY_EXTENSIONS.contains(&e))
+}
+
+/// Read every inventory in a file. A file may hold one JSON document, an array
+/// of them, or a stream of documents (NDJSON, or repeated `--output` appends).
+pub(crate) fn read_inventories(path: &Path) -> Result<Vec<Inventory>, Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_inventories(&text).map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+fn parse_inventories(text: &str) -> Result<Vec<Inventory>, serde_json::Error> {
+    let mut inventories = Vec::new();
+    for document in serde_json::Deserializer::from_str(text).into_iter::<Value>() {
+        match document? {
+            Value::Array(items) => {
+                for item in items {
+                    inventories.push(serde_json::from_value(item)?);
+                }
+            }
+            item => inventories.push(serde_json::from_value(item)?),
+        }
+    }
+    Ok(inventories)
+}
+
+fn summarize(inventories: &[Inventory]) -> FleetSummary {
+    let mut summary = FleetSummary {
+        nodes: inventories.len(),
+        cpu_models: BTreeMap::new(),
+        gpu_models: BTreeMap::new(),
+        kernel_versions: BTreeMap::new(),
+        bios_versions: BTreeMap::new(),
+        bmc_firmware_versions: BTreeMap::new(),
+        nic_firmware_versions: BTreeMap::new(),
+        nodes_with_collection_errors: 0,
+    };
+
+    for inventory in inventories {
+        let node = &inventory.node;
+        count(&mut summary.kernel_versions, node.kernel_version.as_deref());
+        count(&mut summary.bios_versions, node.bios.as_ref().and_then(|b| b.version.as_deref()));
+        count(&mut summary.bmc_firmware_versions, node.bmc.as_ref().and_then(|b| b.firmware_version.as_deref()));
+        count(&mut summary.cpu_models, inventory.cpu.cpus.first().and_then(|c| c.model_name.as_deref()));
+
+        for gpu in &inventory.gpus {
+            count(&mut summary.gpu_models, gpu.model.as_deref());
+        }
+        for nic in &inventory.network.interfaces {
+            let firmware = nic.firmware_version.as_ref().map(|fw| {
+                format!("{} {}", nic.driver.as_deref().unwrap_or("unknown"), fw)
+            });
+            count(&mut summary.nic_firmware_versions, firmware.as_deref());
+        }
+
+        if !inventory.collection_errors.is_empty() {
+            summary.nodes_with_collection_errors += 1;
+        }
+    }
+
+    summary
+}
+
+fn count(counts: &mut BTreeMap<String, usize>, value: Option<&str>) {
+    *counts.entry(value.unwrap_or("unknown").to_string()).or_default() += 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::types::{BiosInfo, CollectorError, GpuInfo, NetInterface};
+
+    fn node(hostname: &str, gpus: &[&str]) -> Inventory {
+        let mut inventory = Inventory::default();
+        inventory.node.hostname = hostname.to_string();
+        inventory.node.kernel_version = Some("6.8.0-45-generic".to_string());
+        inventory.gpus = gpus.iter().map(|model| GpuInfo { model: Some(model.to_string()), ..Default::default() }).collect();
+        inventory
+    }
+
+    fn fleet() -> String {
+        let mut a = node("gpu-a", &["H100 SXM5 80GB", "H100 SXM5 80GB"]);
+        a.node.bios = Some(BiosInfo { version: Some("2.1.4".to_string()), ..Default::default() });
+        a.network.interfaces.push(NetInterface {
+            name: "ens1f0".to_string(),
+            driver: Some("mlx5_core".to_string()),
+            firmware_version: Some("28.39.1002".to_string()),
+            ..Default::default()
+        });
+        let mut b = node("gpu-b", &["L40S"]);
+        b.collection_errors.push(CollectorError {
+            collector: "disks".to_string(),
+            message: "smartctl not found on PATH".to_string(),
+        });
+        serde_json::to_string(&[a, b]).unwrap()
+    }
+
+    #[test]
+    fn test_parse_inventories() {
+        let a = serde_json::to_string(&node("gpu-a", &[])).unwrap();
+        let b = serde_json::to_string(&node("gpu-b", &[])).unwrap();
+
+        assert_eq!(parse_inventories(&a).unwrap().len(), 1);
+        assert_eq!(parse_inventories(&fleet()).unwrap().len(), 2);
+
+        // NDJSON, and documents appended back to back by --output
+        let hostnames: Vec<String> = parse_inventories(&format!("{}\n{}\n", a, b)).unwrap().into_iter().map(|i| i.node.hostname).collect();
+        assert_eq!(hostnames, vec!["gpu-a", "gpu-b"]);
+        assert_eq!(parse_inventories(&format!("{}{}", a, b)).unwrap().len(), 2);
+
+        assert!(parse_inventories("").unwrap().is_empty());
+        assert!(parse_inventories("{\"node\": ").is_err());
+    }
+
+    #[test]
+    fn test_summarize() {
+        let summary = summarize(&parse_inventories(&fleet()).unwrap());
+
+        assert_eq!(summary.nodes, 2);
+        assert_eq!(summary.kernel_versions.get("6.8.0-45-generic"), Some(&2));
+        assert_eq!(summary.gpu_models.get("H100 SXM5 80GB"), Some(&2));
+        assert_eq!(summary.gpu_models.get("L40S"), Some(&1));
+        assert_eq!(summary.bios_versions.get("2.1.4"), Some(&1));
+        assert_eq!(summary.bios_versions.get("unknown"), Some(&1));
+        assert_eq!(summary.nic_firmware_versions.get("mlx5_core 28.39.1002"), Some(&1));
+        assert_eq!(summary.nodes_with_collection_errors, 1);
+    }
+
+    #[test]
+    fn test_expand_inputs() {
+        let dir = std::env::temp_dir().join(format!("farm-manager-merge-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["b.json", "a.ndjson", "notes.txt"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        let files = expand_inputs(&[dir.to_string_lossy().to_string()]).unwrap();
+        assert_eq!(files, vec![dir.join("a.ndjson"), dir.join("b.json")]);
+
+        let pattern = dir.join("*.json").to_string_lossy().to_string();
+        assert_eq!(expand_inputs(&[pattern]).unwrap(), vec![dir.join("b.json")]);
+
+        let pattern = dir.join("*.yaml").to_string_lossy().to_string();
+        assert!(expand_inputs(&[pattern]).unwrap_err().to_string().starts_with("No files match"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -2,8 +2,22 @@ pub mod hardware;
 pub mod test;
 pub mod vm;
 pub mod k8s;
+pub mod version;
+pub mod agent;
+pub mod merge;
+pub mod diff;
+pub mod expect;
+pub mod fields;
+pub mod man;
+#[cfg(feature = "schema")]
+pub mod schema;
 
 pub use hardware::handle_hardware_command;
 pub use test::handle_test_command;
 pub use vm::handle_vm_command;
-pub use k8s::handle_k8s_command;
\ No newline at end of file
+pub use k8s::handle_k8s_command;
+pub use version::handle_version_command;
+pub use agent::handle_agent_command;
+pub use merge::handle_merge_command;
+pub use diff::handle_diff_command;
+pub use man::handle_man_command;
\ No newline at end of file
@@ -0,0 +1,7 @@
+use crate::hardware::types::Inventory;
+
+pub fn handle_schema_command() -> Result<(), Box<dyn std::error::Error>> {
+    let schema = schemars::schema_for!(Inventory);
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
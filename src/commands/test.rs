@@ -1,134 +1,192 @@
-use crate::cli::TestCommands;
+use crate::cli::{GpuSelectArgs, HashcatArgs, TestCommands};
 use crate::testing::{
+    run_cpu_test,
     collect_gpu_errors,
     collect_gpu_health,
+    GpuFilter,
+    collect_gpu_topology,
+    configure_gpus,
+    GpuConfigRequest,
     collect_nccl_info,
     run_nccl_test,
     collect_mpi_info,
     run_mpi_test,
+    run_ib_bw_test,
+    run_net_bw_test,
     collect_hashcat_info,
     run_hashcat_benchmark,
+    run_hashcat_stress,
     run_hashcat_test,
+    restore_hashcat_test,
+    default_session_dir,
+    HashcatOptions,
+    HashcatSession,
     collect_dcgm_info,
     run_dcgm_diag,
     run_dcgm_health_check,
+    run_all_checks,
 };
-use crate::output::output_data;
+use crate::hardware::types::HealthStatus;
+use crate::output::{confirm_action, output_data, print_warning};
+use crate::util::is_root;
+use std::path::PathBuf;
 
 pub fn handle_test_command(cmd: &TestCommands) -> Result<(), Box<dyn std::error::Error>> {
     match cmd {
-        TestCommands::GpuErrors { format } => {
-            match collect_gpu_errors() {
-                Ok(gpu_errors) => {
-                    output_data(&gpu_errors, format)?;
-                }
-                Err(e) => {
-                    eprintln!("✗ Error collecting GPU errors: {}", e);
-                    eprintln!("Note: This command requires NVIDIA GPUs with NVML support.");
-                    return Err(e);
-                }
+        TestCommands::All { format } => {
+            let report = run_all_checks();
+            output_data(&report, format)?;
+            let critical: Vec<&str> = report.checks.iter()
+                .filter(|c| c.status == HealthStatus::Critical)
+                .map(|c| c.name.as_str())
+                .collect();
+            if !critical.is_empty() {
+                return Err(format!("Critical result from {}", critical.join(", ")).into());
             }
         }
-        TestCommands::GpuHealth { format } => {
-            match collect_gpu_health() {
-                Ok(gpu_health) => {
-                    output_data(&gpu_health, format)?;
-                }
-                Err(e) => {
-                    eprintln!("✗ Error collecting GPU health: {}", e);
-                    eprintln!("Note: This command requires NVIDIA GPUs with NVML support.");
-                    return Err(e);
+        TestCommands::Cpu { duration, threads, format } => {
+            let test_result = run_cpu_test(*duration, *threads)?;
+            output_data(&test_result, format)?;
+            if let Some(error) = test_result.error {
+                return Err(format!("CPU test failed: {}", error).into());
+            }
+        }
+        TestCommands::GpuErrors { select, format } => {
+            let gpu_errors = collect_gpu_errors(&gpu_filter(select))
+                .map_err(|e| format!("{}. This command requires NVIDIA GPUs with NVML support.", e))?;
+            output_data(&gpu_errors, format)?;
+        }
+        TestCommands::GpuHealth { select, format } => {
+            let gpu_health = collect_gpu_health(&gpu_filter(select))
+                .map_err(|e| format!("{}. This command requires NVIDIA GPUs with NVML support.", e))?;
+            output_data(&gpu_health, format)?;
+        }
+        TestCommands::GpuConfig { devices, power_limit, memory_clock, graphics_clock, persistence, yes, format } => {
+            let request = GpuConfigRequest {
+                power_limit_watts: *power_limit,
+                application_clocks_mhz: memory_clock.zip(*graphics_clock),
+                persistence_mode: persistence.as_deref().map(|p| p == "on"),
+            };
+            if !request.is_empty() {
+                if !is_root() {
+                    return Err("Changing GPU settings needs root; re-run with sudo".into());
+                }
+                let targets = match devices {
+                    Some(ids) => format!("GPU(s) {}", ids.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(",")),
+                    None => "every GPU".to_string(),
+                };
+                if !*yes && !confirm_action(&format!("This will set {} for {}.", request.describe(), targets)) {
+                    println!("Cancelled.");
+                    return Ok(());
                 }
             }
+            
+            let changes = configure_gpus(devices.as_deref(), &request)?;
+            output_data(&changes, format)?;
+            let failed = changes.iter().filter(|c| c.error.is_some()).count();
+            if failed > 0 {
+                return Err(format!("{} of {} GPU(s) could not be configured", failed, changes.len()).into());
+            }
+        }
+        TestCommands::GpuTopo { format } => {
+            let topology = collect_gpu_topology()
+                .map_err(|e| format!("{}. This command requires NVIDIA GPUs with NVML support.", e))?;
+            output_data(&topology, format)?;
         }
         TestCommands::NcclInfo { format } => {
             let nccl_info = collect_nccl_info();
             output_data(&nccl_info, format)?;
         }
         TestCommands::NcclTest { test_type, size, iterations, format } => {
-            match run_nccl_test(test_type, size, *iterations) {
-                Ok(test_result) => {
-                    output_data(&test_result, format)?;
-                }
-                Err(e) => {
-                    eprintln!("✗ Error running NCCL test: {}", e);
-                    eprintln!("Note: This command requires NVIDIA GPUs and NCCL installation.");
-                    return Err(e);
-                }
-            }
+            let test_result = run_nccl_test(test_type, size, *iterations)
+                .map_err(|e| format!("{}. This command requires NVIDIA GPUs and NCCL installation.", e))?;
+            output_data(&test_result, format)?;
         }
         TestCommands::MpiInfo { format } => {
             let mpi_info = collect_mpi_info();
             output_data(&mpi_info, format)?;
         }
         TestCommands::MpiTest { test_type, processes, size, iterations, format } => {
-            match run_mpi_test(test_type, *processes, size, *iterations) {
-                Ok(test_result) => {
-                    output_data(&test_result, format)?;
-                }
-                Err(e) => {
-                    eprintln!("✗ Error running MPI test: {}", e);
-                    eprintln!("Note: This command requires MPI installation (OpenMPI, MPICH, etc.).");
-                    return Err(e);
-                }
-            }
+            let test_result = run_mpi_test(test_type, *processes, size, *iterations)
+                .map_err(|e| format!("{}. This command requires MPI installation (OpenMPI, MPICH, etc.).", e))?;
+            output_data(&test_result, format)?;
+        }
+        TestCommands::IbBw { test_type, server, device, size, iterations, port, format } => {
+            let test_result = run_ib_bw_test(test_type, server.as_deref(), device.as_deref(), size, *iterations, *port)?;
+            output_data(&test_result, format)?;
+        }
+        TestCommands::NetBw { server, parallel, udp, bitrate, duration, port, format, .. } => {
+            let test_result = run_net_bw_test(server.as_deref(), *parallel, *udp, bitrate.as_deref(), *duration, *port)?;
+            output_data(&test_result, format)?;
         }
         TestCommands::HashcatInfo { format } => {
             let hashcat_info = collect_hashcat_info();
             output_data(&hashcat_info, format)?;
         }
-        TestCommands::HashcatBenchmark { hash_types, devices, format } => {
-            match run_hashcat_benchmark(hash_types.clone(), devices.clone()) {
-                Ok(results) => {
-                    output_data(&results, format)?;
-                }
-                Err(e) => {
-                    eprintln!("✗ Error running Hashcat benchmark: {}", e);
-                    eprintln!("Note: This command requires Hashcat installation.");
-                    return Err(e);
-                }
-            }
+        TestCommands::HashcatBenchmark { hash_types, devices, hashcat, format } => {
+            let results = run_hashcat_benchmark(hash_types.clone(), devices.clone(), &hashcat_options(hashcat))
+                .map_err(|e| format!("{}. This command requires Hashcat installation.", e))?;
+            output_data(&results, format)?;
         }
-        TestCommands::HashcatTest { hash_type, hash_file, wordlist, devices, format } => {
-            match run_hashcat_test(hash_type, hash_file, wordlist, devices.clone()) {
-                Ok(test_result) => {
-                    output_data(&test_result, format)?;
-                }
-                Err(e) => {
-                    eprintln!("✗ Error running Hashcat test: {}", e);
-                    eprintln!("Note: This command requires Hashcat installation.");
-                    return Err(e);
-                }
-            }
+        TestCommands::HashcatStress { hash_type, duration, sample_interval, devices, hashcat, format } => {
+            let stress_result = run_hashcat_stress(hash_type, *duration, devices.clone(), *sample_interval, &hashcat_options(hashcat))
+                .map_err(|e| format!("{}. This command requires Hashcat installation and NVIDIA GPUs.", e))?;
+            output_data(&stress_result, format)?;
+        }
+        TestCommands::HashcatTest { hash_type, hash_file, wordlist, devices, session, restore, session_dir, max_runtime, hashcat, format } => {
+            let session = session.as_ref().map(|name| HashcatSession {
+                name: name.clone(),
+                dir: session_dir.as_ref().map(PathBuf::from).unwrap_or_else(default_session_dir),
+            });
+            let outcome = match (session.as_ref().filter(|_| *restore), hash_file, wordlist) {
+                (Some(session), _, _) => restore_hashcat_test(session),
+                (None, Some(hash_file), Some(wordlist)) => {
+                    let options = HashcatOptions { runtime_secs: *max_runtime, ..hashcat_options(hashcat) };
+                    run_hashcat_test(hash_type, hash_file, wordlist, devices.clone(), &options, session.as_ref())
+                }
+                _ => Err("--hash-file and --wordlist are required unless --restore is given".into()),
+            };
+            let test_result = outcome
+                .map_err(|e| format!("{}. This command requires Hashcat installation.", e))?;
+            output_data(&test_result, format)?;
         }
         TestCommands::DcgmInfo { format } => {
             let dcgm_info = collect_dcgm_info();
             output_data(&dcgm_info, format)?;
         }
-        TestCommands::DcgmDiag { level, gpus, format } => {
-            match run_dcgm_diag(*level, gpus.clone()) {
-                Ok(diag_result) => {
-                    output_data(&diag_result, format)?;
-                }
-                Err(e) => {
-                    eprintln!("✗ Error running DCGM diagnostics: {}", e);
-                    eprintln!("Note: This command requires DCGM installation and NVIDIA GPUs.");
-                    return Err(e);
-                }
-            }
+        TestCommands::DcgmDiag { level, gpus, max_runtime, format } => {
+            let diag_result = run_dcgm_diag(*level, gpus.clone(), *max_runtime)
+                .map_err(|e| format!("{}. This command requires DCGM installation and NVIDIA GPUs.", e))?;
+            output_data(&diag_result, format)?;
         }
         TestCommands::DcgmHealth { format } => {
-            match run_dcgm_health_check() {
-                Ok(health_results) => {
-                    output_data(&health_results, format)?;
-                }
-                Err(e) => {
-                    eprintln!("✗ Error running DCGM health check: {}", e);
-                    eprintln!("Note: This command requires DCGM installation and NVIDIA GPUs.");
-                    return Err(e);
-                }
-            }
+            let health_results = run_dcgm_health_check()
+                .map_err(|e| format!("{}. This command requires DCGM installation and NVIDIA GPUs.", e))?;
+            output_data(&health_results, format)?;
         }
     }
     Ok(())
 }
+
+/// Hashcat flags from the command line. `--force` hides real driver problems,
+/// so say so whenever it is used.
+fn hashcat_options(args: &HashcatArgs) -> HashcatOptions {
+    if args.force {
+        print_warning("--force makes hashcat ignore driver and device warnings; results may not reflect a healthy setup");
+    }
+    HashcatOptions {
+        workload: args.workload,
+        force: args.force,
+        potfile_disable: args.no_potfile,
+        potfile_path: args.potfile_path.as_ref().map(PathBuf::from),
+        runtime_secs: None,
+    }
+}
+
+/// The GPUs picked with `-g/--gpus` and `--uuid`; with neither, every GPU.
+fn gpu_filter(args: &GpuSelectArgs) -> GpuFilter {
+    GpuFilter {
+        indices: args.gpus.clone(),
+        uuids: args.uuid.clone(),
+    }
+}
@@ -0,0 +1,6 @@
+use crate::output::output_data;
+use crate::version::version_info;
+
+pub fn handle_version_command(format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    output_data(&version_info(), format)
+}
@@ -1,7 +1,11 @@
-use crate::cli::VmCommands;
-use crate::output::output_data;
+use crate::cli::{Hypervisor, VmCommands, VmTargetArgs};
+use crate::config::Config;
+use crate::hardware::{lookup_pci_ids, parse_pci_id};
+use crate::http::{api_url, check_response, idempotency_key, post_json};
+use crate::output::{confirm_action, output_data, require_structured_output, status};
 use serde::{Deserialize, Serialize};
 use std::io::{self, Write};
+use std::collections::HashMap;
 use std::process::Command;
 use std::fs;
 use std::path::Path;
@@ -14,18 +18,30 @@ struct VmInfo {
     uuid: Option<String>,
 }
 
-pub fn handle_vm_command(cmd: &VmCommands) -> Result<(), Box<dyn std::error::Error>> {
+pub fn handle_vm_command(cmd: &VmCommands, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
     match cmd {
         VmCommands::List { hypervisor, format } => {
-            list_vms(hypervisor, format)?;
+            list_vms(*hypervisor, format)?;
         }
         
-        VmCommands::Start { name, hypervisor } => {
-            start_vm(name, hypervisor)?;
+        VmCommands::Start { target, hypervisor } => {
+            for_each_vm(target, *hypervisor, "start", |name| start_vm(name, *hypervisor))?;
         }
         
-        VmCommands::Stop { name, hypervisor, force } => {
-            stop_vm(name, hypervisor, *force)?;
+        VmCommands::Stop { target, hypervisor, force } => {
+            for_each_vm(target, *hypervisor, "stop", |name| stop_vm(name, *hypervisor, *force))?;
+        }
+        
+        VmCommands::Suspend { name, hypervisor } => {
+            suspend_vm(name, *hypervisor)?;
+        }
+        
+        VmCommands::Resume { name, hypervisor } => {
+            resume_vm(name, *hypervisor)?;
+        }
+        
+        VmCommands::Autostart { name, hypervisor, enable, disable: _ } => {
+            set_vm_autostart(name, *hypervisor, *enable)?;
         }
         
         VmCommands::Create { 
@@ -34,60 +50,215 @@ pub fn handle_vm_command(cmd: &VmCommands) -> Result<(), Box<dyn std::error::Err
             vcpus, 
             memory, 
             disk, 
+            disk_path,
+            pool,
+            disk_format,
             os_variant, 
             iso, 
-            network 
+            network,
+            cloud_init,
+            format,
         } => {
-            create_vm(name, hypervisor, *vcpus, *memory, *disk, os_variant.as_deref(), iso.as_deref(), network)?;
+            create_vm(&CreateVmOptions {
+                name,
+                hypervisor: *hypervisor,
+                vcpus: *vcpus,
+                memory: *memory,
+                disk: *disk,
+                disk_path: disk_path.as_deref(),
+                pool: pool.as_deref(),
+                disk_format: disk_format.as_deref(),
+                os_variant: os_variant.as_deref(),
+                iso: iso.as_deref(),
+                network,
+                cloud_init: cloud_init.as_deref(),
+            }, format)?;
         }
         
         VmCommands::Delete { name, hypervisor, remove_storage, yes } => {
-            delete_vm(name, hypervisor, *remove_storage, *yes)?;
+            delete_vm(name, *hypervisor, *remove_storage, *yes)?;
         }
         
         VmCommands::Status { name, hypervisor, format } => {
-            vm_status(name, hypervisor, format)?;
+            vm_status(name, *hypervisor, format)?;
         }
         
-        VmCommands::Reboot { name, hypervisor, force } => {
-            reboot_vm(name, hypervisor, *force)?;
+        VmCommands::Reboot { target, hypervisor, force } => {
+            for_each_vm(target, *hypervisor, "reboot", |name| reboot_vm(name, *hypervisor, *force))?;
         }
         
-        VmCommands::PostInventory { url, hypervisor } => {
-            println!("Collecting VM inventory...");
-            let inventory = collect_vm_inventory(hypervisor)?;
-            
-            println!("Host MAC address: {}", inventory.host_mac_address);
+        VmCommands::Console { name, hypervisor } => {
+            vm_console(name, *hypervisor)?;
+        }
+        
+        VmCommands::Pools { format } => {
+            let pools = collect_storage_pools()?;
+            output_data(&pools, format)?;
+        }
+        
+        VmCommands::Networks { format } => {
+            let networks = collect_libvirt_networks()?;
+            if networks.is_empty() && format == "pretty" {
+                println!("No libvirt networks defined");
+            } else {
+                output_data(&networks, format)?;
+            }
+        }
+        
+        VmCommands::Metrics { name, hypervisor, format, watch } => {
+            match watch {
+                Some(interval) => loop {
+                    let metrics = collect_vm_metrics(name, *hypervisor)?;
+                    output_data(&metrics, format)?;
+                    std::thread::sleep(std::time::Duration::from_secs((*interval).max(1)));
+                },
+                None => {
+                    let metrics = collect_vm_metrics(name, *hypervisor)?;
+                    output_data(&metrics, format)?;
+                }
+            }
+        }
+        
+        VmCommands::PostInventory { url, hypervisor, path } => {
+            status!("Collecting VM inventory...");
+            let inventory = collect_vm_inventory(*hypervisor)?;
             
-            let api_url = format!("{}/api/v1/vms/inventory", url.trim_end_matches('/'));
-            println!("Posting VM inventory to: {}", api_url);
+            status!("Host MAC address: {}", inventory.host_mac_address);
             
-            let client = reqwest::blocking::Client::new();
-            let response = client
-                .post(&api_url)
-                .json(&inventory)
-                .send()?;
+            let api_url = api_url(url, &config.vm_inventory_path, path.as_deref());
+            status!("Posting VM inventory to: {}", api_url);
             
-            if response.status().is_success() {
-                let result: serde_json::Value = response.json()?;
-                println!("✓ Success!");
-                println!("{}", serde_json::to_string_pretty(&result)?);
-            } else {
-                let status = response.status();
-                let error_text = response.text()?;
-                eprintln!("✗ Error: HTTP {}", status);
-                eprintln!("{}", error_text);
-                return Err(format!("Failed to post VM inventory: HTTP {}", status).into());
-            }
+            let response = post_json(config, &api_url, &inventory, Some(&inventory.idempotency_key()))?;
+            let result = check_response(response, config)
+                .map_err(|e| format!("Failed to post VM inventory: {}", e))?;
+            status!("✓ Success!");
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
+    }
+    Ok(())
+}
+
+// Bulk actions on more VMs than this ask first unless --yes
+const BULK_CONFIRM_THRESHOLD: usize = 5;
+
+// Run `action` on the named VM, or on every VM selected by --all/--match
+fn for_each_vm(
+    target: &VmTargetArgs,
+    hypervisor: Hypervisor,
+    verb: &str,
+    action: impl Fn(&str) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(name) = &target.name {
+        return action(name);
+    }
+    
+    let names = select_vm_names(list_vm_names(hypervisor)?, target.match_pattern.as_deref())?;
+    run_on_vms(&names, target.yes, verb, action)
+}
+
+// Keep the names matching the --match glob (all of them without one)
+fn select_vm_names(mut names: Vec<String>, pattern: Option<&str>) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    if let Some(pattern) = pattern {
+        let glob = glob::Pattern::new(pattern).map_err(|e| format!("Invalid pattern {}: {}", pattern, e))?;
+        names.retain(|name| glob.matches(name));
+    }
+    if names.is_empty() {
+        return Err(match pattern {
+            Some(pattern) => format!("No VMs match '{}'", pattern).into(),
+            None => "No VMs defined".into(),
+        });
+    }
+    Ok(names)
+}
+
+// Run `action` on each VM (asking first for large batches unless --yes),
+// carrying on past failures and reporting them together at the end
+fn run_on_vms(
+    names: &[String],
+    yes: bool,
+    verb: &str,
+    action: impl Fn(&str) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if names.len() > BULK_CONFIRM_THRESHOLD
+        && !yes
+        && !confirm_action(&format!("This will {} {} VMs: {}", verb, names.len(), names.join(", ")))
+    {
+        println!("Cancelled.");
+        return Ok(());
+    }
+    
+    let mut failed = Vec::new();
+    for name in names {
+        if let Err(e) = action(name) {
+            eprintln!("✗ {}: {}", name, e.to_string().trim());
+            failed.push(name.as_str());
         }
     }
+    
+    status!("{} of {} VM(s) succeeded ({})", names.len() - failed.len(), names.len(), verb);
+    if !failed.is_empty() {
+        return Err(format!("Failed to {} {} VM(s): {}", verb, failed.len(), failed.join(", ")).into());
+    }
     Ok(())
 }
 
-fn list_vms(hypervisor: &str, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+// Names of every VM defined on this host, running or not
+fn list_vm_names(hypervisor: Hypervisor) -> Result<Vec<String>, Box<dyn std::error::Error>> {
     match hypervisor {
-        "kvm" | "qemu" => {
-            println!("Listing VMs via virsh...");
+        Hypervisor::Kvm | Hypervisor::Qemu => {
+            let output = Command::new("virsh")
+                .args(["list", "--all", "--name"])
+                .output()?;
+            if !output.status.success() {
+                return Err(format!("virsh command failed: {}", String::from_utf8_lossy(&output.stderr)).into());
+            }
+            Ok(parse_virsh_names(&String::from_utf8_lossy(&output.stdout)))
+        }
+        
+        Hypervisor::VirtualBox => {
+            let output = Command::new("VBoxManage")
+                .args(["list", "vms"])
+                .output()?;
+            if !output.status.success() {
+                return Err(format!("VBoxManage command failed: {}", String::from_utf8_lossy(&output.stderr)).into());
+            }
+            Ok(parse_vbox_vm_names(&String::from_utf8_lossy(&output.stdout)))
+        }
+        
+        Hypervisor::Vmware => Err(not_implemented(hypervisor, "list")),
+    }
+}
+
+// `virsh list --name` prints one name per line and a trailing blank line
+fn parse_virsh_names(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// `VBoxManage list vms` prints `"name" {uuid}`; names may contain quotes and spaces
+fn parse_vbox_vm_names(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix('"')?.rsplit_once("\" ").map(|(name, _)| name.to_string()))
+        .collect()
+}
+
+// Error for actions that have no backend for the selected hypervisor yet
+fn not_implemented(hypervisor: Hypervisor, action: &str) -> Box<dyn std::error::Error> {
+    format!("'vm {}' is not yet implemented for hypervisor '{}'", action, hypervisor).into()
+}
+
+fn list_vms(hypervisor: Hypervisor, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    match hypervisor {
+        Hypervisor::Kvm | Hypervisor::Qemu => {
+            if format == "pretty" {
+                require_structured_output()?;
+            }
+            status!("Listing VMs via virsh...");
             let output = Command::new("virsh")
                 .args(&["list", "--all"])
                 .output()?;
@@ -107,8 +278,9 @@ fn list_vms(hypervisor: &str, format: &str) -> Result<(), Box<dyn std::error::Er
             }
         }
         
-        "virtualbox" => {
-            println!("Listing VMs via VBoxManage...");
+        Hypervisor::VirtualBox => {
+            require_structured_output()?;
+            status!("Listing VMs via VBoxManage...");
             let output = Command::new("VBoxManage")
                 .args(&["list", "vms", "--long"])
                 .output()?;
@@ -120,107 +292,290 @@ fn list_vms(hypervisor: &str, format: &str) -> Result<(), Box<dyn std::error::Er
             println!("{}", String::from_utf8_lossy(&output.stdout));
         }
         
-        _ => {
-            return Err(format!("Unsupported hypervisor: {}", hypervisor).into());
+        Hypervisor::Vmware => {
+            return Err(not_implemented(hypervisor, "list"));
         }
     }
     
     Ok(())
 }
 
-fn start_vm(name: &str, hypervisor: &str) -> Result<(), Box<dyn std::error::Error>> {
+fn start_vm(name: &str, hypervisor: Hypervisor) -> Result<(), Box<dyn std::error::Error>> {
     match hypervisor {
-        "kvm" | "qemu" => {
-            println!("Starting VM '{}' via virsh...", name);
+        Hypervisor::Kvm | Hypervisor::Qemu => {
+            status!("Starting VM '{}' via virsh...", name);
             let output = Command::new("virsh")
                 .args(&["start", name])
                 .output()?;
             
             if output.status.success() {
-                println!("✓ VM '{}' started successfully", name);
+                status!("✓ VM '{}' started successfully", name);
             } else {
                 let error = String::from_utf8_lossy(&output.stderr);
                 return Err(format!("Failed to start VM: {}", error).into());
             }
         }
         
-        "virtualbox" => {
-            println!("Starting VM '{}' via VBoxManage...", name);
+        Hypervisor::VirtualBox => {
+            status!("Starting VM '{}' via VBoxManage...", name);
             let output = Command::new("VBoxManage")
                 .args(&["startvm", name, "--type", "headless"])
                 .output()?;
             
             if output.status.success() {
-                println!("✓ VM '{}' started successfully", name);
+                status!("✓ VM '{}' started successfully", name);
             } else {
                 let error = String::from_utf8_lossy(&output.stderr);
                 return Err(format!("Failed to start VM: {}", error).into());
             }
         }
         
-        _ => {
-            return Err(format!("Unsupported hypervisor: {}", hypervisor).into());
+        Hypervisor::Vmware => {
+            return Err(not_implemented(hypervisor, "start"));
         }
     }
     
     Ok(())
 }
 
-fn stop_vm(name: &str, hypervisor: &str, force: bool) -> Result<(), Box<dyn std::error::Error>> {
+fn stop_vm(name: &str, hypervisor: Hypervisor, force: bool) -> Result<(), Box<dyn std::error::Error>> {
     match hypervisor {
-        "kvm" | "qemu" => {
+        Hypervisor::Kvm | Hypervisor::Qemu => {
             let action = if force { "destroy" } else { "shutdown" };
-            println!("{} VM '{}' via virsh...", if force { "Forcing stop of" } else { "Shutting down" }, name);
+            status!("{} VM '{}' via virsh...", if force { "Forcing stop of" } else { "Shutting down" }, name);
             
             let output = Command::new("virsh")
                 .args(&[action, name])
                 .output()?;
             
             if output.status.success() {
-                println!("✓ VM '{}' {} successfully", name, if force { "stopped" } else { "shutdown initiated" });
+                status!("✓ VM '{}' {} successfully", name, if force { "stopped" } else { "shutdown initiated" });
             } else {
                 let error = String::from_utf8_lossy(&output.stderr);
                 return Err(format!("Failed to stop VM: {}", error).into());
             }
         }
         
-        "virtualbox" => {
+        Hypervisor::VirtualBox => {
             let action_type = if force { "poweroff" } else { "acpipowerbutton" };
-            println!("{} VM '{}' via VBoxManage...", if force { "Forcing stop of" } else { "Shutting down" }, name);
+            status!("{} VM '{}' via VBoxManage...", if force { "Forcing stop of" } else { "Shutting down" }, name);
             
             let output = Command::new("VBoxManage")
                 .args(&["controlvm", name, action_type])
                 .output()?;
             
             if output.status.success() {
-                println!("✓ VM '{}' {} successfully", name, if force { "stopped" } else { "shutdown initiated" });
+                status!("✓ VM '{}' {} successfully", name, if force { "stopped" } else { "shutdown initiated" });
             } else {
                 let error = String::from_utf8_lossy(&output.stderr);
                 return Err(format!("Failed to stop VM: {}", error).into());
             }
         }
         
-        _ => {
-            return Err(format!("Unsupported hypervisor: {}", hypervisor).into());
+        Hypervisor::Vmware => {
+            return Err(not_implemented(hypervisor, "stop"));
+        }
+    }
+    
+    Ok(())
+}
+
+fn suspend_vm(name: &str, hypervisor: Hypervisor) -> Result<(), Box<dyn std::error::Error>> {
+    match hypervisor {
+        Hypervisor::Kvm | Hypervisor::Qemu => {
+            status!("Suspending VM '{}' via virsh...", name);
+            let output = Command::new("virsh")
+                .args(["suspend", name])
+                .output()?;
+            
+            if !output.status.success() {
+                let error = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("Failed to suspend VM: {}", error).into());
+            }
+        }
+        
+        Hypervisor::VirtualBox => {
+            status!("Suspending VM '{}' via VBoxManage...", name);
+            let output = Command::new("VBoxManage")
+                .args(["controlvm", name, "pause"])
+                .output()?;
+            
+            if !output.status.success() {
+                let error = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("Failed to suspend VM: {}", error).into());
+            }
+        }
+        
+        Hypervisor::Vmware => {
+            return Err(not_implemented(hypervisor, "suspend"));
+        }
+    }
+    
+    let state = query_vm_state(name, hypervisor).unwrap_or_else(|| "paused".to_string());
+    status!("✓ VM '{}' suspended successfully (state: {})", name, state);
+    Ok(())
+}
+
+fn resume_vm(name: &str, hypervisor: Hypervisor) -> Result<(), Box<dyn std::error::Error>> {
+    match hypervisor {
+        Hypervisor::Kvm | Hypervisor::Qemu => {
+            status!("Resuming VM '{}' via virsh...", name);
+            let output = Command::new("virsh")
+                .args(["resume", name])
+                .output()?;
+            
+            if !output.status.success() {
+                let error = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("Failed to resume VM: {}", error).into());
+            }
+        }
+        
+        Hypervisor::VirtualBox => {
+            status!("Resuming VM '{}' via VBoxManage...", name);
+            let output = Command::new("VBoxManage")
+                .args(["controlvm", name, "resume"])
+                .output()?;
+            
+            if !output.status.success() {
+                let error = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("Failed to resume VM: {}", error).into());
+            }
+        }
+        
+        Hypervisor::Vmware => {
+            return Err(not_implemented(hypervisor, "resume"));
+        }
+    }
+    
+    let state = query_vm_state(name, hypervisor).unwrap_or_else(|| "running".to_string());
+    status!("✓ VM '{}' resumed successfully (state: {})", name, state);
+    Ok(())
+}
+
+// Read back the VM's current state, normalized to the inventory state values
+fn query_vm_state(name: &str, hypervisor: Hypervisor) -> Option<String> {
+    match hypervisor {
+        Hypervisor::Kvm | Hypervisor::Qemu => {
+            let output = Command::new("virsh")
+                .args(["domstate", name])
+                .output()
+                .ok()?;
+            
+            if !output.status.success() {
+                return None;
+            }
+            
+            let state = String::from_utf8_lossy(&output.stdout);
+            Some(normalize_vm_state(state.trim()))
+        }
+        
+        Hypervisor::VirtualBox => {
+            let output = Command::new("VBoxManage")
+                .args(["showvminfo", name, "--machinereadable"])
+                .output()
+                .ok()?;
+            
+            if !output.status.success() {
+                return None;
+            }
+            
+            // VMState="paused"
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .find_map(|line| line.strip_prefix("VMState="))
+                .map(|state| normalize_vm_state(state.trim_matches('"')))
+        }
+        
+        _ => None,
+    }
+}
+
+fn set_vm_autostart(name: &str, hypervisor: Hypervisor, enable: bool) -> Result<(), Box<dyn std::error::Error>> {
+    match hypervisor {
+        Hypervisor::Kvm | Hypervisor::Qemu => {
+            status!("{} autostart for VM '{}' via virsh...", if enable { "Enabling" } else { "Disabling" }, name);
+            
+            let mut args = vec!["autostart"];
+            if !enable {
+                args.push("--disable");
+            }
+            args.push(name);
+            
+            let output = Command::new("virsh")
+                .args(&args)
+                .output()?;
+            
+            if !output.status.success() {
+                let error = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("Failed to configure autostart: {}", error).into());
+            }
+            
+            // Confirm what libvirt actually recorded
+            let dominfo = Command::new("virsh")
+                .args(["dominfo", name])
+                .output()?;
+            let autostart = String::from_utf8_lossy(&dominfo.stdout)
+                .lines()
+                .find_map(|line| line.strip_prefix("Autostart:").map(|v| v.trim().to_string()))
+                .unwrap_or_else(|| "unknown".to_string());
+            
+            status!("✓ VM '{}' autostart: {}", name, autostart);
+        }
+        
+        Hypervisor::VirtualBox => {
+            status!("{} autostart for VM '{}' via VBoxManage...", if enable { "Enabling" } else { "Disabling" }, name);
+            let output = Command::new("VBoxManage")
+                .args(["modifyvm", name, "--autostart-enabled", if enable { "on" } else { "off" }])
+                .output()?;
+            
+            if !output.status.success() {
+                let error = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("Failed to configure autostart: {}", error).into());
+            }
+            
+            status!("✓ VM '{}' autostart: {}", name, if enable { "enable" } else { "disable" });
+        }
+        
+        Hypervisor::Vmware => {
+            return Err(not_implemented(hypervisor, "autostart"));
         }
     }
     
     Ok(())
 }
 
-fn create_vm(
-    name: &str,
-    hypervisor: &str,
+/// Everything `vm create` needs to define a new VM, taken from the command line.
+struct CreateVmOptions<'a> {
+    name: &'a str,
+    hypervisor: Hypervisor,
     vcpus: u32,
-    memory: u32,
-    disk: u32,
-    os_variant: Option<&str>,
-    iso: Option<&str>,
-    network: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+    memory: u32, // MB
+    disk: u32,   // GB
+    disk_path: Option<&'a str>,
+    pool: Option<&'a str>,
+    disk_format: Option<&'a str>,
+    os_variant: Option<&'a str>,
+    iso: Option<&'a str>,
+    network: &'a str,
+    cloud_init: Option<&'a str>,
+}
+
+fn create_vm(options: &CreateVmOptions, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let CreateVmOptions { name, hypervisor, vcpus, memory, disk, disk_path, pool, disk_format, os_variant, iso, network, cloud_init } = *options;
+    if cloud_init.is_some() && !matches!(hypervisor, Hypervisor::Kvm | Hypervisor::Qemu) {
+        return Err("--cloud-init is only supported for kvm/qemu".into());
+    }
+    // virt-install's own output is printed as-is ahead of the result
+    if format == "pretty" && matches!(hypervisor, Hypervisor::Kvm | Hypervisor::Qemu) {
+        require_structured_output()?;
+    }
+    if pool.is_some() && !matches!(hypervisor, Hypervisor::Kvm | Hypervisor::Qemu) {
+        return Err("--pool is only supported for kvm/qemu".into());
+    }
+    
     match hypervisor {
-        "kvm" | "qemu" => {
-            println!("Creating VM '{}' via virt-install...", name);
+        Hypervisor::Kvm | Hypervisor::Qemu => {
+            status!("Creating VM '{}' via virt-install...", name);
             
             let mut args = vec![
                 "--name".to_string(),
@@ -230,7 +585,7 @@ fn create_vm(
                 "--memory".to_string(),
                 memory.to_string(),
                 "--disk".to_string(),
-                format!("size={}", disk),
+                kvm_disk_spec(disk, disk_path, pool, disk_format)?,
             ];
             
             // Add OS variant if provided
@@ -247,6 +602,14 @@ fn create_vm(
                 args.push("--pxe".to_string());
             }
             
+            // Attach cloud-init seed as a second cdrom
+            if let Some(user_data) = cloud_init {
+                let seed_path = build_cloud_init_seed(name, user_data)?;
+                status!("Built cloud-init seed image: {}", seed_path);
+                args.push("--disk".to_string());
+                args.push(format!("path={},device=cdrom", seed_path));
+            }
+            
             // Add network
             if network != "none" {
                 args.push("--network".to_string());
@@ -266,17 +629,33 @@ fn create_vm(
                 .args(&args)
                 .output()?;
             
-            if output.status.success() {
-                println!("✓ VM '{}' created successfully", name);
-                println!("{}", String::from_utf8_lossy(&output.stdout));
-            } else {
+            if !output.status.success() {
                 let error = String::from_utf8_lossy(&output.stderr);
                 return Err(format!("Failed to create VM: {}", error).into());
             }
+            status!("✓ VM '{}' created successfully", name);
+            if format == "pretty" {
+                println!("{}", String::from_utf8_lossy(&output.stdout));
+            }
+            
+            let result = VmCreateResult {
+                name: name.to_string(),
+                hypervisor: hypervisor.to_string(),
+                uuid: query_kvm_uuid(name),
+                vcpus,
+                memory_mb: memory,
+                disk_gb: disk,
+                disk_path: collect_kvm_vm_disks(name)
+                    .ok()
+                    .and_then(|disks| disks.into_iter().next())
+                    .map(|d| d.disk_path),
+                vnc_port: query_kvm_vnc_port(name),
+            };
+            output_data(&result, format)?;
         }
         
-        "virtualbox" => {
-            println!("Creating VM '{}' via VBoxManage...", name);
+        Hypervisor::VirtualBox => {
+            status!("Creating VM '{}' via VBoxManage...", name);
             
             // Create the VM
             let output = Command::new("VBoxManage")
@@ -294,9 +673,17 @@ fn create_vm(
                 .output()?;
             
             // Create disk
-            let disk_path = format!("/var/lib/virtualbox/{}.vdi", name);
+            let vbox_format = match disk_format.map(|f| f.to_lowercase()).as_deref() {
+                None | Some("vdi") => "VDI",
+                Some("vmdk") => "VMDK",
+                Some(other) => return Err(format!("Unsupported disk format for VirtualBox: {} (use vdi or vmdk)", other).into()),
+            };
+            let disk_path = match disk_path {
+                Some(path) => path.to_string(),
+                None => format!("/var/lib/virtualbox/{}.{}", name, vbox_format.to_lowercase()),
+            };
             Command::new("VBoxManage")
-                .args(&["createhd", "--filename", &disk_path, "--size", &(disk * 1024).to_string()])
+                .args(&["createhd", "--filename", &disk_path, "--size", &(disk * 1024).to_string(), "--format", vbox_format])
                 .output()?;
             
             // Attach disk
@@ -308,18 +695,145 @@ fn create_vm(
                 .args(&["storageattach", name, "--storagectl", "SATA", "--port", "0", "--device", "0", "--type", "hdd", "--medium", &disk_path])
                 .output()?;
             
-            println!("✓ VM '{}' created successfully", name);
+            status!("✓ VM '{}' created successfully", name);
+            
+            let result = VmCreateResult {
+                name: name.to_string(),
+                hypervisor: hypervisor.to_string(),
+                uuid: query_vbox_uuid(name),
+                vcpus,
+                memory_mb: memory,
+                disk_gb: disk,
+                disk_path: Some(disk_path),
+                vnc_port: None,
+            };
+            output_data(&result, format)?;
         }
         
-        _ => {
-            return Err(format!("Unsupported hypervisor: {}", hypervisor).into());
+        Hypervisor::Vmware => {
+            return Err(not_implemented(hypervisor, "create"));
         }
     }
     
     Ok(())
 }
 
-fn delete_vm(name: &str, hypervisor: &str, remove_storage: bool, yes: bool) -> Result<(), Box<dyn std::error::Error>> {
+/// What `vm create` made, so scripts can act on the new VM right away.
+#[derive(Debug, Serialize)]
+struct VmCreateResult {
+    name: String,
+    hypervisor: String,
+    uuid: Option<String>,
+    vcpus: u32,
+    memory_mb: u32,
+    disk_gb: u32,
+    disk_path: Option<String>,
+    vnc_port: Option<u16>,
+}
+
+// UUID of a defined libvirt domain, from `virsh dominfo`
+fn query_kvm_uuid(name: &str) -> Option<String> {
+    let output = Command::new("virsh").args(["dominfo", name]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let info = parse_virsh_dominfo(&String::from_utf8_lossy(&output.stdout)).ok()?;
+    info.get("uuid").and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+fn query_kvm_vnc_port(name: &str) -> Option<u16> {
+    let output = Command::new("virsh").args(["vncdisplay", name]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_vnc_display(&String::from_utf8_lossy(&output.stdout))
+}
+
+// `virsh vncdisplay` prints e.g. "127.0.0.1:0"; display N listens on 5900 + N
+fn parse_vnc_display(output: &str) -> Option<u16> {
+    let display: u16 = output.trim().rsplit(':').next()?.parse().ok()?;
+    5900u16.checked_add(display)
+}
+
+fn query_vbox_uuid(name: &str) -> Option<String> {
+    let output = Command::new("VBoxManage").args(["showvminfo", name, "--machinereadable"]).output().ok()?;
+    parse_vbox_uuid(&String::from_utf8_lossy(&output.stdout))
+}
+
+// UUID="..." line of `VBoxManage showvminfo --machinereadable`
+fn parse_vbox_uuid(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("UUID=\"")?.strip_suffix('"').map(|s| s.to_string()))
+}
+
+// Build the virt-install --disk argument from size, optional path and format
+fn kvm_disk_spec(size_gb: u32, path: Option<&str>, pool: Option<&str>, format: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+    let mut spec = match (path, pool) {
+        (Some(p), _) => format!("path={},size={}", p, size_gb),
+        (None, Some(pool)) => format!("pool={},size={}", pool, size_gb),
+        (None, None) => format!("size={}", size_gb),
+    };
+    
+    match format.map(|f| f.to_lowercase()).as_deref() {
+        None => {}
+        Some("qcow2") => spec.push_str(",format=qcow2"),
+        // Fully allocate raw images up front for predictable I/O performance
+        Some("raw") => spec.push_str(",format=raw,sparse=no"),
+        Some(other) => return Err(format!("Unsupported disk format for KVM: {} (use qcow2 or raw)", other).into()),
+    }
+    
+    Ok(spec)
+}
+
+// Build a NoCloud seed ISO from a user-data file, preferring cloud-localds
+fn build_cloud_init_seed(vm_name: &str, user_data_path: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let user_data = fs::read_to_string(user_data_path)
+        .map_err(|e| format!("Failed to read cloud-init user-data {}: {}", user_data_path, e))?;
+    
+    serde_yaml::from_str::<serde_yaml::Value>(&user_data)
+        .map_err(|e| format!("cloud-init user-data {} is not valid YAML: {}", user_data_path, e))?;
+    
+    let seed_path = format!("/var/lib/libvirt/images/{}-seed.iso", vm_name);
+    
+    let has_cloud_localds = Command::new("which")
+        .arg("cloud-localds")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+    
+    let output = if has_cloud_localds {
+        Command::new("cloud-localds")
+            .args([seed_path.as_str(), user_data_path])
+            .output()?
+    } else {
+        // NoCloud expects a volume labelled "cidata" containing user-data and meta-data
+        let staging = std::env::temp_dir().join(format!("{}-cidata", vm_name));
+        fs::create_dir_all(&staging)?;
+        fs::write(staging.join("user-data"), &user_data)?;
+        fs::write(
+            staging.join("meta-data"),
+            format!("instance-id: {}\nlocal-hostname: {}\n", vm_name, vm_name),
+        )?;
+        
+        let output = Command::new("genisoimage")
+            .args(["-output", seed_path.as_str(), "-volid", "cidata", "-joliet", "-rock"])
+            .arg(staging.join("user-data"))
+            .arg(staging.join("meta-data"))
+            .output();
+        let _ = fs::remove_dir_all(&staging);
+        output?
+    };
+    
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to build cloud-init seed image: {}", error).into());
+    }
+    
+    Ok(seed_path)
+}
+
+fn delete_vm(name: &str, hypervisor: Hypervisor, remove_storage: bool, yes: bool) -> Result<(), Box<dyn std::error::Error>> {
     if !yes {
         print!("Are you sure you want to delete VM '{}'? [y/N]: ", name);
         io::stdout().flush()?;
@@ -334,8 +848,8 @@ fn delete_vm(name: &str, hypervisor: &str, remove_storage: bool, yes: bool) -> R
     }
     
     match hypervisor {
-        "kvm" | "qemu" => {
-            println!("Deleting VM '{}' via virsh...", name);
+        Hypervisor::Kvm | Hypervisor::Qemu => {
+            status!("Deleting VM '{}' via virsh...", name);
             
             // Stop VM if running
             let _ = Command::new("virsh")
@@ -353,15 +867,15 @@ fn delete_vm(name: &str, hypervisor: &str, remove_storage: bool, yes: bool) -> R
                 .output()?;
             
             if output.status.success() {
-                println!("✓ VM '{}' deleted successfully", name);
+                status!("✓ VM '{}' deleted successfully", name);
             } else {
                 let error = String::from_utf8_lossy(&output.stderr);
                 return Err(format!("Failed to delete VM: {}", error).into());
             }
         }
         
-        "virtualbox" => {
-            println!("Deleting VM '{}' via VBoxManage...", name);
+        Hypervisor::VirtualBox => {
+            status!("Deleting VM '{}' via VBoxManage...", name);
             
             // Stop VM if running
             let _ = Command::new("VBoxManage")
@@ -382,25 +896,28 @@ fn delete_vm(name: &str, hypervisor: &str, remove_storage: bool, yes: bool) -> R
                 .output()?;
             
             if output.status.success() {
-                println!("✓ VM '{}' deleted successfully", name);
+                status!("✓ VM '{}' deleted successfully", name);
             } else {
                 let error = String::from_utf8_lossy(&output.stderr);
                 return Err(format!("Failed to delete VM: {}", error).into());
             }
         }
         
-        _ => {
-            return Err(format!("Unsupported hypervisor: {}", hypervisor).into());
+        Hypervisor::Vmware => {
+            return Err(not_implemented(hypervisor, "delete"));
         }
     }
     
     Ok(())
 }
 
-fn vm_status(name: &str, hypervisor: &str, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+fn vm_status(name: &str, hypervisor: Hypervisor, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if format == "pretty" {
+        require_structured_output()?;
+    }
     match hypervisor {
-        "kvm" | "qemu" => {
-            println!("Getting status for VM '{}'...", name);
+        Hypervisor::Kvm | Hypervisor::Qemu => {
+            status!("Getting status for VM '{}'...", name);
             let output = Command::new("virsh")
                 .args(&["dominfo", name])
                 .output()?;
@@ -410,79 +927,515 @@ fn vm_status(name: &str, hypervisor: &str, format: &str) -> Result<(), Box<dyn s
             }
             
             let stdout = String::from_utf8_lossy(&output.stdout);
+            let (display_uri, display_port) = query_kvm_display(name);
             
             if format == "pretty" {
                 println!("{}", stdout);
+                print_display(display_uri.as_deref(), display_port);
             } else {
                 // Parse and format as JSON/YAML
-                let info = parse_virsh_dominfo(&stdout)?;
-                output_data(&info, format)?;
+                let status = VmStatus {
+                    info: parse_virsh_dominfo(&stdout)?,
+                    display_uri,
+                    display_port,
+                };
+                output_data(&status, format)?;
             }
         }
         
-        "virtualbox" => {
-            println!("Getting status for VM '{}'...", name);
+        Hypervisor::VirtualBox => {
+            status!("Getting status for VM '{}'...", name);
             let output = Command::new("VBoxManage")
-                .args(&["showvminfo", name])
+                .args(&["showvminfo", name, "--machinereadable"])
                 .output()?;
             
             if !output.status.success() {
                 return Err(format!("VBoxManage command failed: {}", String::from_utf8_lossy(&output.stderr)).into());
             }
             
-            println!("{}", String::from_utf8_lossy(&output.stdout));
+            let info = parse_vbox_machinereadable(&String::from_utf8_lossy(&output.stdout));
+            // VirtualBox has no console URI; the VRDE (RDP) server port is the way in
+            let display_port = vbox_vrde_port(&info);
+            
+            if format == "pretty" {
+                let output = Command::new("VBoxManage")
+                    .args(["showvminfo", name])
+                    .output()?;
+                println!("{}", String::from_utf8_lossy(&output.stdout));
+                print_display(None, display_port);
+            } else {
+                let status = VmStatus {
+                    info: serde_json::Value::Object(info),
+                    display_uri: None,
+                    display_port,
+                };
+                output_data(&status, format)?;
+            }
         }
         
-        _ => {
-            return Err(format!("Unsupported hypervisor: {}", hypervisor).into());
+        Hypervisor::Vmware => {
+            return Err(not_implemented(hypervisor, "status"));
         }
     }
     
     Ok(())
 }
 
-fn reboot_vm(name: &str, hypervisor: &str, force: bool) -> Result<(), Box<dyn std::error::Error>> {
+/// `vm status` output: the hypervisor's own fields plus how to reach the console.
+#[derive(Debug, Serialize)]
+struct VmStatus {
+    #[serde(flatten)]
+    info: serde_json::Value,
+    display_uri: Option<String>, // e.g. vnc://127.0.0.1:0 or spice://127.0.0.1:5901
+    display_port: Option<u16>,
+}
+
+fn print_display(uri: Option<&str>, port: Option<u16>) {
+    match (uri, port) {
+        (None, None) => println!("Display:        none (headless)"),
+        (Some(uri), Some(port)) => println!("Display:        {} (port {})", uri, port),
+        (Some(uri), None) => println!("Display:        {}", uri),
+        (None, Some(port)) => println!("Display:        port {}", port),
+    }
+}
+
+// Console URI from `virsh domdisplay` and the listening port from the
+// <graphics> element; both are None for headless or shut-off domains
+fn query_kvm_display(name: &str) -> (Option<String>, Option<u16>) {
+    let uri = Command::new("virsh")
+        .args(["domdisplay", name])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|uri| !uri.is_empty());
+    
+    // Autoport graphics report port='-1' until the domain is running
+    let port = Command::new("virsh")
+        .args(["dumpxml", name])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| parse_graphics_port(&String::from_utf8_lossy(&o.stdout)));
+    
+    (uri, port)
+}
+
+// VRDE server port from parsed `showvminfo --machinereadable`, when VRDE is on
+fn vbox_vrde_port(info: &serde_json::Map<String, serde_json::Value>) -> Option<u16> {
+    if info.get("vrde").and_then(|v| v.as_str()) != Some("on") {
+        return None;
+    }
+    info.get("vrdeport").and_then(|v| v.as_str()).and_then(|p| p.parse().ok())
+}
+
+// `VBoxManage showvminfo --machinereadable` prints key="value" lines
+fn parse_vbox_machinereadable(output: &str) -> serde_json::Map<String, serde_json::Value> {
+    let mut info = serde_json::Map::new();
+    for line in output.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            let unquote = |s: &str| s.trim_matches('"').to_string();
+            info.insert(unquote(key), serde_json::Value::String(unquote(value)));
+        }
+    }
+    info
+}
+
+fn reboot_vm(name: &str, hypervisor: Hypervisor, force: bool) -> Result<(), Box<dyn std::error::Error>> {
     match hypervisor {
-        "kvm" | "qemu" => {
+        Hypervisor::Kvm | Hypervisor::Qemu => {
             let action = if force { "reset" } else { "reboot" };
-            println!("{} VM '{}'...", if force { "Resetting" } else { "Rebooting" }, name);
+            status!("{} VM '{}'...", if force { "Resetting" } else { "Rebooting" }, name);
             
             let output = Command::new("virsh")
                 .args(&[action, name])
                 .output()?;
             
             if output.status.success() {
-                println!("✓ VM '{}' {} successfully", name, if force { "reset" } else { "reboot initiated" });
+                status!("✓ VM '{}' {} successfully", name, if force { "reset" } else { "reboot initiated" });
             } else {
                 let error = String::from_utf8_lossy(&output.stderr);
                 return Err(format!("Failed to reboot VM: {}", error).into());
             }
         }
         
-        "virtualbox" => {
+        Hypervisor::VirtualBox => {
             let action_type = if force { "reset" } else { "acpireboot" };
-            println!("{} VM '{}'...", if force { "Resetting" } else { "Rebooting" }, name);
+            status!("{} VM '{}'...", if force { "Resetting" } else { "Rebooting" }, name);
             
             let output = Command::new("VBoxManage")
                 .args(&["controlvm", name, action_type])
                 .output()?;
             
             if output.status.success() {
-                println!("✓ VM '{}' {} successfully", name, if force { "reset" } else { "reboot initiated" });
+                status!("✓ VM '{}' {} successfully", name, if force { "reset" } else { "reboot initiated" });
             } else {
                 let error = String::from_utf8_lossy(&output.stderr);
                 return Err(format!("Failed to reboot VM: {}", error).into());
             }
         }
         
-        _ => {
-            return Err(format!("Unsupported hypervisor: {}", hypervisor).into());
+        Hypervisor::Vmware => {
+            return Err(not_implemented(hypervisor, "reboot"));
+        }
+    }
+    
+    Ok(())
+}
+
+fn vm_console(name: &str, hypervisor: Hypervisor) -> Result<(), Box<dyn std::error::Error>> {
+    match hypervisor {
+        Hypervisor::Kvm | Hypervisor::Qemu => {
+            let xml = Command::new("virsh")
+                .args(["dumpxml", name])
+                .output()?;
+            
+            if !xml.status.success() {
+                return Err(format!("virsh command failed: {}", String::from_utf8_lossy(&xml.stderr)).into());
+            }
+            
+            let xml = String::from_utf8_lossy(&xml.stdout);
+            if !xml.contains("<serial") && !xml.contains("<console") {
+                return Err(format!(
+                    "VM '{}' has no serial console configured. Add one with \
+                     `virt-xml {} --add-device --serial pty` (or `virsh edit {}`) and restart the VM",
+                    name, name, name
+                ).into());
+            }
+            
+            println!("Connecting to console of VM '{}' (press Ctrl+] to exit)...", name);
+            
+            // Hand the terminal over to virsh until the user detaches
+            let status = Command::new("virsh")
+                .args(["console", name])
+                .status()?;
+            
+            if !status.success() {
+                return Err(format!("virsh console exited with {}", status).into());
+            }
+        }
+        
+        Hypervisor::VirtualBox => {
+            let output = Command::new("VBoxManage")
+                .args(["showvminfo", name, "--machinereadable"])
+                .output()?;
+            
+            if !output.status.success() {
+                return Err(format!("VBoxManage command failed: {}", String::from_utf8_lossy(&output.stderr)).into());
+            }
+            
+            let info = String::from_utf8_lossy(&output.stdout);
+            let value = |key: &str| {
+                info.lines()
+                    .find_map(|line| line.strip_prefix(key).and_then(|v| v.strip_prefix('=')))
+                    .map(|v| v.trim_matches('"').to_string())
+            };
+            
+            println!("Console connection info for VM '{}':", name);
+            
+            let vrde_enabled = value("vrde").as_deref() == Some("on");
+            match value("vrdeport") {
+                Some(port) if vrde_enabled => println!("  Remote display (VRDE/VNC): port {}", port),
+                _ => println!("  Remote display (VRDE/VNC): disabled"),
+            }
+            
+            match (value("uart1"), value("uartmode1")) {
+                (Some(uart), Some(mode)) if uart != "off" => println!("  Serial port 1: {} ({})", uart, mode),
+                _ => {
+                    println!("  Serial port 1: not configured");
+                    println!("  Add one with `VBoxManage modifyvm {} --uart1 0x3F8 4 --uartmode1 server /tmp/{}.sock`", name, name);
+                }
+            }
+        }
+        
+        Hypervisor::Vmware => {
+            return Err(not_implemented(hypervisor, "console"));
         }
     }
     
     Ok(())
 }
 
+#[derive(Debug, Serialize)]
+struct StoragePool {
+    name: String,
+    state: String,
+    pool_type: Option<String>,
+    autostart: Option<bool>,
+    persistent: Option<bool>,
+    capacity_bytes: Option<u64>,
+    allocation_bytes: Option<u64>,
+    available_bytes: Option<u64>,
+}
+
+fn collect_storage_pools() -> Result<Vec<StoragePool>, Box<dyn std::error::Error>> {
+    let output = Command::new("virsh")
+        .args(["pool-list", "--all", "--details"])
+        .output()?;
+    
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        if error.contains("failed to connect") {
+            return Err("Cannot connect to libvirt; is libvirtd running? (try `systemctl start libvirtd`)".into());
+        }
+        return Err(format!("virsh pool-list failed: {}", error).into());
+    }
+    
+    let mut pools = parse_virsh_pool_list(&String::from_utf8_lossy(&output.stdout));
+    for pool in &mut pools {
+        pool.pool_type = query_pool_type(&pool.name);
+    }
+    
+    Ok(pools)
+}
+
+// Helper function to parse `virsh pool-list --all --details` output
+fn parse_virsh_pool_list(output: &str) -> Vec<StoragePool> {
+    let mut pools = Vec::new();
+    
+    for line in output.lines().skip(2) { // Skip header lines
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 4 {
+            continue;
+        }
+        
+        // Sizes are "<value> <unit>" pairs, or "-" for inactive pools
+        let mut sizes = Vec::new();
+        let mut rest = parts[4..].iter();
+        while let Some(value) = rest.next() {
+            if *value == "-" {
+                sizes.push(None);
+            } else {
+                sizes.push(rest.next().and_then(|unit| parse_virsh_size(value, unit)));
+            }
+        }
+        
+        pools.push(StoragePool {
+            name: parts[0].to_string(),
+            state: parts[1].to_string(),
+            pool_type: None,
+            autostart: Some(parts[2] == "yes"),
+            persistent: Some(parts[3] == "yes"),
+            capacity_bytes: sizes.first().copied().flatten(),
+            allocation_bytes: sizes.get(1).copied().flatten(),
+            available_bytes: sizes.get(2).copied().flatten(),
+        });
+    }
+    
+    pools
+}
+
+fn parse_virsh_size(value: &str, unit: &str) -> Option<u64> {
+    let value: f64 = value.parse().ok()?;
+    let multiplier = match unit {
+        "B" | "bytes" => 1u64,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        "TiB" => 1 << 40,
+        "PiB" => 1 << 50,
+        _ => return None,
+    };
+    Some((value * multiplier as f64) as u64)
+}
+
+fn query_pool_type(pool_name: &str) -> Option<String> {
+    let output = Command::new("virsh")
+        .args(["pool-dumpxml", pool_name])
+        .output()
+        .ok()?;
+    
+    // <pool type='dir'>
+    let xml = String::from_utf8_lossy(&output.stdout);
+    let doc = roxmltree::Document::parse(&xml).ok()?;
+    doc.root_element().attribute("type").map(str::to_string)
+}
+
+#[derive(Debug, Serialize)]
+struct LibvirtNetwork {
+    name: String,
+    state: String,
+    autostart: Option<bool>,
+    persistent: Option<bool>,
+    bridge: Option<String>,
+    forward_mode: Option<String>,
+    ip_address: Option<String>,
+    netmask: Option<String>,
+    dhcp_start: Option<String>,
+    dhcp_end: Option<String>,
+}
+
+fn collect_libvirt_networks() -> Result<Vec<LibvirtNetwork>, Box<dyn std::error::Error>> {
+    let output = Command::new("virsh")
+        .args(["net-list", "--all"])
+        .output()?;
+    
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        if error.contains("failed to connect") {
+            return Err("Cannot connect to libvirt; is libvirtd running? (try `systemctl start libvirtd`)".into());
+        }
+        return Err(format!("virsh net-list failed: {}", error).into());
+    }
+    
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut networks = Vec::new();
+    
+    for line in stdout.lines().skip(2) { // Skip header lines
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 2 {
+            continue;
+        }
+        
+        let mut network = LibvirtNetwork {
+            name: parts[0].to_string(),
+            state: parts[1].to_string(),
+            autostart: parts.get(2).map(|v| *v == "yes"),
+            persistent: parts.get(3).map(|v| *v == "yes"),
+            bridge: None,
+            forward_mode: None,
+            ip_address: None,
+            netmask: None,
+            dhcp_start: None,
+            dhcp_end: None,
+        };
+        
+        let xml_output = Command::new("virsh")
+            .args(["net-dumpxml", parts[0]])
+            .output()?;
+        
+        if xml_output.status.success() {
+            let _ = parse_network_xml(&String::from_utf8_lossy(&xml_output.stdout), &mut network);
+        }
+        
+        networks.push(network);
+    }
+    
+    Ok(networks)
+}
+
+// Bridge, forward mode and the first IP/DHCP range from `virsh net-dumpxml`
+fn parse_network_xml(xml: &str, network: &mut LibvirtNetwork) -> Result<(), roxmltree::Error> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let root = doc.root_element();
+    let attr = |node: Option<roxmltree::Node>, name: &str| node?.attribute(name).map(str::to_string);
+    
+    network.bridge = attr(xml_child(root, "bridge"), "name");
+    // No <forward> element means an isolated network; <forward/> without a mode means NAT
+    network.forward_mode = Some(match xml_child(root, "forward") {
+        Some(forward) => forward.attribute("mode").unwrap_or("nat").to_string(),
+        None => "isolated".to_string(),
+    });
+    
+    let ip = xml_child(root, "ip");
+    let range = ip.and_then(|ip| xml_child(ip, "dhcp")).and_then(|dhcp| xml_child(dhcp, "range"));
+    network.ip_address = attr(ip, "address");
+    network.netmask = attr(ip, "netmask");
+    network.dhcp_start = attr(range, "start");
+    network.dhcp_end = attr(range, "end");
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct VmMetrics {
+    name: String,
+    timestamp: String,
+    vcpu_count: Option<u32>,
+    cpu_time_ns: Option<u64>,
+    balloon_current_kb: Option<u64>,
+    balloon_maximum_kb: Option<u64>,
+    block: Vec<VmBlockStats>,
+    net: Vec<VmNetStats>,
+}
+
+#[derive(Debug, Serialize)]
+struct VmBlockStats {
+    name: String,
+    rd_bytes: Option<u64>,
+    wr_bytes: Option<u64>,
+    rd_reqs: Option<u64>,
+    wr_reqs: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct VmNetStats {
+    name: String,
+    rx_bytes: Option<u64>,
+    tx_bytes: Option<u64>,
+    rx_pkts: Option<u64>,
+    tx_pkts: Option<u64>,
+}
+
+fn collect_vm_metrics(name: &str, hypervisor: Hypervisor) -> Result<VmMetrics, Box<dyn std::error::Error>> {
+    match hypervisor {
+        Hypervisor::Kvm | Hypervisor::Qemu => {
+            let output = Command::new("virsh")
+                .args(["domstats", name])
+                .output()?;
+            
+            if !output.status.success() {
+                return Err(format!("virsh domstats failed: {}", String::from_utf8_lossy(&output.stderr)).into());
+            }
+            
+            let stats = parse_virsh_domstats(&String::from_utf8_lossy(&output.stdout));
+            
+            // state.state=1 is VIR_DOMAIN_RUNNING; other states only report static counters
+            if stats.get("state.state").map(String::as_str) != Some("1") {
+                return Err(format!("VM '{}' is not running", name).into());
+            }
+            
+            Ok(build_vm_metrics(name, &stats))
+        }
+        
+        Hypervisor::VirtualBox | Hypervisor::Vmware => Err(not_implemented(hypervisor, "metrics")),
+    }
+}
+
+// Helper function to parse virsh domstats output into key/value pairs
+fn parse_virsh_domstats(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+fn build_vm_metrics(name: &str, stats: &HashMap<String, String>) -> VmMetrics {
+    let get_u64 = |key: &str| stats.get(key).and_then(|v| v.parse::<u64>().ok());
+    let count = |prefix: &str| get_u64(&format!("{}.count", prefix)).unwrap_or(0);
+    
+    let block = (0..count("block"))
+        .map(|i| VmBlockStats {
+            name: stats.get(&format!("block.{}.name", i)).cloned().unwrap_or_else(|| format!("block{}", i)),
+            rd_bytes: get_u64(&format!("block.{}.rd.bytes", i)),
+            wr_bytes: get_u64(&format!("block.{}.wr.bytes", i)),
+            rd_reqs: get_u64(&format!("block.{}.rd.reqs", i)),
+            wr_reqs: get_u64(&format!("block.{}.wr.reqs", i)),
+        })
+        .collect();
+    
+    let net = (0..count("net"))
+        .map(|i| VmNetStats {
+            name: stats.get(&format!("net.{}.name", i)).cloned().unwrap_or_else(|| format!("net{}", i)),
+            rx_bytes: get_u64(&format!("net.{}.rx.bytes", i)),
+            tx_bytes: get_u64(&format!("net.{}.tx.bytes", i)),
+            rx_pkts: get_u64(&format!("net.{}.rx.pkts", i)),
+            tx_pkts: get_u64(&format!("net.{}.tx.pkts", i)),
+        })
+        .collect();
+    
+    VmMetrics {
+        name: name.to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        vcpu_count: get_u64("vcpu.current").map(|v| v as u32),
+        cpu_time_ns: get_u64("cpu.time"),
+        balloon_current_kb: get_u64("balloon.current"),
+        balloon_maximum_kb: get_u64("balloon.maximum"),
+        block,
+        net,
+    }
+}
+
 // Helper function to parse virsh list output
 fn parse_virsh_list(output: &str) -> Result<Vec<VmInfo>, Box<dyn std::error::Error>> {
     let mut vms = Vec::new();
@@ -518,12 +1471,20 @@ fn parse_virsh_dominfo(output: &str) -> Result<serde_json::Value, Box<dyn std::e
 }
 // Structures for VM inventory
 #[derive(Debug, Serialize, Deserialize)]
-struct VmInventory {
+pub(crate) struct VmInventory {
     host_mac_address: String,
+    collected_at: String, // RFC 3339, UTC
     hypervisor_type: String,
     vms: Vec<VmDetail>,
 }
 
+impl VmInventory {
+    // The host MAC identifies the hypervisor node in VM inventory
+    pub(crate) fn idempotency_key(&self) -> String {
+        idempotency_key(&self.host_mac_address, &self.collected_at)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct VmDetail {
     vm_name: String,
@@ -535,6 +1496,27 @@ struct VmDetail {
     guest_os_family: Option<String>,
     disks: Vec<VmDiskDetail>,
     network_interfaces: Vec<VmNetworkDetail>,
+    #[serde(default)]
+    passthrough_devices: Vec<String>, // host PCI devices, e.g. "0000:41:00.0 NVIDIA Corporation GA102 [...]"
+    #[serde(flatten)]
+    tuning: VmTuning,
+}
+
+// Placement-relevant settings from the libvirt domain XML
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VmTuning {
+    current_memory_mb: Option<i32>,  // balloon target; below memory_mb when ballooned down
+    max_memory_mb: Option<i32>,      // hotplug ceiling from <maxMemory>, if memory can grow
+    memory_balloon: Option<bool>,    // false when <memballoon model='none'>
+    hugepages: Option<bool>,
+    hugepage_size_kb: Option<u64>,
+    cpu_pinning: Option<Vec<VcpuPin>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct VcpuPin {
+    vcpu: u32,
+    cpuset: String, // host CPUs, e.g. "2-3,6"
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -543,6 +1525,7 @@ struct VmDiskDetail {
     disk_type: Option<String>,
     disk_format: Option<String>,
     disk_size_gb: Option<i32>,
+    disk_actual_size_gb: Option<i32>,
     disk_path: String,
     is_bootable: Option<bool>,
     storage_type: Option<String>,
@@ -598,13 +1581,13 @@ fn get_host_primary_mac() -> Result<String, Box<dyn std::error::Error>> {
     Err("Could not find primary network interface MAC address".into())
 }
 
-fn collect_vm_inventory(hypervisor: &str) -> Result<VmInventory, Box<dyn std::error::Error>> {
+pub(crate) fn collect_vm_inventory(hypervisor: Hypervisor) -> Result<VmInventory, Box<dyn std::error::Error>> {
     let host_mac = get_host_primary_mac()?;
     
     match hypervisor {
-        "kvm" | "qemu" => collect_kvm_inventory(host_mac),
-        "virtualbox" => collect_virtualbox_inventory(host_mac),
-        _ => Err(format!("Unsupported hypervisor: {}", hypervisor).into()),
+        Hypervisor::Kvm | Hypervisor::Qemu => collect_kvm_inventory(host_mac),
+        Hypervisor::VirtualBox => collect_virtualbox_inventory(host_mac),
+        Hypervisor::Vmware => Err(not_implemented(hypervisor, "post-inventory")),
     }
 }
 
@@ -634,6 +1617,7 @@ fn collect_kvm_inventory(host_mac_address: String) -> Result<VmInventory, Box<dy
     
     Ok(VmInventory {
         host_mac_address,
+        collected_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
         hypervisor_type: "KVM".to_string(),
         vms,
     })
@@ -680,8 +1664,22 @@ fn collect_kvm_vm_detail(vm_name: &str) -> Result<VmDetail, Box<dyn std::error::
     // Get VM network interfaces
     let network_interfaces = collect_kvm_vm_networks(vm_name)?;
     
-    // Get guest OS info if possible
-    let guest_os_family = detect_guest_os(vm_name);
+    // Get guest OS info if possible, preferring the guest agent over name heuristics
+    let guest_os_family = query_guest_agent_os(vm_name).or_else(|| detect_guest_os(vm_name));
+    
+    let domain_xml = Command::new("virsh")
+        .args(["dumpxml", vm_name])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default();
+    let tuning = parse_domain_tuning(&domain_xml).unwrap_or_default();
+    let passthrough_devices = parse_hostdev_addresses(&domain_xml)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|address| describe_host_pci_device(&address))
+        .collect();
     
     Ok(VmDetail {
         vm_name: vm_name.to_string(),
@@ -693,6 +1691,8 @@ fn collect_kvm_vm_detail(vm_name: &str) -> Result<VmDetail, Box<dyn std::error::
         guest_os_family,
         disks,
         network_interfaces,
+        passthrough_devices,
+        tuning,
     })
 }
 
@@ -717,8 +1717,9 @@ fn collect_kvm_vm_disks(vm_name: &str) -> Result<Vec<VmDiskDetail>, Box<dyn std:
             let target = parts[2].to_string(); // e.g., "vda"
             let source = parts[3].to_string(); // disk path
             
-            // Try to get disk size
-            let disk_size_gb = get_disk_size(&source).unwrap_or(0); // Default to 0 if size cannot be determined
+            // Try to get virtual and allocated disk sizes
+            let (virtual_size_gb, disk_actual_size_gb) = get_disk_sizes(&source);
+            let disk_size_gb = virtual_size_gb.unwrap_or(0); // Default to 0 if size cannot be determined
             
             // Determine disk format from file extension or qemu-img
             let disk_format = detect_disk_format(&source);
@@ -728,6 +1729,7 @@ fn collect_kvm_vm_disks(vm_name: &str) -> Result<Vec<VmDiskDetail>, Box<dyn std:
                 disk_type: Some(detect_disk_type(&target)),
                 disk_format: Some(disk_format),
                 disk_size_gb: Some(disk_size_gb),
+                disk_actual_size_gb,
                 disk_path: source,
                 is_bootable: Some(disks.is_empty()), // First disk is usually bootable
                 storage_type: Some(if disk_type == "file" { "file".to_string() } else { "block".to_string() }),
@@ -738,6 +1740,119 @@ fn collect_kvm_vm_disks(vm_name: &str) -> Result<Vec<VmDiskDetail>, Box<dyn std:
     Ok(disks)
 }
 
+// Read memory sizing, ballooning, hugepages and vCPU pinning from `virsh dumpxml`
+fn parse_domain_tuning(xml: &str) -> Result<VmTuning, roxmltree::Error> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let domain = doc.root_element();
+    let memory_mb = |name: &str| {
+        let node = xml_child(domain, name)?;
+        let kib = memory_kib(node.text()?.trim().parse().ok()?, node.attribute("unit"))?;
+        i32::try_from(kib / 1024).ok()
+    };
+    
+    let backing = xml_child(domain, "memoryBacking");
+    let hugepages = backing.and_then(|b| xml_child(b, "hugepages"));
+    let hugepage_size_kb = hugepages
+        .and_then(|h| xml_child(h, "page"))
+        .and_then(|page| memory_kib(page.attribute("size")?.parse().ok()?, page.attribute("unit")));
+    
+    let memory_balloon = xml_child(domain, "devices")
+        .and_then(|devices| xml_child(devices, "memballoon"))
+        .map(|balloon| balloon.attribute("model") != Some("none"));
+    
+    let pins: Vec<VcpuPin> = xml_child(domain, "cputune")
+        .map(|cputune| {
+            cputune
+                .children()
+                .filter(|n| n.has_tag_name("vcpupin"))
+                .filter_map(|pin| {
+                    Some(VcpuPin {
+                        vcpu: pin.attribute("vcpu")?.parse().ok()?,
+                        cpuset: pin.attribute("cpuset")?.to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    
+    Ok(VmTuning {
+        current_memory_mb: memory_mb("currentMemory"),
+        max_memory_mb: memory_mb("maxMemory"),
+        memory_balloon,
+        hugepages: Some(hugepages.is_some()),
+        hugepage_size_kb,
+        cpu_pinning: if pins.is_empty() { None } else { Some(pins) },
+    })
+}
+
+// Host PCI addresses of <hostdev type='pci'> devices passed through to the VM
+fn parse_hostdev_addresses(xml: &str) -> Result<Vec<String>, roxmltree::Error> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let Some(devices) = xml_child(doc.root_element(), "devices") else {
+        return Ok(Vec::new());
+    };
+    
+    let hex = |node: roxmltree::Node, attr: &str| {
+        let value = node.attribute(attr)?;
+        u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+    };
+    Ok(devices
+        .children()
+        .filter(|n| n.has_tag_name("hostdev") && n.attribute("type") == Some("pci"))
+        .filter_map(|hostdev| {
+            let address = xml_child(xml_child(hostdev, "source")?, "address")?;
+            Some(format!(
+                "{:04x}:{:02x}:{:02x}.{:x}",
+                hex(address, "domain").unwrap_or(0),
+                hex(address, "bus")?,
+                hex(address, "slot")?,
+                hex(address, "function")?,
+            ))
+        })
+        .collect())
+}
+
+// "0000:41:00.0 NVIDIA Corporation GA102 [GeForce RTX 3090]", or just the
+// address when the device isn't present on the host or not in pci.ids
+fn describe_host_pci_device(address: &str) -> String {
+    let device_dir = Path::new("/sys/bus/pci/devices").join(address);
+    let read_id = |file: &str| fs::read_to_string(device_dir.join(file)).ok().and_then(|id| parse_pci_id(id.trim()));
+    match read_id("vendor")
+        .zip(read_id("device"))
+        .and_then(|(vendor, device)| lookup_pci_ids(vendor, device))
+    {
+        Some((vendor, device)) => format!("{} {} {}", address, vendor, device),
+        None => address.to_string(),
+    }
+}
+
+// Port of the domain's first <graphics> device
+fn parse_graphics_port(xml: &str) -> Option<u16> {
+    let doc = roxmltree::Document::parse(xml).ok()?;
+    let devices = xml_child(doc.root_element(), "devices")?;
+    xml_child(devices, "graphics")?.attribute("port")?.parse().ok()
+}
+
+// First child element of `parent` named `name`
+fn xml_child<'a, 'input>(parent: roxmltree::Node<'a, 'input>, name: &str) -> Option<roxmltree::Node<'a, 'input>> {
+    parent.children().find(|n| n.has_tag_name(name))
+}
+
+// libvirt memory sizes default to KiB; the unit attribute can override it
+fn memory_kib(value: u64, unit: Option<&str>) -> Option<u64> {
+    match unit.unwrap_or("KiB") {
+        "b" | "bytes" => Some(value / 1024),
+        "k" | "KiB" => Some(value),
+        "KB" => Some(value * 1000 / 1024),
+        "M" | "MiB" => Some(value * 1024),
+        "MB" => Some(value * 1_000_000 / 1024),
+        "G" | "GiB" => Some(value * 1024 * 1024),
+        "GB" => Some(value * 1_000_000_000 / 1024),
+        "T" | "TiB" => Some(value * 1024 * 1024 * 1024),
+        _ => None,
+    }
+}
+
 // Collect network interface information for a KVM VM
 fn collect_kvm_vm_networks(vm_name: &str) -> Result<Vec<VmNetworkDetail>, Box<dyn std::error::Error>> {
     let output = Command::new("virsh")
@@ -833,21 +1948,23 @@ fn detect_disk_format(path: &str) -> String {
     }
 }
 
-fn get_disk_size(path: &str) -> Option<i32> {
-    let output = Command::new("qemu-img")
-        .args(&["info", "--output=json", path])
+// Returns (virtual size, allocated size) in GB; thin-provisioned images allocate less than they expose
+fn get_disk_sizes(path: &str) -> (Option<i32>, Option<i32>) {
+    let output = match Command::new("qemu-img")
+        .args(["info", "--output=json", "--force-share", path])
         .output()
-        .ok()?;
+    {
+        Ok(output) => output,
+        Err(_) => return (None, None),
+    };
     
-    if let Ok(json_str) = String::from_utf8(output.stdout) {
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&json_str) {
-            if let Some(size) = json["virtual-size"].as_i64() {
-                return Some((size / 1024 / 1024 / 1024) as i32);
-            }
-        }
-    }
+    let json = match serde_json::from_slice::<serde_json::Value>(&output.stdout) {
+        Ok(json) => json,
+        Err(_) => return (None, None),
+    };
     
-    None
+    let to_gb = |key: &str| json[key].as_i64().map(|size| (size / 1024 / 1024 / 1024) as i32);
+    (to_gb("virtual-size"), to_gb("actual-size"))
 }
 
 fn normalize_vm_state(state: &str) -> String {
@@ -866,6 +1983,41 @@ fn normalize_vm_state(state: &str) -> String {
     }
 }
 
+// Ask qemu-guest-agent for the guest's OS. Returns None when the VM is not running
+// or the agent is not connected/responding.
+fn query_guest_agent_os(vm_name: &str) -> Option<String> {
+    let output = Command::new("virsh")
+        .args(["qemu-agent-command", "--timeout", "5", vm_name, r#"{"execute":"guest-get-osinfo"}"#])
+        .output()
+        .ok()?;
+    
+    if !output.status.success() {
+        // e.g. "Guest agent is not responding: QEMU guest agent is not connected"
+        return None;
+    }
+    
+    let response: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    parse_guest_osinfo(&response["return"])
+}
+
+fn parse_guest_osinfo(osinfo: &serde_json::Value) -> Option<String> {
+    let id = osinfo["id"].as_str().unwrap_or("").to_lowercase();
+    let name = osinfo["name"].as_str().unwrap_or("").to_lowercase();
+    
+    if id.is_empty() && name.is_empty() {
+        return None;
+    }
+    
+    if id == "mswindows" || name.contains("windows") {
+        Some("Windows".to_string())
+    } else if id.contains("freebsd") || name.contains("freebsd") {
+        Some("FreeBSD".to_string())
+    } else {
+        // The agent reports os-release data for every Linux distribution
+        Some("Linux".to_string())
+    }
+}
+
 fn detect_guest_os(vm_name: &str) -> Option<String> {
     // Try to detect from VM name patterns
     let name_lower = vm_name.to_lowercase();
@@ -915,6 +2067,8 @@ fn collect_virtualbox_inventory(host_mac_address: String) -> Result<VmInventory,
                     guest_os_family: None,
                     disks: Vec::new(),
                     network_interfaces: Vec::new(),
+                    passthrough_devices: Vec::new(),
+                    tuning: VmTuning::default(),
                 });
             }
         }
@@ -922,7 +2076,323 @@ fn collect_virtualbox_inventory(host_mac_address: String) -> Result<VmInventory,
     
     Ok(VmInventory {
         host_mac_address,
+        collected_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
         hypervisor_type: "VirtualBox".to_string(),
         vms,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_domain_tuning() {
+        let tuning = parse_domain_tuning(
+            "<domain type='kvm'>
+  <name>worker-1</name>
+  <maxMemory slots='16' unit='KiB'>67108864</maxMemory>
+  <memory unit='KiB'>16777216</memory>
+  <currentMemory unit='GiB'>8</currentMemory>
+  <memoryBacking>
+    <hugepages>
+      <page size='1' unit='G'/>
+    </hugepages>
+  </memoryBacking>
+  <vcpu placement='static'>2</vcpu>
+  <cputune>
+    <vcpupin vcpu='0' cpuset='2'/>
+    <vcpupin vcpu='1' cpuset='3-4'/>
+    <emulatorpin cpuset='0'/>
+  </cputune>
+  <devices>
+    <memballoon model='virtio'/>
+  </devices>
+</domain>",
+        )
+        .unwrap();
+
+        assert_eq!(tuning.max_memory_mb, Some(65536));
+        assert_eq!(tuning.current_memory_mb, Some(8192));
+        assert_eq!(tuning.memory_balloon, Some(true));
+        assert_eq!(tuning.hugepages, Some(true));
+        assert_eq!(tuning.hugepage_size_kb, Some(1024 * 1024));
+        let pins = tuning.cpu_pinning.unwrap();
+        assert_eq!(pins.len(), 2);
+        assert_eq!((pins[1].vcpu, pins[1].cpuset.as_str()), (1, "3-4"));
+    }
+
+    #[test]
+    fn test_parse_hostdev_addresses() {
+        let addresses = parse_hostdev_addresses(
+            "<domain type='kvm'>
+  <devices>
+    <hostdev mode='subsystem' type='pci' managed='yes'>
+      <source>
+        <address domain='0x0000' bus='0x41' slot='0x00' function='0x0'/>
+      </source>
+      <address type='pci' domain='0x0000' bus='0x06' slot='0x00' function='0x0'/>
+    </hostdev>
+    <hostdev mode='subsystem' type='usb' managed='yes'>
+      <source>
+        <vendor id='0x0781'/>
+      </source>
+    </hostdev>
+  </devices>
+</domain>",
+        )
+        .unwrap();
+
+        assert_eq!(addresses, vec!["0000:41:00.0"]);
+    }
+
+    #[test]
+    fn test_parse_domain_tuning_defaults() {
+        let tuning = parse_domain_tuning(
+            "<domain type='kvm'>
+  <memory unit='KiB'>2097152</memory>
+  <currentMemory unit='KiB'>2097152</currentMemory>
+  <devices>
+    <memballoon model='none'/>
+  </devices>
+</domain>",
+        )
+        .unwrap();
+
+        assert_eq!(tuning.max_memory_mb, None);
+        assert_eq!(tuning.current_memory_mb, Some(2048));
+        assert_eq!(tuning.memory_balloon, Some(false));
+        assert_eq!(tuning.hugepages, Some(false));
+        assert!(tuning.cpu_pinning.is_none());
+    }
+
+    #[test]
+    fn test_parse_network_xml() {
+        let mut network = LibvirtNetwork {
+            name: "default".to_string(),
+            state: "active".to_string(),
+            autostart: None,
+            persistent: None,
+            bridge: None,
+            forward_mode: None,
+            ip_address: None,
+            netmask: None,
+            dhcp_start: None,
+            dhcp_end: None,
+        };
+        parse_network_xml(
+            "<network>
+  <name>default</name>
+  <forward mode='nat'>
+    <nat>
+      <port start='1024' end='65535'/>
+    </nat>
+  </forward>
+  <bridge name='virbr0' stp='on' delay='0'/>
+  <ip address='192.168.122.1' netmask='255.255.255.0'>
+    <dhcp>
+      <range start='192.168.122.2' end='192.168.122.254'/>
+    </dhcp>
+  </ip>
+</network>",
+            &mut network,
+        )
+        .unwrap();
+
+        assert_eq!(network.bridge.as_deref(), Some("virbr0"));
+        assert_eq!(network.forward_mode.as_deref(), Some("nat"));
+        assert_eq!(network.ip_address.as_deref(), Some("192.168.122.1"));
+        assert_eq!(network.netmask.as_deref(), Some("255.255.255.0"));
+        assert_eq!(network.dhcp_start.as_deref(), Some("192.168.122.2"));
+        assert_eq!(network.dhcp_end.as_deref(), Some("192.168.122.254"));
+
+        parse_network_xml("<network><name>isolated</name><bridge name='virbr1'/></network>", &mut network).unwrap();
+        assert_eq!(network.forward_mode.as_deref(), Some("isolated"));
+        assert_eq!(network.ip_address, None);
+    }
+
+    #[test]
+    fn test_parse_graphics_port() {
+        let xml = |port: &str| format!("<domain type='kvm'><devices><graphics type='vnc' port='{}' autoport='yes' listen='127.0.0.1'/></devices></domain>", port);
+
+        assert_eq!(parse_graphics_port(&xml("5901")), Some(5901));
+        // Autoport reports -1 until the domain is running
+        assert_eq!(parse_graphics_port(&xml("-1")), None);
+    }
+
+    #[test]
+    fn test_parse_vbox_machinereadable() {
+        let info = parse_vbox_machinereadable(
+            "name=\"worker-1\"
+UUID=\"0b6f3c2e-8d4a-4f7e-9c1a-2e5d7b9f1a3c\"
+VMState=\"running\"
+memory=4096
+vrde=\"on\"
+vrdeport=3389
+\"storagecontrollername0\"=\"SATA\"
+",
+        );
+
+        assert_eq!(info["name"], "worker-1");
+        assert_eq!(info["UUID"], "0b6f3c2e-8d4a-4f7e-9c1a-2e5d7b9f1a3c");
+        assert_eq!(info["memory"], "4096");
+        assert_eq!(info["vrdeport"], "3389");
+        assert_eq!(info["storagecontrollername0"], "SATA");
+        assert_eq!(vbox_vrde_port(&info), Some(3389));
+        assert_eq!(vbox_vrde_port(&parse_vbox_machinereadable("vrde=\"off\"\nvrdeport=3389\n")), None);
+        assert!(parse_vbox_machinereadable("").is_empty());
+    }
+
+    #[test]
+    fn test_build_vm_metrics() {
+        let stats = parse_virsh_domstats(
+            "Domain: 'worker-1'
+  state.state=1
+  state.reason=1
+  cpu.time=8712345678
+  balloon.current=4194304
+  balloon.maximum=8388608
+  vcpu.current=4
+  net.count=1
+  net.0.name=vnet0
+  net.0.rx.bytes=1048576
+  net.0.rx.pkts=900
+  net.0.tx.bytes=524288
+  net.0.tx.pkts=450
+  block.count=2
+  block.0.name=vda
+  block.0.rd.reqs=120
+  block.0.rd.bytes=4915200
+  block.0.wr.reqs=60
+  block.0.wr.bytes=2457600
+  block.1.name=sda
+",
+        );
+
+        assert_eq!(stats.get("state.state").map(String::as_str), Some("1"));
+        let metrics = build_vm_metrics("worker-1", &stats);
+        assert_eq!(metrics.vcpu_count, Some(4));
+        assert_eq!(metrics.cpu_time_ns, Some(8712345678));
+        assert_eq!(metrics.balloon_current_kb, Some(4194304));
+        assert_eq!(metrics.balloon_maximum_kb, Some(8388608));
+        assert_eq!(metrics.block.len(), 2);
+        assert_eq!((metrics.block[0].name.as_str(), metrics.block[0].rd_bytes, metrics.block[0].wr_reqs), ("vda", Some(4915200), Some(60)));
+        // A CD-ROM with no media reports only its name
+        assert_eq!((metrics.block[1].name.as_str(), metrics.block[1].rd_bytes), ("sda", None));
+        assert_eq!(metrics.net.len(), 1);
+        assert_eq!((metrics.net[0].name.as_str(), metrics.net[0].rx_bytes, metrics.net[0].tx_pkts), ("vnet0", Some(1048576), Some(450)));
+    }
+
+    #[test]
+    fn test_build_vm_metrics_shut_off() {
+        let stats = parse_virsh_domstats("Domain: 'worker-2'\n  state.state=5\n  state.reason=1\n");
+        let metrics = build_vm_metrics("worker-2", &stats);
+
+        assert_eq!(stats.get("state.state").map(String::as_str), Some("5"));
+        assert_eq!(metrics.cpu_time_ns, None);
+        assert!(metrics.block.is_empty() && metrics.net.is_empty());
+    }
+
+    #[test]
+    fn test_parse_guest_osinfo() {
+        let ubuntu = serde_json::json!({
+            "name": "Ubuntu", "kernel-release": "5.15.0-91-generic", "version": "22.04.3 LTS (Jammy Jellyfish)",
+            "pretty-name": "Ubuntu 22.04.3 LTS", "version-id": "22.04", "kernel-version": "#101-Ubuntu SMP", "machine": "x86_64", "id": "ubuntu"
+        });
+        let windows = serde_json::json!({
+            "name": "Microsoft Windows", "kernel-release": "20348", "version": "Microsoft Windows Server 2022",
+            "variant": "server", "pretty-name": "Windows Server 2022 Standard", "version-id": "2022", "variant-id": "server",
+            "kernel-version": "10.0", "machine": "x86_64", "id": "mswindows"
+        });
+        let freebsd = serde_json::json!({"name": "FreeBSD", "id": "freebsd", "version-id": "14.0"});
+
+        assert_eq!(parse_guest_osinfo(&ubuntu).as_deref(), Some("Linux"));
+        assert_eq!(parse_guest_osinfo(&windows).as_deref(), Some("Windows"));
+        assert_eq!(parse_guest_osinfo(&freebsd).as_deref(), Some("FreeBSD"));
+        // `return` is missing from an error response
+        assert_eq!(parse_guest_osinfo(&serde_json::Value::Null), None);
+    }
+
+    #[test]
+    fn test_parse_virsh_pool_list() {
+        let pools = parse_virsh_pool_list(
+            " Name      State      Autostart   Persistent   Capacity     Allocation   Available
+---------------------------------------------------------------------------------------
+ default   running    yes         yes          97.87 GiB    22.04 GiB    75.83 GiB
+ images    inactive   no          yes          -            -            -
+ nvme      running    yes         yes          1.75 TiB     512.00 MiB   1.75 TiB
+
+",
+        );
+
+        assert_eq!(pools.len(), 3);
+        assert_eq!((pools[0].name.as_str(), pools[0].state.as_str()), ("default", "running"));
+        assert_eq!((pools[0].autostart, pools[0].persistent), (Some(true), Some(true)));
+        assert_eq!(pools[0].capacity_bytes, Some((97.87 * (1u64 << 30) as f64) as u64));
+        assert_eq!(pools[1].state, "inactive");
+        assert_eq!(pools[1].autostart, Some(false));
+        assert_eq!((pools[1].capacity_bytes, pools[1].available_bytes), (None, None));
+        assert_eq!(pools[2].allocation_bytes, Some(512 << 20));
+    }
+
+    #[test]
+    fn test_parse_virsh_size() {
+        assert_eq!(parse_virsh_size("1.50", "GiB"), Some(3 << 29));
+        assert_eq!(parse_virsh_size("0.00", "B"), Some(0));
+        assert_eq!(parse_virsh_size("2", "TiB"), Some(2 << 40));
+        assert_eq!(parse_virsh_size("2", "GB"), None);
+        assert_eq!(parse_virsh_size("-", "GiB"), None);
+    }
+
+    #[test]
+    fn test_parse_vm_names() {
+        assert_eq!(parse_virsh_names("worker-1\n gpu-vm \n\n"), vec!["worker-1", "gpu-vm"]);
+        assert!(parse_virsh_names("\n").is_empty());
+        assert_eq!(
+            parse_vbox_vm_names("\"worker-1\" {0b6f3c2e-8d4a-4f7e-9c1a-2e5d7b9f1a3c}\n\"my \"test\" vm\" {5c1e2a7d-3b9f-4e8a-a1c6-7d2f4b8e9c0a}\n"),
+            vec!["worker-1", "my \"test\" vm"]
+        );
+    }
+
+    #[test]
+    fn test_select_vm_names() {
+        let names = || vec!["gpu-1".to_string(), "gpu-2".to_string(), "cpu-1".to_string()];
+
+        assert_eq!(select_vm_names(names(), None).unwrap(), names());
+        assert_eq!(select_vm_names(names(), Some("gpu-*")).unwrap(), vec!["gpu-1", "gpu-2"]);
+        assert_eq!(select_vm_names(names(), Some("db-*")).unwrap_err().to_string(), "No VMs match 'db-*'");
+        assert_eq!(select_vm_names(Vec::new(), None).unwrap_err().to_string(), "No VMs defined");
+        assert!(select_vm_names(names(), Some("[")).unwrap_err().to_string().starts_with("Invalid pattern ["));
+    }
+
+    #[test]
+    fn test_run_on_vms_reports_failures() {
+        let names = vec!["gpu-1".to_string(), "gpu-2".to_string(), "gpu-3".to_string()];
+        let visited = std::cell::RefCell::new(Vec::new());
+        let result = run_on_vms(&names, true, "stop", |name| {
+            visited.borrow_mut().push(name.to_string());
+            if name == "gpu-2" { Err("domain is not running".into()) } else { Ok(()) }
+        });
+
+        // A failure doesn't stop the rest
+        assert_eq!(*visited.borrow(), names);
+        assert_eq!(result.unwrap_err().to_string(), "Failed to stop 1 VM(s): gpu-2");
+        assert!(run_on_vms(&names, true, "start", |_| Ok(())).is_ok());
+    }
+
+    #[test]
+    fn test_parse_vnc_display() {
+        assert_eq!(parse_vnc_display("127.0.0.1:0\n\n"), Some(5900));
+        assert_eq!(parse_vnc_display(":3\n"), Some(5903));
+        assert_eq!(parse_vnc_display("[::1]:1\n"), Some(5901));
+        assert_eq!(parse_vnc_display(""), None);
+        assert_eq!(parse_vnc_display("127.0.0.1:65000"), None);
+    }
+
+    #[test]
+    fn test_parse_vbox_uuid() {
+        let output = "name=\"worker-1\"\ngroups=\"/\"\nUUID=\"0b6f3c2e-8d4a-4f7e-9c1a-2e5d7b9f1a3c\"\nVMState=\"poweroff\"\n";
+        assert_eq!(parse_vbox_uuid(output).as_deref(), Some("0b6f3c2e-8d4a-4f7e-9c1a-2e5d7b9f1a3c"));
+        assert_eq!(parse_vbox_uuid("VBoxManage: error: Could not find a registered machine named 'x'\n"), None);
+    }
+}
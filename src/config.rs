@@ -0,0 +1,183 @@
+// Config file support
+//
+// Precedence (highest first):
+//   1. CLI flags (--url, --format)
+//   2. Environment (FARM_MANAGER_URL, FARM_MANAGER_TOKEN, FARM_MANAGER_TIMEOUT, FARM_MANAGER_FORMAT)
+//   3. Config file (--config <path>, else ~/.config/farm-manager/config.toml)
+//   4. Built-in defaults
+//
+// Example config.toml:
+//   url = "https://farmcore.example.com"
+//   token = "..."
+//   timeout_secs = 30
+//   format = "json"
+//   server_inventory_path = "/api/v1/servers/inventory"
+//   vm_inventory_path = "/api/v1/vms/inventory"
+//   client_cert = "/etc/farm-manager/client.pem"   # mTLS, with client_key
+//   client_key = "/etc/farm-manager/client.key"
+//   ca_cert = "/etc/farm-manager/ca.pem"
+//   proxy = "http://proxy.example.com:3128"
+//
+// Proxy: --proxy, then `proxy` in the config file, then the usual
+// HTTPS_PROXY/HTTP_PROXY/ALL_PROXY environment. NO_PROXY is honored either way.
+
+use clap::Command;
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+pub const DEFAULT_URL: &str = "http://localhost:6183";
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+pub const DEFAULT_SERVER_INVENTORY_PATH: &str = "/api/v1/servers/inventory";
+pub const DEFAULT_VM_INVENTORY_PATH: &str = "/api/v1/vms/inventory";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    url: Option<String>,
+    token: Option<String>,
+    timeout_secs: Option<u64>,
+    format: Option<String>,
+    server_inventory_path: Option<String>,
+    vm_inventory_path: Option<String>,
+    client_cert: Option<String>,
+    client_key: Option<String>,
+    ca_cert: Option<String>,
+    proxy: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub url: String,               // FarmCore API base URL
+    pub token: Option<String>,     // sent as a bearer token on FarmCore requests
+    pub timeout_secs: u64,         // HTTP request timeout
+    pub format: Option<String>,    // default output format for every subcommand
+    pub server_inventory_path: String, // FarmCore route for hardware inventory (--path overrides)
+    pub vm_inventory_path: String,     // FarmCore route for VM inventory (--path overrides)
+    pub client_cert: Option<String>,   // PEM client certificate for mTLS
+    pub client_key: Option<String>,    // PKCS#8 PEM key for client_cert
+    pub ca_cert: Option<String>,       // PEM bundle trusted in addition to the system roots
+    pub proxy: Option<String>,         // explicit proxy; None falls back to the *_PROXY environment
+    pub insecure: bool,                // --insecure: skip TLS verification (CLI only)
+    pub verbose: bool,             // --verbose (CLI only)
+}
+
+impl Config {
+    /// Load the config file (if any) and apply environment overrides.
+    /// CLI flags are applied on top by `apply_defaults`.
+    pub fn load(path: Option<&str>) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = match path {
+            Some(p) => read_config_file(&PathBuf::from(p))?
+                .ok_or_else(|| format!("Config file not found: {}", p))?,
+            None => match default_config_path() {
+                Some(p) => read_config_file(&p)?.unwrap_or_default(),
+                None => FileConfig::default(),
+            },
+        };
+        Self::resolve(file, env_var)
+    }
+
+    /// Layer the environment (looked up through `env`) over the file values.
+    fn resolve(file: FileConfig, env: impl Fn(&str) -> Option<String>) -> Result<Self, Box<dyn std::error::Error>> {
+        let env_timeout = match env("FARM_MANAGER_TIMEOUT") {
+            Some(v) => Some(v.parse::<u64>().map_err(|_| format!("Invalid FARM_MANAGER_TIMEOUT: {}", v))?),
+            None => None,
+        };
+
+        Ok(Config {
+            url: env("FARM_MANAGER_URL").or(file.url).unwrap_or_else(|| DEFAULT_URL.to_string()),
+            token: env("FARM_MANAGER_TOKEN").or(file.token),
+            timeout_secs: env_timeout.or(file.timeout_secs).unwrap_or(DEFAULT_TIMEOUT_SECS),
+            format: env("FARM_MANAGER_FORMAT").or(file.format),
+            server_inventory_path: file.server_inventory_path.unwrap_or_else(|| DEFAULT_SERVER_INVENTORY_PATH.to_string()),
+            vm_inventory_path: file.vm_inventory_path.unwrap_or_else(|| DEFAULT_VM_INVENTORY_PATH.to_string()),
+            client_cert: file.client_cert,
+            client_key: file.client_key,
+            ca_cert: file.ca_cert,
+            proxy: file.proxy,
+            insecure: false,
+            verbose: false,
+        })
+    }
+
+    /// Make the resolved values the defaults of every `--url`/`--format` argument,
+    /// so an explicit flag still wins.
+    pub fn apply_defaults(&self, cmd: Command) -> Command {
+        cmd.mut_args(|arg| match arg.get_id().as_str() {
+            "url" => arg.default_value(self.url.clone()),
+            "format" => match &self.format {
+                Some(format) => arg.default_value(format.clone()),
+                None => arg,
+            },
+            _ => arg,
+        })
+        .mut_subcommands(|sub| self.apply_defaults(sub))
+    }
+}
+
+fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("farm-manager").join("config.toml"))
+}
+
+fn read_config_file(path: &PathBuf) -> Result<Option<FileConfig>, Box<dyn std::error::Error>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+    let config = toml::from_str(&content)
+        .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))?;
+    Ok(Some(config))
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::CommandFactory;
+
+    fn resolve(file: &str, env: &[(&str, &str)]) -> Config {
+        let file: FileConfig = toml::from_str(file).unwrap();
+        Config::resolve(file, |name| env.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())).unwrap()
+    }
+
+    fn agent_url(config: &Config, args: &[&str]) -> String {
+        let matches = config.apply_defaults(Cli::command()).try_get_matches_from(args).unwrap();
+        let (_, agent) = matches.subcommand().unwrap();
+        agent.get_one::<String>("url").unwrap().clone()
+    }
+
+    #[test]
+    fn test_precedence() {
+        let file = "url = \"https://file:6183\"\nformat = \"yaml\"\ntimeout_secs = 10\n";
+
+        let config = resolve("", &[]);
+        assert_eq!(config.url, DEFAULT_URL);
+        assert_eq!(config.timeout_secs, DEFAULT_TIMEOUT_SECS);
+        assert_eq!(config.format, None);
+
+        let config = resolve(file, &[]);
+        assert_eq!(config.url, "https://file:6183");
+        assert_eq!(config.timeout_secs, 10);
+        assert_eq!(config.format.as_deref(), Some("yaml"));
+
+        let config = resolve(file, &[("FARM_MANAGER_URL", "https://env:6183"), ("FARM_MANAGER_TIMEOUT", "5")]);
+        assert_eq!(config.url, "https://env:6183");
+        assert_eq!(config.timeout_secs, 5);
+        assert_eq!(config.format.as_deref(), Some("yaml"));
+
+        assert_eq!(agent_url(&config, &["farm-manager", "agent"]), "https://env:6183");
+        assert_eq!(agent_url(&config, &["farm-manager", "agent", "--url", "https://flag:6183"]), "https://flag:6183");
+    }
+
+    #[test]
+    fn test_invalid_env_timeout() {
+        let file = FileConfig::default();
+        let err = Config::resolve(file, |name| (name == "FARM_MANAGER_TIMEOUT").then(|| "soon".to_string())).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid FARM_MANAGER_TIMEOUT: soon");
+    }
+}
@@ -0,0 +1,148 @@
+use crate::hardware::types::BootDeviceInfo;
+use crate::hardware::{ipmi_host, ipmitool_command};
+use crate::util::run_capture;
+
+/// Run `ipmitool chassis power <action>` (status, on, off, cycle or reset)
+/// and return the BMC's reply, e.g. "Chassis Power is on".
+pub fn chassis_power(action: &str) -> Result<String, Box<dyn std::error::Error>> {
+    run_chassis(&["power", action])
+}
+
+/// Blink the chassis identify LED for `seconds` (0 turns it off); `None`
+/// uses the BMC default of 15 seconds.
+pub fn chassis_identify(seconds: Option<u32>) -> Result<String, Box<dyn std::error::Error>> {
+    match seconds {
+        Some(seconds) => run_chassis(&["identify", &seconds.to_string()]),
+        None => run_chassis(&["identify"]),
+    }
+}
+
+/// Read the boot device override (boot parameter 5, the boot flags).
+pub fn boot_device() -> Result<BootDeviceInfo, Box<dyn std::error::Error>> {
+    let output = run_chassis(&["bootparam", "get", "5"])?;
+    Ok(parse_boot_flags(&output))
+}
+
+/// Override the boot device via `ipmitool chassis bootdev`: for the next
+/// boot only, or for every boot when `persistent`.
+pub fn set_boot_device(device: &str, persistent: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if persistent {
+        run_chassis(&["bootdev", device, "options=persistent"])?;
+    } else {
+        run_chassis(&["bootdev", device])?;
+    }
+    Ok(())
+}
+
+fn run_chassis(args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    let output = run_capture(ipmitool_command().arg("chassis").args(args))
+        .map_err(|e| format!("Failed to run ipmitool (is it installed?): {}", e))?;
+
+    if !output.success {
+        let stderr = &output.stderr;
+        if ipmi_host().is_none() && stderr.contains("Could not open device") {
+            return Err("Cannot access the IPMI device; run as root and ensure the ipmi_devintf/ipmi_si modules are loaded".into());
+        }
+        let bmc = ipmi_host().unwrap_or("local BMC");
+        return Err(format!("ipmitool chassis {} failed on {}: {}", args.join(" "), bmc, stderr.trim()).into());
+    }
+
+    Ok(output.stdout.trim().to_string())
+}
+
+/// Parse `ipmitool chassis bootparam get 5`:
+///
+/// ```text
+///  Boot Flags :
+///    - Boot Flag Valid
+///    - Options apply to only next boot
+///    - BIOS EFI boot
+///    - Boot Device Selector : Force PXE
+/// ```
+fn parse_boot_flags(output: &str) -> BootDeviceInfo {
+    let mut info = BootDeviceInfo::default();
+
+    for line in output.lines() {
+        let line = line.trim().trim_start_matches("- ");
+        if line == "Boot Flag Valid" {
+            info.override_active = true;
+        } else if line == "Options apply to all future boots" {
+            info.persistent = true;
+        } else if line.starts_with("BIOS EFI boot") {
+            info.boot_mode = Some("UEFI".to_string());
+        } else if line.starts_with("BIOS PC Compatible") {
+            info.boot_mode = Some("legacy".to_string());
+        } else if let Some(selector) = line.strip_prefix("Boot Device Selector :") {
+            let selector = selector.trim();
+            info.device = Some(boot_device_name(selector).to_string());
+            info.selector = Some(selector.to_string());
+        }
+    }
+
+    // A selector left over from an expired override doesn't apply
+    if !info.override_active {
+        info.device = Some("none".to_string());
+    }
+
+    info
+}
+
+/// Map the BMC's selector wording back to the `bootdev` name that sets it.
+fn boot_device_name(selector: &str) -> &str {
+    let lower = selector.to_lowercase();
+    if lower.contains("no override") {
+        "none"
+    } else if lower.contains("pxe") {
+        "pxe"
+    } else if lower.contains("hard-drive") || lower.contains("hard drive") {
+        "disk"
+    } else if lower.contains("bios setup") {
+        "bios"
+    } else if lower.contains("cd/dvd") {
+        "cdrom"
+    } else {
+        selector
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_boot_flags_persistent_pxe() {
+        let info = parse_boot_flags(
+            "Boot parameter version: 1
+Boot parameter 5 is valid/unlocked
+Boot parameter data: e004000000
+ Boot Flags :
+   - Boot Flag Valid
+   - Options apply to all future boots
+   - BIOS EFI boot
+   - Boot Device Selector : Force PXE
+   - Console Redirection control : System Default
+",
+        );
+        assert!(info.override_active);
+        assert!(info.persistent);
+        assert_eq!(info.device.as_deref(), Some("pxe"));
+        assert_eq!(info.selector.as_deref(), Some("Force PXE"));
+        assert_eq!(info.boot_mode.as_deref(), Some("UEFI"));
+    }
+
+    #[test]
+    fn test_parse_boot_flags_no_override() {
+        let info = parse_boot_flags(
+            " Boot Flags :
+   - Boot Flag Invalid
+   - Options apply to only next boot
+   - BIOS PC Compatible (legacy) boot
+   - Boot Device Selector : Force Boot from default Hard-Drive
+",
+        );
+        assert!(!info.override_active);
+        assert!(!info.persistent);
+        assert_eq!(info.device.as_deref(), Some("none"));
+        assert_eq!(info.boot_mode.as_deref(), Some("legacy"));
+    }
+}
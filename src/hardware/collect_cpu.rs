@@ -1,7 +1,9 @@
 use std::fs;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 use smbioslib::*;
-use crate::hardware::types::{CpuInfo, CpuSocket};
+use crate::hardware::{load_smbios, read_trim, Collected};
+use crate::hardware::types::{CpuInfo, CpuSocket, CpuFreqInfo, CpuFreqSocket, CoreFreqInfo};
 
 fn get_cache_size_by_handle(smbios: &SMBiosData, handle: Handle) -> Option<u32> {
     for structure in smbios.iter() {
@@ -23,11 +25,19 @@ fn get_cache_size_by_handle(smbios: &SMBiosData, handle: Handle) -> Option<u32>
 }
 
 pub fn collect_cpu_info() -> CpuInfo {
+    collect_cpu_info_checked().data
+}
+
+/// Like [`collect_cpu_info`], but also reports why the result may be incomplete.
+pub(crate) fn collect_cpu_info_checked() -> Collected<CpuInfo> {
     let mut cpu_data: HashMap<u32, CpuSocket> = HashMap::new();
     let mut socket_count = 0u32;
+    let mut errors = Vec::new();
 
     // Collect CPU information using smbios-lib
-    collect_with_smbios(&mut cpu_data);
+    if let Err(e) = collect_with_smbios(&mut cpu_data) {
+        errors.push(e);
+    }
 
     // Calculate totals based on detected CPUs
     let mut total_cores = 0u32;
@@ -47,28 +57,18 @@ pub fn collect_cpu_info() -> CpuInfo {
     let mut cpus: Vec<CpuSocket> = cpu_data.into_values().collect();
     cpus.sort_by_key(|cpu| cpu.socket);
 
-    CpuInfo {
+    let data = CpuInfo {
         sockets: if socket_count > 0 { Some(socket_count) } else { None },
         cores: if total_cores > 0 { Some(total_cores) } else { None },
         threads: if total_threads > 0 { Some(total_threads) } else { None },
         cpus,
-    }
+    };
+
+    Collected { data, errors }
 }
 
-fn collect_with_smbios(cpu_data: &mut HashMap<u32, CpuSocket>) {
-    // Try to load SMBIOS data from the system
-    let smbios_data = match SMBiosData::try_load_from_file("/sys/firmware/dmi/tables/DMI", None) {
-        Ok(data) => data,
-        Err(_) => {
-            // If that fails, try reading the raw data and parsing it
-            match fs::read("/sys/firmware/dmi/tables/DMI") {
-                Ok(table_data) => {
-                    SMBiosData::from_vec_and_version(table_data, None)
-                },
-                Err(_) => return,
-            }
-        }
-    };
+fn collect_with_smbios(cpu_data: &mut HashMap<u32, CpuSocket>) -> Result<(), String> {
+    let smbios_data = load_smbios()?;
 
     let mut socket_index = 0u32;
     
@@ -178,4 +178,70 @@ fn collect_with_smbios(cpu_data: &mut HashMap<u32, CpuSocket>) {
             _ => continue,
         }
     }
-}
\ No newline at end of file
+
+    Ok(())
+}
+
+/// Collect per-logical-CPU frequency and governor settings from cpufreq sysfs,
+/// grouped by physical socket.
+pub fn collect_cpu_freq_info() -> CpuFreqInfo {
+    let mut sockets: BTreeMap<u32, Vec<CoreFreqInfo>> = BTreeMap::new();
+    let mut governors: Vec<String> = Vec::new();
+    let mut cpufreq_available = false;
+
+    let cpu_root = Path::new("/sys/devices/system/cpu");
+    if let Ok(entries) = fs::read_dir(cpu_root) {
+        for entry in entries.flatten() {
+            let name = match entry.file_name().into_string() {
+                Ok(n) => n,
+                Err(_) => continue,
+            };
+
+            // Only "cpuN" directories, not "cpufreq", "cpuidle", etc.
+            let cpu = match name.strip_prefix("cpu").and_then(|n| n.parse::<u32>().ok()) {
+                Some(c) => c,
+                None => continue,
+            };
+
+            let cpu_path = entry.path();
+            let socket = read_trim(cpu_path.join("topology/physical_package_id")).and_then(|s| s.parse().ok()).unwrap_or(0);
+            let core_id = read_trim(cpu_path.join("topology/core_id")).and_then(|s| s.parse().ok());
+
+            let freq_path = cpu_path.join("cpufreq");
+            if freq_path.exists() {
+                cpufreq_available = true;
+            }
+
+            // cpufreq reports frequencies in kHz
+            let scaling_governor = read_trim(freq_path.join("scaling_governor"));
+            if let Some(gov) = &scaling_governor {
+                if !governors.contains(gov) {
+                    governors.push(gov.clone());
+                }
+            }
+
+            sockets.entry(socket).or_default().push(CoreFreqInfo {
+                cpu,
+                core_id,
+                scaling_cur_freq_mhz: read_trim(freq_path.join("scaling_cur_freq")).and_then(|s| s.parse::<u32>().ok()).map(|khz| khz / 1000),
+                scaling_min_freq_mhz: read_trim(freq_path.join("scaling_min_freq")).and_then(|s| s.parse::<u32>().ok()).map(|khz| khz / 1000),
+                scaling_max_freq_mhz: read_trim(freq_path.join("scaling_max_freq")).and_then(|s| s.parse::<u32>().ok()).map(|khz| khz / 1000),
+                scaling_governor,
+            });
+        }
+    }
+
+    let sockets = sockets
+        .into_iter()
+        .map(|(socket, mut cpus)| {
+            cpus.sort_by_key(|c| c.cpu);
+            CpuFreqSocket { socket, cpus }
+        })
+        .collect();
+
+    CpuFreqInfo {
+        cpufreq_available,
+        governors,
+        sockets,
+    }
+}
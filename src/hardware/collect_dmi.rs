@@ -0,0 +1,25 @@
+use crate::hardware::load_smbios;
+use crate::hardware::types::DmiStructure;
+
+/// Decode every SMBIOS structure, optionally keeping only one DMI type
+/// (e.g. 17 for memory devices).
+pub fn collect_dmi_structures(dmi_type: Option<u8>) -> Result<Vec<DmiStructure>, Box<dyn std::error::Error>> {
+    let smbios_data = load_smbios()
+        .map_err(|e| format!("Unable to read SMBIOS table: {} (are you root?)", e))?;
+
+    let mut structures = Vec::new();
+    for structure in smbios_data.iter() {
+        let struct_type = structure.header.struct_type();
+        if dmi_type.is_some_and(|t| t != struct_type) {
+            continue;
+        }
+
+        structures.push(DmiStructure {
+            dmi_type: struct_type,
+            handle: format!("0x{:04X}", *structure.header.handle()),
+            data: serde_json::to_value(structure.defined_struct())?,
+        });
+    }
+
+    Ok(structures)
+}
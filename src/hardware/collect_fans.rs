@@ -0,0 +1,107 @@
+use std::fs;
+
+use crate::hardware::{ipmi_host, ipmitool, read_trim, Collected};
+use crate::hardware::types::FanInfo;
+
+/// Collect fan speeds from hwmon sysfs and IPMI fan sensors.
+pub fn collect_fans() -> Vec<FanInfo> {
+    collect_fans_checked().data
+}
+
+/// Like [`collect_fans`], but also reports why the result may be incomplete.
+pub(crate) fn collect_fans_checked() -> Collected<Vec<FanInfo>> {
+    let mut errors = Vec::new();
+    // hwmon describes this machine, not a remote BMC's
+    let mut fans = if ipmi_host().is_some() { Vec::new() } else { collect_hwmon_fans(&mut errors) };
+    fans.extend(collect_ipmi_fans());
+    Collected { data: fans, errors }
+}
+
+/// Read `/sys/class/hwmon/*/fan*_input`, naming fans from `fanN_label` when present.
+fn collect_hwmon_fans(errors: &mut Vec<String>) -> Vec<FanInfo> {
+    let mut fans = Vec::new();
+
+    let entries = match fs::read_dir("/sys/class/hwmon") {
+        Ok(e) => e,
+        Err(e) => {
+            errors.push(format!("cannot read /sys/class/hwmon: {}", e));
+            return fans;
+        }
+    };
+
+    let mut hwmons: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+    hwmons.sort();
+
+    for hwmon in hwmons {
+        let chip = read_trim(hwmon.join("name")).unwrap_or_else(|| "hwmon".to_string());
+
+        let mut indices: Vec<u32> = match fs::read_dir(&hwmon) {
+            Ok(files) => files
+                .flatten()
+                .filter_map(|f| f.file_name().into_string().ok())
+                .filter_map(|n| n.strip_prefix("fan")?.strip_suffix("_input")?.parse().ok())
+                .collect(),
+            Err(_) => continue,
+        };
+        indices.sort_unstable();
+
+        for index in indices {
+            let speed_rpm = read_trim(hwmon.join(format!("fan{}_input", index))).and_then(|v| v.parse::<u32>().ok());
+            let name = read_trim(hwmon.join(format!("fan{}_label", index)))
+                .unwrap_or_else(|| format!("{} fan{}", chip, index));
+
+            let flag = |suffix: &str| read_trim(hwmon.join(format!("fan{}_{}", index, suffix))).as_deref() == Some("1");
+            let status = if flag("fault") {
+                "fault"
+            } else if flag("alarm") {
+                "alarm"
+            } else if speed_rpm == Some(0) {
+                "stopped"
+            } else {
+                "ok"
+            };
+
+            fans.push(FanInfo {
+                name,
+                speed_rpm,
+                status: Some(status.to_string()),
+            });
+        }
+    }
+
+    fans
+}
+
+/// Parse `ipmitool sdr type Fan`:
+/// `FAN1             | 41h | ok  | 29.1 | 5400 RPM`
+fn collect_ipmi_fans() -> Vec<FanInfo> {
+    let Some(output) = ipmitool(&["sdr", "type", "Fan"]) else {
+        return Vec::new();
+    };
+
+    output
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split('|').map(|p| p.trim()).collect();
+            if parts.len() < 5 {
+                return None;
+            }
+
+            // Redundancy/presence sensors share the Fan type but carry no RPM
+            let reading = parts[4];
+            if !reading.contains("RPM") && reading != "No Reading" {
+                return None;
+            }
+
+            Some(FanInfo {
+                name: parts[0].to_string(),
+                speed_rpm: reading.split_whitespace().next().and_then(|v| v.parse::<f64>().ok()).map(|v| v as u32),
+                status: Some(match parts[2] {
+                    "ok" => "ok".to_string(),
+                    "ns" => "no reading".to_string(),
+                    other => other.to_string(),
+                }),
+            })
+        })
+        .collect()
+}
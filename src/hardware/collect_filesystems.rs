@@ -0,0 +1,138 @@
+use std::ffi::CString;
+use std::fs;
+
+use crate::hardware::types::FilesystemInfo;
+
+/// Kernel/pseudo filesystems that never hold user data.
+const PSEUDO_FILESYSTEMS: &[&str] = &[
+    "proc", "sysfs", "cgroup", "cgroup2", "devpts", "securityfs", "debugfs",
+    "tracefs", "pstore", "bpf", "mqueue", "hugetlbfs", "configfs", "fusectl",
+    "autofs", "binfmt_misc", "efivarfs", "nsfs", "rpc_pipefs", "selinuxfs",
+    "ramfs", "fuse.gvfsd-fuse", "fuse.portal",
+];
+
+/// RAM-backed filesystems, only reported when explicitly requested.
+const TMPFS_FILESYSTEMS: &[&str] = &["tmpfs", "devtmpfs"];
+
+/// Space and inode usage for a mounted filesystem, as reported by statvfs(3).
+pub struct FsUsage {
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub available_bytes: u64,
+    pub inodes_total: u64,
+    pub inodes_used: u64,
+    pub inodes_free: u64,
+}
+
+/// Entry point: list mounted filesystems from /proc/mounts with usage.
+pub fn collect_filesystems(include_tmpfs: bool) -> Vec<FilesystemInfo> {
+    let mut filesystems = Vec::new();
+
+    let mounts = match fs::read_to_string("/proc/mounts") {
+        Ok(m) => m,
+        Err(_) => return filesystems,
+    };
+
+    for line in mounts.lines() {
+        // Format: <device> <mountpoint> <fstype> <options> <dump> <pass>
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 4 {
+            continue;
+        }
+
+        let device = unescape_mount_field(parts[0]);
+        let mountpoint = unescape_mount_field(parts[1]);
+        let fstype = parts[2].to_string();
+
+        if PSEUDO_FILESYSTEMS.contains(&fstype.as_str()) {
+            continue;
+        }
+        if !include_tmpfs && TMPFS_FILESYSTEMS.contains(&fstype.as_str()) {
+            continue;
+        }
+
+        let read_only = parts[3].split(',').any(|opt| opt == "ro");
+        let usage = statvfs_usage(&mountpoint);
+
+        filesystems.push(FilesystemInfo {
+            device,
+            mountpoint,
+            fstype,
+            read_only,
+            total_bytes: usage.as_ref().map(|u| u.total_bytes),
+            used_bytes: usage.as_ref().map(|u| u.used_bytes),
+            available_bytes: usage.as_ref().map(|u| u.available_bytes),
+            inodes_total: usage.as_ref().map(|u| u.inodes_total),
+            inodes_used: usage.as_ref().map(|u| u.inodes_used),
+            inodes_free: usage.as_ref().map(|u| u.inodes_free),
+        });
+    }
+
+    filesystems
+}
+
+/// Query space and inode usage for the filesystem containing `path`.
+pub fn statvfs_usage(path: &str) -> Option<FsUsage> {
+    let c_path = CString::new(path).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+
+    // SAFETY: c_path is a valid NUL-terminated string and stat is a valid out-pointer.
+    let ret = unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) };
+    if ret != 0 {
+        return None;
+    }
+
+    let frsize = stat.f_frsize as u64;
+    let total_bytes = stat.f_blocks as u64 * frsize;
+    let free_bytes = stat.f_bfree as u64 * frsize;
+    // f_bavail excludes blocks reserved for root, matching what `df` shows as available
+    let available_bytes = stat.f_bavail as u64 * frsize;
+
+    Some(FsUsage {
+        total_bytes,
+        used_bytes: total_bytes.saturating_sub(free_bytes),
+        available_bytes,
+        inodes_total: stat.f_files as u64,
+        inodes_used: (stat.f_files as u64).saturating_sub(stat.f_ffree as u64),
+        inodes_free: stat.f_ffree as u64,
+    })
+}
+
+/// /proc/mounts escapes whitespace and backslashes as octal (e.g. "\040" for space).
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 4 <= bytes.len() {
+            let octal = std::str::from_utf8(&bytes[i + 1..i + 4]).unwrap_or("");
+            if let Ok(code) = u8::from_str_radix(octal, 8) {
+                out.push(code);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unescape_mount_field() {
+        assert_eq!(unescape_mount_field("/mnt/My\\040Drive"), "/mnt/My Drive");
+        assert_eq!(unescape_mount_field("/srv/a\\011b\\012c"), "/srv/a\tb\nc");
+        assert_eq!(unescape_mount_field("/data\\134raw"), "/data\\raw");
+        // Multi-byte names pass through untouched
+        assert_eq!(unescape_mount_field("/mnt/données"), "/mnt/données");
+        // Not a complete octal escape
+        assert_eq!(unescape_mount_field("/mnt/x\\04"), "/mnt/x\\04");
+        assert_eq!(unescape_mount_field("/mnt/x\\999"), "/mnt/x\\999");
+    }
+}
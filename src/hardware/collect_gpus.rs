@@ -1,34 +1,49 @@
 use std::process::Command;
 use std::fs;
 use std::path::Path;
-use pciid_parser::Database;
+use crate::hardware::{lookup_pci_ids, parse_pci_id, tool_on_path, Collected};
 use crate::hardware::types::GpuInfo;
+use crate::util::run_capture;
 
-pub fn collect_gpus() -> Vec<GpuInfo> {
+/// Find GPUs on the PCI bus, enriched by vendor tools, and report why the
+/// list may be incomplete. Only the full inventory collects GPUs.
+pub(crate) fn collect_gpus_checked() -> Collected<Vec<GpuInfo>> {
     let mut gpus = Vec::new();
+    let mut errors = Vec::new();
 
     // Scan PCI devices in /sys/bus/pci/devices for GPU devices
     let pci_devices_path = Path::new("/sys/bus/pci/devices");
-    if let Ok(entries) = fs::read_dir(pci_devices_path) {
-        for entry in entries.flatten() {
-            let device_path = entry.path();
+    match fs::read_dir(pci_devices_path) {
+        Ok(entries) => {
+            for entry in entries.flatten() {
+                let device_path = entry.path();
             
-            // Check if this is a GPU device by reading the class
-            if let Some(class_id) = read_pci_class(&device_path) {
-                // GPU classes: 0x030000 (VGA), 0x030200 (3D), 0x038000 (Display)
-                if is_gpu_class(&class_id) {
-                    if let Some(gpu) = create_gpu_info(&device_path) {
-                        gpus.push(gpu);
+                // Check if this is a GPU device by reading the class
+                if let Some(class_id) = read_pci_class(&device_path) {
+                    // GPU classes: 0x030000 (VGA), 0x030200 (3D), 0x038000 (Display)
+                    if is_gpu_class(&class_id) {
+                        if let Some(gpu) = create_gpu_info(&device_path) {
+                            gpus.push(gpu);
+                        }
                     }
                 }
             }
         }
+        Err(e) => errors.push(format!("cannot read /sys/bus/pci/devices: {}", e)),
     }
 
     // Also try vendor-specific tools for enhanced information
     enhance_gpus_with_tools(&mut gpus);
 
-    gpus
+    let has_vendor = |name: &str| gpus.iter().any(|g| g.vendor.as_deref().is_some_and(|v| v.to_lowercase().contains(name)));
+    if has_vendor("nvidia") && !tool_on_path("nvidia-smi") {
+        errors.push("nvidia-smi not found on PATH; NVIDIA VRAM, driver and UUID are missing".to_string());
+    }
+    if has_vendor("amd") && !tool_on_path("rocm-smi") {
+        errors.push("rocm-smi not found on PATH; AMD VRAM is missing".to_string());
+    }
+
+    Collected { data: gpus, errors }
 }
 
 fn read_pci_class(device_path: &Path) -> Option<String> {
@@ -61,7 +76,7 @@ fn create_gpu_info(device_path: &Path) -> Option<GpuInfo> {
     let device_id = read_hex_file(&device_path.join("device"))?;
 
     // Look up vendor and device names using PCI database
-    let (vendor_name, device_name) = lookup_pci_names(vendor_id, device_id)?;
+    let (vendor_name, device_name) = lookup_pci_ids(vendor_id, device_id)?;
 
     Some(GpuInfo {
         vendor: Some(vendor_name),
@@ -75,24 +90,9 @@ fn create_gpu_info(device_path: &Path) -> Option<GpuInfo> {
 
 fn read_hex_file(path: &Path) -> Option<u16> {
     let content = fs::read_to_string(path).ok()?;
-    let hex_str = content.trim().strip_prefix("0x").unwrap_or(content.trim());
-    u16::from_str_radix(hex_str, 16).ok()
+    parse_pci_id(content.trim())
 }
 
-fn lookup_pci_names(vendor_id: u16, device_id: u16) -> Option<(String, String)> {
-    let db = Database::read().ok()?;
-    
-    // Get vendor
-    let vendor = db.vendors.get(&vendor_id)?;
-    let vendor_name = vendor.name.clone();
-    
-    // Get device - fallback if device not found
-    let device_name = vendor.devices.get(&device_id)
-        .map(|d| d.name.clone())
-        .unwrap_or_else(|| format!("Unknown Device [0x{:04x}]", device_id));
-    
-    Some((vendor_name, device_name))
-}
 fn enhance_gpus_with_tools(gpus: &mut Vec<GpuInfo>) {
     // Try to enhance with vendor-specific tools for VRAM, driver version, and UUID
     for gpu in gpus.iter_mut() {
@@ -108,14 +108,12 @@ fn enhance_gpus_with_tools(gpus: &mut Vec<GpuInfo>) {
 
 fn enhance_nvidia_gpu(gpu: &mut GpuInfo) {
     // Try nvidia-smi for VRAM, driver version, and UUID
-    if let Ok(output) = Command::new("nvidia-smi")
-        .args(&["--query-gpu=name,memory.total,driver_version,uuid", 
-                "--format=csv,noheader,nounits"])
-        .output()
+    if let Ok(output) = run_capture(Command::new("nvidia-smi")
+        .args(["--query-gpu=name,memory.total,driver_version,uuid",
+               "--format=csv,noheader,nounits"]))
     {
-        if output.status.success() {
-            let nvidia_output = String::from_utf8_lossy(&output.stdout);
-            for line in nvidia_output.lines() {
+        if output.success {
+            for line in output.stdout.lines() {
                 let parts: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
                 if parts.len() >= 4 {
                     // Try to match this GPU by checking if the model contains key parts
@@ -137,15 +135,12 @@ fn enhance_nvidia_gpu(gpu: &mut GpuInfo) {
 
 fn enhance_amd_gpu(gpu: &mut GpuInfo) {
     // Try rocm-smi for AMD GPUs - focus on driver version and memory info
-    if let Ok(output) = Command::new("rocm-smi")
-        .args(&["--showproductname", "--showmeminfo"])
-        .output()
+    if let Ok(output) = run_capture(Command::new("rocm-smi")
+        .args(["--showproductname", "--showmeminfo"]))
     {
-        if output.status.success() {
-            let rocm_output = String::from_utf8_lossy(&output.stdout);
-            
+        if output.success {
             // Basic parsing for memory information
-            for line in rocm_output.lines() {
+            for line in output.stdout.lines() {
                 if line.contains("Memory") && line.contains("MB") {
                     if let Some(mem_str) = extract_number_from_line(line) {
                         if let Ok(vram) = mem_str.parse::<u32>() {
@@ -1,12 +1,24 @@
-use std::fs;
 use smbioslib::*;
+use crate::hardware::{load_smbios, Collected};
 use crate::hardware::types::{MemoryInfo, DimmInfo};
 
 pub fn collect_memory_info() -> MemoryInfo {
+    collect_memory_info_checked().data
+}
+
+/// Like [`collect_memory_info`], but also reports why the result may be incomplete.
+pub(crate) fn collect_memory_info_checked() -> Collected<MemoryInfo> {
     let mut total_bytes: u64 = 0;
+    let mut errors = Vec::new();
 
     // Collect memory information using smbios-lib
-    let dimms = collect_memory_with_smbios();
+    let dimms = match load_smbios() {
+        Ok(smbios_data) => dimms_from_smbios(&smbios_data),
+        Err(e) => {
+            errors.push(e);
+            Vec::new()
+        }
+    };
     
     // Calculate total from collected DIMMs
     for dimm in &dimms {
@@ -15,28 +27,16 @@ pub fn collect_memory_info() -> MemoryInfo {
         }
     }
 
-    MemoryInfo {
+    let data = MemoryInfo {
         total_bytes: if total_bytes > 0 { Some(total_bytes) } else { None },
         dimms,
-    }
+    };
+
+    Collected { data, errors }
 }
 
-fn collect_memory_with_smbios() -> Vec<DimmInfo> {
+fn dimms_from_smbios(smbios_data: &SMBiosData) -> Vec<DimmInfo> {
     let mut dimms = Vec::new();
-    
-    // Try to load SMBIOS data from the system
-    let smbios_data = match SMBiosData::try_load_from_file("/sys/firmware/dmi/tables/DMI", None) {
-        Ok(data) => data,
-        Err(_) => {
-            // If that fails, try reading the raw data and parsing it
-            match fs::read("/sys/firmware/dmi/tables/DMI") {
-                Ok(table_data) => {
-                    SMBiosData::from_vec_and_version(table_data, None)
-                },
-                Err(_) => return dimms,
-            }
-        }
-    };
 
     // Iterate through SMBIOS structures looking for memory devices
     for structure in smbios_data.iter() {
@@ -55,6 +55,10 @@ fn collect_memory_with_smbios() -> Vec<DimmInfo> {
                                 manufacturer: None,
                                 serial_number: None,
                                 part_number: None,
+                                rank: None,
+                                voltage_v: None,
+                                form_factor: None,
+                                technology: None,
                             };
 
                             // Device locator (slot)
@@ -77,12 +81,19 @@ fn collect_memory_with_smbios() -> Vec<DimmInfo> {
 
                             // Memory type
                             if let Some(mem_type) = memory_device.memory_type() {
-                                let type_str = format!("{:?}", mem_type.value).to_uppercase();
-                                if type_str != "UNKNOWN" {
-                                    dimm.mem_type = Some(type_str);
-                                }
+                                dimm.mem_type = memory_type_to_string(mem_type);
                             }
 
+                            // Rank (attributes bits 0-3, 0 = unknown)
+                            dimm.rank = memory_device.attributes().map(|a| a & 0x0F).filter(|r| *r > 0);
+
+                            // Configured voltage (millivolts, 0 = unknown)
+                            dimm.voltage_v = memory_device.configured_voltage().filter(|mv| *mv > 0).map(|mv| mv as f32 / 1000.0);
+
+                            // Form factor and technology (DRAM vs NVDIMM)
+                            dimm.form_factor = memory_device.form_factor().and_then(form_factor_to_string);
+                            dimm.technology = memory_device.memory_technology().and_then(memory_technology_to_string);
+
                             // Speed - check configured and max speed
                             if let Some(config_speed) = memory_device.configured_memory_speed() {
                                 match config_speed {
@@ -138,6 +149,10 @@ fn collect_memory_with_smbios() -> Vec<DimmInfo> {
                                 manufacturer: None,
                                 serial_number: None,
                                 part_number: None,
+                                rank: None,
+                                voltage_v: None,
+                                form_factor: None,
+                                technology: None,
                             };
 
                             // Device locator (slot)
@@ -175,12 +190,19 @@ fn collect_memory_with_smbios() -> Vec<DimmInfo> {
 
                             // Memory type
                             if let Some(mem_type) = memory_device.memory_type() {
-                                let type_str = format!("{:?}", mem_type).to_uppercase();
-                                if type_str != "UNKNOWN" {
-                                    dimm.mem_type = Some(type_str);
-                                }
+                                dimm.mem_type = memory_type_to_string(mem_type);
                             }
 
+                            // Rank (attributes bits 0-3, 0 = unknown)
+                            dimm.rank = memory_device.attributes().map(|a| a & 0x0F).filter(|r| *r > 0);
+
+                            // Configured voltage (millivolts, 0 = unknown)
+                            dimm.voltage_v = memory_device.configured_voltage().filter(|mv| *mv > 0).map(|mv| mv as f32 / 1000.0);
+
+                            // Form factor and technology (DRAM vs NVDIMM)
+                            dimm.form_factor = memory_device.form_factor().and_then(form_factor_to_string);
+                            dimm.technology = memory_device.memory_technology().and_then(memory_technology_to_string);
+
                             // Speed - check configured and max speed
                             if let Some(config_speed) = memory_device.configured_memory_speed() {
                                 match config_speed {
@@ -233,6 +255,10 @@ fn collect_memory_with_smbios() -> Vec<DimmInfo> {
                                 manufacturer: None,
                                 serial_number: None,
                                 part_number: None,
+                                rank: None,
+                                voltage_v: None,
+                                form_factor: None,
+                                technology: None,
                             };
 
                             // Device locator (slot)
@@ -262,12 +288,19 @@ fn collect_memory_with_smbios() -> Vec<DimmInfo> {
 
                             // Memory type
                             if let Some(mem_type) = memory_device.memory_type() {
-                                let type_str = format!("{:?}", mem_type).to_uppercase();
-                                if type_str != "UNKNOWN" {
-                                    dimm.mem_type = Some(type_str);
-                                }
+                                dimm.mem_type = memory_type_to_string(mem_type);
                             }
 
+                            // Rank (attributes bits 0-3, 0 = unknown)
+                            dimm.rank = memory_device.attributes().map(|a| a & 0x0F).filter(|r| *r > 0);
+
+                            // Configured voltage (millivolts, 0 = unknown)
+                            dimm.voltage_v = memory_device.configured_voltage().filter(|mv| *mv > 0).map(|mv| mv as f32 / 1000.0);
+
+                            // Form factor and technology (DRAM vs NVDIMM)
+                            dimm.form_factor = memory_device.form_factor().and_then(form_factor_to_string);
+                            dimm.technology = memory_device.memory_technology().and_then(memory_technology_to_string);
+
                             // Speed - check configured and max speed
                             if let Some(config_speed) = memory_device.configured_memory_speed() {
                                 match config_speed {
@@ -321,3 +354,139 @@ fn collect_memory_with_smbios() -> Vec<DimmInfo> {
     
     dimms
 }
+
+/// Clean, uppercase memory type name (e.g. "DDR4", "LPDDR5"); None when unknown.
+fn memory_type_to_string(mem_type: MemoryDeviceTypeData) -> Option<String> {
+    let name = match mem_type.value {
+        MemoryDeviceType::Other => "OTHER",
+        MemoryDeviceType::Dram => "DRAM",
+        MemoryDeviceType::Edram => "EDRAM",
+        MemoryDeviceType::Vram => "VRAM",
+        MemoryDeviceType::Sram => "SRAM",
+        MemoryDeviceType::Ram => "RAM",
+        MemoryDeviceType::Rom => "ROM",
+        MemoryDeviceType::Flash => "FLASH",
+        MemoryDeviceType::Eeprom => "EEPROM",
+        MemoryDeviceType::Feprom => "FEPROM",
+        MemoryDeviceType::Eprom => "EPROM",
+        MemoryDeviceType::Cdram => "CDRAM",
+        MemoryDeviceType::ThreeDram => "3DRAM",
+        MemoryDeviceType::Sdram => "SDRAM",
+        MemoryDeviceType::Sgram => "SGRAM",
+        MemoryDeviceType::Rdram => "RDRAM",
+        MemoryDeviceType::Ddr => "DDR",
+        MemoryDeviceType::Ddr2 => "DDR2",
+        MemoryDeviceType::Ddr2Fbdimm => "DDR2 FB-DIMM",
+        MemoryDeviceType::Ddr3 => "DDR3",
+        MemoryDeviceType::Fbd2 => "FBD2",
+        MemoryDeviceType::Ddr4 => "DDR4",
+        MemoryDeviceType::Lpddr => "LPDDR",
+        MemoryDeviceType::Lpddr2 => "LPDDR2",
+        MemoryDeviceType::Lpddr3 => "LPDDR3",
+        MemoryDeviceType::Lpddr4 => "LPDDR4",
+        MemoryDeviceType::LogicalNonVolatileDevice => "NVDIMM",
+        MemoryDeviceType::Hbm => "HBM",
+        MemoryDeviceType::Hbm2 => "HBM2",
+        MemoryDeviceType::Ddr5 => "DDR5",
+        MemoryDeviceType::Lpddr5 => "LPDDR5",
+        MemoryDeviceType::Hbm3 => "HBM3",
+        MemoryDeviceType::Mrdimm => "MRDIMM",
+        MemoryDeviceType::Unknown | MemoryDeviceType::None => return None,
+    };
+    Some(name.to_string())
+}
+
+/// Readable module form factor (e.g. "DIMM", "SODIMM"); None when unknown.
+fn form_factor_to_string(form_factor: MemoryFormFactorData) -> Option<String> {
+    let name = match form_factor.value {
+        MemoryFormFactor::Other => "Other",
+        MemoryFormFactor::Simm => "SIMM",
+        MemoryFormFactor::Sip => "SIP",
+        MemoryFormFactor::Chip => "Chip",
+        MemoryFormFactor::Dip => "DIP",
+        MemoryFormFactor::Zip => "ZIP",
+        MemoryFormFactor::ProprietaryCard => "Proprietary Card",
+        MemoryFormFactor::Dimm => "DIMM",
+        MemoryFormFactor::Tsop => "TSOP",
+        MemoryFormFactor::RowOfChips => "Row Of Chips",
+        MemoryFormFactor::Rimm => "RIMM",
+        MemoryFormFactor::Sodimm => "SODIMM",
+        MemoryFormFactor::Srimm => "SRIMM",
+        MemoryFormFactor::Fbdimm => "FB-DIMM",
+        MemoryFormFactor::Die => "Die",
+        MemoryFormFactor::Camm => "CAMM",
+        MemoryFormFactor::Cudimm => "CUDIMM",
+        MemoryFormFactor::Csodimm => "CSODIMM",
+        MemoryFormFactor::Unknown | MemoryFormFactor::None => return None,
+    };
+    Some(name.to_string())
+}
+
+/// Readable memory technology (e.g. "DRAM", "NVDIMM-N"); None when unknown.
+fn memory_technology_to_string(technology: MemoryDeviceTechnologyData) -> Option<String> {
+    let name = match technology.value {
+        MemoryDeviceTechnology::Other => "Other",
+        MemoryDeviceTechnology::Dram => "DRAM",
+        MemoryDeviceTechnology::NvdimmN => "NVDIMM-N",
+        MemoryDeviceTechnology::NvdimmF => "NVDIMM-F",
+        MemoryDeviceTechnology::NvdimmP => "NVDIMM-P",
+        MemoryDeviceTechnology::IntelOptaneDcPersistentMemory => "Intel Optane DC Persistent Memory",
+        MemoryDeviceTechnology::Mrdimm => "MRDIMM",
+        MemoryDeviceTechnology::Unknown | MemoryDeviceTechnology::None => return None,
+    };
+    Some(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Minimal SMBIOS type 17 (Memory Device) record with the given size word,
+    // extended size and memory type code.
+    fn memory_device_record(handle: u16, size: u16, extended_mb: u32, mem_type: u8) -> Vec<u8> {
+        let mut rec = vec![0u8; 0x29];
+        rec[0] = 17;
+        rec[1] = 0x29;
+        rec[2..4].copy_from_slice(&handle.to_le_bytes());
+        rec[0x0C..0x0E].copy_from_slice(&size.to_le_bytes());
+        rec[0x0E] = 0x09; // form factor: DIMM
+        rec[0x10] = 1; // device locator -> string 1
+        rec[0x12] = mem_type;
+        rec[0x15..0x17].copy_from_slice(&3200u16.to_le_bytes());
+        rec[0x1B] = 2; // attributes: dual rank
+        rec[0x1C..0x20].copy_from_slice(&extended_mb.to_le_bytes());
+        rec[0x26..0x28].copy_from_slice(&1200u16.to_le_bytes()); // configured voltage (mV)
+        rec[0x28] = 0x04; // memory technology: NVDIMM-N
+        rec.extend_from_slice(b"DIMM_A1\0\0");
+        rec
+    }
+
+    fn dimms_for_type(mem_type: u8) -> Vec<DimmInfo> {
+        let mut table = Vec::new();
+        table.extend(memory_device_record(0x1100, 0x7FFF, 65536, mem_type)); // SeeExtendedSize
+        table.extend(memory_device_record(0x1101, 0x8000 | 16384, 0, mem_type)); // Kilobytes
+        table.extend(memory_device_record(0x1102, 16384, 0, mem_type)); // Megabytes
+        dimms_from_smbios(&SMBiosData::from_vec_and_version(table, Some(SMBiosVersion::new(3, 2, 0))))
+    }
+
+    #[test]
+    fn test_every_size_branch_reports_the_same_fields() {
+        for (code, expected) in [(0x1A, "DDR4"), (0x22, "DDR5"), (0x1E, "LPDDR4"), (0x23, "LPDDR5")] {
+            let dimms = dimms_for_type(code);
+            assert_eq!(dimms.len(), 3, "one DIMM per size branch");
+            for dimm in &dimms {
+                assert_eq!(dimm.mem_type.as_deref(), Some(expected));
+                assert_eq!(dimm.rank, Some(2));
+                assert_eq!(dimm.voltage_v, Some(1.2));
+                assert_eq!(dimm.form_factor.as_deref(), Some("DIMM"));
+                assert_eq!(dimm.technology.as_deref(), Some("NVDIMM-N"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_memory_type_is_omitted() {
+        assert!(dimms_for_type(0x02).iter().all(|d| d.mem_type.is_none()));
+        assert_eq!(memory_type_to_string(MemoryDeviceTypeData::from(0xFE)), None);
+    }
+}
\ No newline at end of file
@@ -3,13 +3,24 @@ use std::fs;
 use std::path::Path;
 use std::process::Command;
 
-use pciid_parser::Database;
 use serde_json::Value;
 
+use crate::hardware::{lookup_pci_ids, parse_pci_id, tool_on_path, Collected};
 use crate::hardware::types::{IpAddress, NetInterface, NetworkInfo, RouteInfo};
+use crate::util::run_capture;
 
 /// Entry point: collect full network info (interfaces + routes).
 pub fn collect_network_info() -> NetworkInfo {
+    collect_network_info_checked().data
+}
+
+/// Like [`collect_network_info`], but also reports why the result may be incomplete.
+pub(crate) fn collect_network_info_checked() -> Collected<NetworkInfo> {
+    let mut errors = Vec::new();
+    if !tool_on_path("ip") {
+        errors.push("ip not found on PATH; addresses and routes are missing".to_string());
+    }
+
     let iface_addrs = collect_ip_addrs();
     let routes = collect_routes();
 
@@ -18,11 +29,15 @@ pub fn collect_network_info() -> NetworkInfo {
 
     let entries = match fs::read_dir(sys_class_net) {
         Ok(e) => e,
-        Err(_) => {
-            return NetworkInfo {
-                interfaces,
-                routes,
-            }
+        Err(e) => {
+            errors.push(format!("cannot read /sys/class/net: {}", e));
+            return Collected {
+                data: NetworkInfo {
+                    interfaces,
+                    routes,
+                },
+                errors,
+            };
         }
     };
 
@@ -51,8 +66,13 @@ pub fn collect_network_info() -> NetworkInfo {
         // PCI address from device path
         let pci_address = read_pci_address(&iface_sys_path.join("device"));
 
-        // Firmware version from ethtool -i
-        let firmware_version = ethtool_firmware(&name);
+        // NUMA locality; the kernel reports -1 when there is no affinity
+        let numa_node = read_to_string_trim(iface_sys_path.join("device/numa_node"))
+            .and_then(|s| s.parse::<i32>().ok())
+            .filter(|&node| node >= 0);
+
+        // Firmware and driver versions from ethtool -i
+        let (firmware_version, driver_version) = ethtool_versions(&name);
 
         // Vendor/Device information for PCI devices
         let (vendor_name, device_name) = read_vendor_device_info(&iface_sys_path);
@@ -69,10 +89,12 @@ pub fn collect_network_info() -> NetworkInfo {
             mtu,
             speed_mbps,
             driver,
+            driver_version,
             firmware_version,
             vendor_name,
             device_name,
             pci_address,
+            numa_node,
             addresses,
             is_primary,
             bond_group,
@@ -80,9 +102,12 @@ pub fn collect_network_info() -> NetworkInfo {
         });
     }
 
-    NetworkInfo {
-        interfaces,
-        routes,
+    Collected {
+        data: NetworkInfo {
+            interfaces,
+            routes,
+        },
+        errors,
     }
 }
 
@@ -182,7 +207,8 @@ fn read_vendor_device_info(iface_sys_path: &Path) -> (Option<String>, Option<Str
     let device_id = read_to_string_trim(device_path.join("device"));
 
     if let (Some(vendor), Some(device)) = (vendor_id, device_id) {
-        if let Some((vendor_name, device_name)) = lookup_pci_ids(&vendor, &device) {
+        let ids = parse_pci_id(&vendor).zip(parse_pci_id(&device));
+        if let Some((vendor_name, device_name)) = ids.and_then(|(v, d)| lookup_pci_ids(v, d)) {
             return (Some(vendor_name), Some(device_name));
         }
     }
@@ -190,48 +216,17 @@ fn read_vendor_device_info(iface_sys_path: &Path) -> (Option<String>, Option<Str
     (None, None)
 }
 
-/// Look up vendor and device names using PCI database
-fn lookup_pci_ids(vendor_hex: &str, device_hex: &str) -> Option<(String, String)> {
-    // Parse hex IDs
-    let vendor_id = u16::from_str_radix(
-        vendor_hex.strip_prefix("0x").unwrap_or(vendor_hex), 
-        16
-    ).ok()?;
-    let device_id = u16::from_str_radix(
-        device_hex.strip_prefix("0x").unwrap_or(device_hex), 
-        16
-    ).ok()?;
-
-    // Load PCI database from system default paths
-    let db = Database::read().ok()?;
-    
-    // Get vendor - this should always work if the vendor exists
-    let vendor = db.vendors.get(&vendor_id)?;
-    let vendor_name = vendor.name.clone();
-    
-    // Get device - fallback to "Unknown Device" if device not found
-    let device_name = vendor.devices.get(&device_id)
-        .map(|d| d.name.clone())
-        .unwrap_or_else(|| format!("Unknown Device [{}]", device_hex));
-    
-    Some((vendor_name, device_name))
-}
-
 //
 // ethtool fallbacks
 //
 
 fn ethtool_speed(iface: &str) -> Option<u32> {
-    let output = Command::new("ethtool")
-        .arg(iface)
-        .output()
-        .ok()?;
-    if !output.status.success() {
+    let output = run_capture(Command::new("ethtool").arg(iface)).ok()?;
+    if !output.success {
         return None;
     }
 
-    let text = String::from_utf8_lossy(&output.stdout);
-    for line in text.lines() {
+    for line in output.stdout.lines() {
         // Example: "Speed: 25000Mb/s"
         if let Some(rest) = line.trim().strip_prefix("Speed:") {
             let part = rest.trim().split_whitespace().next().unwrap_or("");
@@ -245,22 +240,25 @@ fn ethtool_speed(iface: &str) -> Option<u32> {
     None
 }
 
-fn ethtool_firmware(iface: &str) -> Option<String> {
-    let output = Command::new("ethtool")
-        .args(["-i", iface])
-        .output()
-        .ok()?;
-    if !output.status.success() {
-        return None;
-    }
+/// Firmware and driver versions from `ethtool -i`. The firmware version falls
+/// back to the driver version when the NIC does not report one.
+fn ethtool_versions(iface: &str) -> (Option<String>, Option<String>) {
+    let output = match run_capture(Command::new("ethtool").args(["-i", iface])) {
+        Ok(o) if o.success => o,
+        _ => return (None, None),
+    };
+
+    let (firmware_version, driver_version) = parse_ethtool_info(&output.stdout);
+    (firmware_version.or_else(|| driver_version.clone()), driver_version)
+}
 
-    let text = String::from_utf8_lossy(&output.stdout);
+/// Pull `firmware-version:` and `version:` out of `ethtool -i` output.
+fn parse_ethtool_info(text: &str) -> (Option<String>, Option<String>) {
     let mut firmware_version = None;
     let mut driver_version = None;
     
     for line in text.lines() {
         let line = line.trim();
-        // Look for firmware version first (preferred)
         if let Some(rest) = line.strip_prefix("firmware-version:") {
             let version = rest.trim().to_string();
             if !version.is_empty() && version != "N/A" && version != "n/a" {
@@ -274,8 +272,7 @@ fn ethtool_firmware(iface: &str) -> Option<String> {
         }
     }
     
-    // Prefer firmware-version over driver version
-    firmware_version.or(driver_version)
+    (firmware_version, driver_version)
 }
 
 //
@@ -285,16 +282,14 @@ fn ethtool_firmware(iface: &str) -> Option<String> {
 fn collect_ip_addrs() -> HashMap<String, Vec<IpAddress>> {
     let mut map: HashMap<String, Vec<IpAddress>> = HashMap::new();
 
-    let output = Command::new("ip")
-        .args(["-j", "addr"])
-        .output();
+    let output = run_capture(Command::new("ip").args(["-j", "addr"]));
 
     let output = match output {
-        Ok(o) if o.status.success() => o,
+        Ok(o) if o.success => o,
         _ => return map,
     };
 
-    let json: Value = match serde_json::from_slice(&output.stdout) {
+    let json: Value = match serde_json::from_str(&output.stdout) {
         Ok(v) => v,
         Err(_) => return map,
     };
@@ -321,6 +316,12 @@ fn collect_ip_addrs() -> HashMap<String, Vec<IpAddress>> {
                     .get("prefixlen")
                     .and_then(|v| v.as_u64())
                     .unwrap_or(0) as u8;
+                let scope = addr.get("scope").and_then(|v| v.as_str()).map(|s| s.to_string());
+                // ip reports "forever" as the all-ones u32
+                let valid_lifetime_secs = addr
+                    .get("valid_life_time")
+                    .and_then(|v| v.as_u64())
+                    .filter(|&secs| secs != u32::MAX as u64);
 
                 if local.is_empty() {
                     continue;
@@ -330,6 +331,8 @@ fn collect_ip_addrs() -> HashMap<String, Vec<IpAddress>> {
                     family: family.to_string(),
                     address: local.to_string(),
                     prefix,
+                    scope,
+                    valid_lifetime_secs,
                 });
             }
         }
@@ -341,22 +344,35 @@ fn collect_ip_addrs() -> HashMap<String, Vec<IpAddress>> {
 }
 
 //
-// Routes via `ip -j route`
+// Routes via `ip -j -4 route` and `ip -j -6 route`
 //
 
 fn collect_routes() -> Vec<RouteInfo> {
-    let mut routes = Vec::new();
+    // Plain `ip route` only lists IPv4, so ask for each family explicitly
+    let tables: Vec<(&str, String)> = [("-4", "inet"), ("-6", "inet6")]
+        .into_iter()
+        .filter_map(|(flag, family)| {
+            let output = run_capture(Command::new("ip").args(["-j", flag, "route"])).ok()?;
+            output.success.then_some((family, output.stdout))
+        })
+        .collect();
+
+    merge_route_tables(&tables)
+}
 
-    let output = Command::new("ip")
-        .args(["-j", "route"])
-        .output();
+/// One route list from each family's `ip -j route` output, in table order.
+fn merge_route_tables(tables: &[(&str, String)]) -> Vec<RouteInfo> {
+    tables
+        .iter()
+        .flat_map(|(family, stdout)| parse_routes(stdout.as_bytes(), family))
+        .collect()
+}
 
-    let output = match output {
-        Ok(o) if o.status.success() => o,
-        _ => return routes,
-    };
+/// Parse one family's `ip -j route` output.
+fn parse_routes(stdout: &[u8], family: &str) -> Vec<RouteInfo> {
+    let mut routes = Vec::new();
 
-    let json: Value = match serde_json::from_slice(&output.stdout) {
+    let json: Value = match serde_json::from_slice(stdout) {
         Ok(v) => v,
         Err(_) => return routes,
     };
@@ -382,8 +398,17 @@ fn collect_routes() -> Vec<RouteInfo> {
             .and_then(|v| v.as_str())
             .unwrap_or("")
             .to_string();
-
-        routes.push(RouteInfo { dst, gateway, iface });
+        let prefsrc = r.get("prefsrc").and_then(|v| v.as_str()).map(|s| s.to_string());
+        let metric = r.get("metric").and_then(|v| v.as_u64()).map(|m| m as u32);
+
+        routes.push(RouteInfo {
+            family: family.to_string(),
+            dst,
+            gateway,
+            iface,
+            prefsrc,
+            metric,
+        });
     }
 
     routes
@@ -421,12 +446,10 @@ fn detect_bond_info(iface: &str, iface_sys_path: &Path) -> (bool, Option<String>
     // This is a fallback for interfaces that aren't in bonds but are the main interface
     if !is_primary {
         // Check if interface has any IP addresses configured
-        let output = Command::new("ip")
-            .args(["-j", "addr", "show", iface])
-            .output();
+        let output = run_capture(Command::new("ip").args(["-j", "addr", "show", iface]));
 
         if let Ok(output) = output {
-            if let Ok(json) = serde_json::from_slice::<Value>(&output.stdout) {
+            if let Ok(json) = serde_json::from_str::<Value>(&output.stdout) {
                 if let Some(arr) = json.as_array() {
                     for iface_data in arr {
                         if let Some(addr_info) = iface_data.get("addr_info").and_then(|v| v.as_array()) {
@@ -450,3 +473,82 @@ fn detect_bond_info(iface: &str, iface_sys_path: &Path) -> (bool, Option<String>
 
     (is_primary, bond_group, bond_master)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ethtool_info_reports_both_versions() {
+        let text = "driver: ice\nversion: 1.13.7\nfirmware-version: 4.40 0x8001af1e 1.3429.0\nexpansion-rom-version: \nbus-info: 0000:3b:00.0\n";
+        let (firmware_version, driver_version) = parse_ethtool_info(text);
+
+        assert_eq!(firmware_version.as_deref(), Some("4.40 0x8001af1e 1.3429.0"));
+        assert_eq!(driver_version.as_deref(), Some("1.13.7"));
+    }
+
+    #[test]
+    fn test_parse_ethtool_info_ignores_na_firmware() {
+        let text = "driver: virtio_net\nversion: 1.0.0\nfirmware-version: N/A\n";
+        assert_eq!(parse_ethtool_info(text), (None, Some("1.0.0".to_string())));
+    }
+
+    #[test]
+    fn test_parse_routes_ipv4() {
+        let stdout = br#"[
+            {"dst":"default","gateway":"10.0.0.1","dev":"eno1","protocol":"dhcp","prefsrc":"10.0.0.42","metric":100,"flags":[]},
+            {"dst":"10.0.0.0/24","dev":"eno1","protocol":"kernel","scope":"link","prefsrc":"10.0.0.42","flags":[]}
+        ]"#;
+        let routes = parse_routes(stdout, "inet");
+
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].family, "inet");
+        assert_eq!(routes[0].dst, "default");
+        assert_eq!(routes[0].gateway, "10.0.0.1");
+        assert_eq!(routes[0].prefsrc.as_deref(), Some("10.0.0.42"));
+        assert_eq!(routes[0].metric, Some(100));
+        assert_eq!(routes[1].gateway, "");
+        assert_eq!(routes[1].metric, None);
+    }
+
+    #[test]
+    fn test_parse_routes_ipv6_default() {
+        let stdout = br#"[
+            {"dst":"2001:db8::/64","dev":"eno1","protocol":"ra","metric":100,"flags":[],"pref":"medium"},
+            {"dst":"fe80::/64","dev":"eno1","protocol":"kernel","metric":256,"flags":[],"pref":"medium"},
+            {"dst":"default","gateway":"fe80::1","dev":"eno1","protocol":"ra","metric":1024,"flags":[],"pref":"medium"}
+        ]"#;
+        let routes = parse_routes(stdout, "inet6");
+
+        let default = routes.iter().find(|r| r.dst == "default").expect("v6 default route");
+        assert_eq!(default.family, "inet6");
+        assert_eq!(default.gateway, "fe80::1");
+        assert_eq!(default.iface, "eno1");
+        assert_eq!(default.metric, Some(1024));
+        assert_eq!(default.prefsrc, None);
+    }
+
+    #[test]
+    fn test_merge_route_tables_dual_stack() {
+        let tables = [
+            ("inet", include_str!("testdata/ip_route_v4.json").to_string()),
+            ("inet6", include_str!("testdata/ip_route_v6.json").to_string()),
+        ];
+        let routes = merge_route_tables(&tables);
+
+        assert_eq!(routes.len(), 6);
+        assert!(routes[..3].iter().all(|r| r.family == "inet"));
+        assert!(routes[3..].iter().all(|r| r.family == "inet6"));
+
+        let defaults: Vec<_> = routes.iter().filter(|r| r.dst == "default").collect();
+        assert_eq!(defaults.len(), 2);
+        assert_eq!((defaults[0].family.as_str(), defaults[0].gateway.as_str()), ("inet", "10.20.1.1"));
+        assert_eq!((defaults[1].family.as_str(), defaults[1].gateway.as_str()), ("inet6", "fe80::1"));
+        assert_eq!(routes[1].prefsrc.as_deref(), Some("10.20.1.107"));
+    }
+
+    #[test]
+    fn test_parse_routes_rejects_non_json() {
+        assert!(parse_routes(b"Error: ipv6 disabled", "inet6").is_empty());
+    }
+}
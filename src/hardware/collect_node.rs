@@ -1,21 +1,40 @@
 use std::fs;
 use std::process::Command;
 use smbioslib::*;
+use crate::hardware::{ipmi_host, ipmitool_retried, load_smbios, tool_on_path, Collected};
+use crate::util::{retry_tool, run_capture};
 use crate::hardware::types::{NodeInfo, BiosInfo, BmcInfo, MotherboardInfo};
 
 pub fn collect_node_info() -> NodeInfo {
-    let hostname = get_hostname();
+    collect_node_info_checked().data
+}
+
+/// Like [`collect_node_info`], but also reports why the result may be incomplete.
+pub(crate) fn collect_node_info_checked() -> Collected<NodeInfo> {
+    let mut errors = Vec::new();
+    if let Some(host) = ipmi_host() {
+        return collect_remote_node_info(host);
+    }
+
+    let hostname = get_hostname(&mut errors);
     let architecture = std::env::consts::ARCH.to_string();
+    let kernel_version = fs::read_to_string("/proc/sys/kernel/osrelease")
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
     
     // Collect all DMI information using smbios-lib
-    let (product_name, manufacturer, serial_number, chassis_manufacturer, chassis_serial_number, motherboard, bios) = 
-        collect_dmi_info();
+    let DmiInfo { product_name, manufacturer, serial_number, chassis_manufacturer, chassis_serial_number, motherboard, bios } =
+        collect_dmi_info(&mut errors);
+    let virtualization = detect_virtualization(product_name.as_deref(), manufacturer.as_deref());
     
     let bmc = Some(collect_bmc_from_dmi());
 
-    NodeInfo {
+    let data = NodeInfo {
         hostname,
         architecture,
+        kernel_version,
+        virtualization,
         product_name,
         manufacturer,
         serial_number,
@@ -24,29 +43,149 @@ pub fn collect_node_info() -> NodeInfo {
         motherboard,
         bios,
         bmc,
+    };
+
+    Collected { data, errors }
+}
+
+/// A node known only through its BMC: named after the BMC host, with the
+/// SMBIOS and kernel fields, which come from this machine, left empty.
+fn collect_remote_node_info(host: &str) -> Collected<NodeInfo> {
+    let mut errors = Vec::new();
+    let bmc = collect_ipmi_bmc();
+    if bmc.is_none() {
+        errors.push(format!("no answer to ipmitool mc info from {}", host));
     }
+
+    let data = NodeInfo {
+        hostname: host.to_string(),
+        bmc,
+        ..Default::default()
+    };
+    Collected { data, errors }
 }
 
-fn get_hostname() -> String {
-    fs::read_to_string("/proc/sys/kernel/hostname")
-        .unwrap_or_else(|_| "unknown".to_string())
-        .trim()
-        .to_string()
+fn get_hostname(errors: &mut Vec<String>) -> String {
+    match fs::read_to_string("/proc/sys/kernel/hostname") {
+        Ok(hostname) => hostname.trim().to_string(),
+        Err(e) => {
+            errors.push(format!("cannot read /proc/sys/kernel/hostname: {}", e));
+            "unknown".to_string()
+        }
+    }
 }
 
-fn collect_dmi_info() -> (Option<String>, Option<String>, Option<String>, Option<String>, Option<String>, Option<MotherboardInfo>, Option<BiosInfo>) {
-    // Try to load SMBIOS data from the system
-    let smbios_data = match SMBiosData::try_load_from_file("/sys/firmware/dmi/tables/DMI", None) {
-        Ok(data) => data,
-        Err(_) => {
-            // If that fails, try reading the raw data and parsing it
-            match fs::read("/sys/firmware/dmi/tables/DMI") {
-                Ok(table_data) => {
-                    SMBiosData::from_vec_and_version(table_data, None)
-                },
-                Err(_) => return (None, None, None, None, None, None, None),
+/// Work out whether we run on bare metal, in a VM guest or in a container,
+/// using systemd-detect-virt's vocabulary. systemd-detect-virt is asked first
+/// when installed; otherwise containers are spotted from marker files and
+/// PID 1's cgroup, and VMs from the DMI strings and the CPU hypervisor flag.
+fn detect_virtualization(product_name: Option<&str>, manufacturer: Option<&str>) -> Option<String> {
+    if tool_on_path("systemd-detect-virt") {
+        // Exits 1 after printing "none" on bare metal, so only the output counts
+        if let Ok(output) = run_capture(&mut Command::new("systemd-detect-virt")) {
+            let id = output.stdout.trim();
+            if !id.is_empty() {
+                return Some(id.to_string());
             }
         }
+    }
+
+    let container = if fs::metadata("/.dockerenv").is_ok() {
+        Some("docker")
+    } else if fs::metadata("/run/.containerenv").is_ok() {
+        Some("podman")
+    } else {
+        fs::read_to_string("/proc/1/cgroup").ok().as_deref().and_then(container_from_cgroup)
+    };
+    if let Some(container) = container {
+        return Some(container.to_string());
+    }
+
+    if let Some(vm) = vm_from_dmi(product_name, manufacturer) {
+        return Some(vm.to_string());
+    }
+    // Xen PV guests have no DMI table of their own
+    if fs::read_to_string("/sys/hypervisor/type").is_ok_and(|t| t.trim() == "xen") {
+        return Some("xen".to_string());
+    }
+
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    let hypervisor_flag = cpuinfo
+        .lines()
+        .filter(|line| line.starts_with("flags"))
+        .any(|line| line.split_whitespace().any(|flag| flag == "hypervisor"));
+    // systemd-detect-virt's id for a hypervisor it can't name
+    Some(if hypervisor_flag { "vm-other" } else { "none" }.to_string())
+}
+
+/// Recognise a container runtime from the cgroup paths of PID 1.
+fn container_from_cgroup(cgroup: &str) -> Option<&'static str> {
+    for line in cgroup.lines() {
+        // hierarchy-id:controllers:path
+        let path = line.splitn(3, ':').nth(2).unwrap_or("");
+        if path.contains("/docker/") || path.contains("/docker-") {
+            return Some("docker");
+        }
+        if path.contains("libpod") {
+            return Some("podman");
+        }
+        if path.contains("kubepods") || path.contains("/cri-containerd-") {
+            return Some("container-other");
+        }
+        if path.starts_with("/lxc/") || path.contains("/lxc.payload") {
+            return Some("lxc");
+        }
+    }
+    None
+}
+
+/// Recognise a hypervisor from the SMBIOS system product name and manufacturer.
+fn vm_from_dmi(product_name: Option<&str>, manufacturer: Option<&str>) -> Option<&'static str> {
+    let product = product_name.unwrap_or("").to_lowercase();
+    let vendor = manufacturer.unwrap_or("").to_lowercase();
+
+    if vendor == "qemu" || product.contains("kvm") || product.starts_with("standard pc") {
+        Some("kvm")
+    } else if product.starts_with("vmware") || vendor.starts_with("vmware") {
+        Some("vmware")
+    } else if product == "virtualbox" || vendor == "innotek gmbh" {
+        Some("oracle")
+    } else if vendor == "xen" || product.contains("hvm domu") {
+        Some("xen")
+    } else if vendor == "microsoft corporation" && product == "virtual machine" {
+        Some("microsoft")
+    } else if product.contains("google compute engine") {
+        Some("google")
+    } else if vendor == "amazon ec2" && !product.contains("metal") {
+        Some("amazon")
+    } else if product.starts_with("bhyve") {
+        Some("bhyve")
+    } else if vendor.starts_with("parallels") {
+        Some("parallels")
+    } else {
+        None
+    }
+}
+
+/// The NodeInfo fields read from SMBIOS.
+#[derive(Default)]
+struct DmiInfo {
+    product_name: Option<String>,
+    manufacturer: Option<String>,
+    serial_number: Option<String>,
+    chassis_manufacturer: Option<String>,
+    chassis_serial_number: Option<String>,
+    motherboard: Option<MotherboardInfo>,
+    bios: Option<BiosInfo>,
+}
+
+fn collect_dmi_info(errors: &mut Vec<String>) -> DmiInfo {
+    let smbios_data = match load_smbios() {
+        Ok(data) => data,
+        Err(e) => {
+            errors.push(e);
+            return DmiInfo::default();
+        }
     };
 
     let mut system_info = (None, None, None, None);
@@ -162,7 +301,15 @@ fn collect_dmi_info() -> (Option<String>, Option<String>, Option<String>, Option
     }
 
     // Return system info with chassis manufacturer and serial number
-    (system_info.0, system_info.1, system_info.2, chassis_manufacturer, chassis_serial_number, motherboard_info, bios_info)
+    DmiInfo {
+        product_name: system_info.0,
+        manufacturer: system_info.1,
+        serial_number: system_info.2,
+        chassis_manufacturer,
+        chassis_serial_number,
+        motherboard: motherboard_info,
+        bios: bios_info,
+    }
 }
 
 fn collect_bmc_from_dmi() -> BmcInfo {
@@ -226,20 +373,14 @@ fn detect_network_management() -> Option<BmcInfo> {
     // Check for common management ports (this is speculative)
     let mgmt_ports = [623, 443, 80]; // IPMI, HTTPS, HTTP
     for port in &mgmt_ports {
-        if let Ok(output) = Command::new("netstat")
-            .args(&["-ln"])
-            .output()
-        {
-            if output.status.success() {
-                let netstat_output = String::from_utf8_lossy(&output.stdout);
-                if netstat_output.contains(&format!(":{}", port)) && *port == 623 {
-                    return Some(BmcInfo {
-                        ip_address: None,
-                        mac_address: None,
-                        firmware_version: None,
-                        release_date: None,
-                    });
-                }
+        if let Ok(output) = run_capture(Command::new("netstat").arg("-ln")) {
+            if output.success && output.stdout.contains(&format!(":{}", port)) && *port == 623 {
+                return Some(BmcInfo {
+                    ip_address: None,
+                    mac_address: None,
+                    firmware_version: None,
+                    release_date: None,
+                });
             }
         }
     }
@@ -273,44 +414,38 @@ fn detect_smbios_bmc() -> Option<BmcInfo> {
 
 fn collect_ipmi_bmc() -> Option<BmcInfo> {
     // Check if ipmitool exists first
-    if Command::new("which").arg("ipmitool").output().is_err() {
+    if !tool_on_path("ipmitool") {
         return None;
     }
     
-    // Try ipmitool mc info
-    if let Ok(output) = Command::new("ipmitool")
-        .args(&["mc", "info"])
-        .output()
-    {
-        if output.status.success() {
-            let mut firmware_version = None;
-            let mut release_date = None;
-            
-            let ipmi_output = String::from_utf8_lossy(&output.stdout);
-            for line in ipmi_output.lines() {
-                if line.contains("Firmware Revision") {
-                    if let Some(version) = line.split(':').nth(1) {
-                        firmware_version = Some(version.trim().to_string());
-                    }
+    // Try ipmitool mc info; the BMC often refuses the first connection
+    if let Some(output) = ipmitool_retried(&["mc", "info"]) {
+        let mut firmware_version = None;
+        let mut release_date = None;
+        
+        for line in output.lines() {
+            if line.contains("Firmware Revision") {
+                if let Some(version) = line.split(':').nth(1) {
+                    firmware_version = Some(version.trim().to_string());
                 }
-                // Look for firmware build date or similar
-                if line.contains("Build Time") || line.contains("Build Date") || line.contains("Firmware Build") {
-                    if let Some(date) = line.split(':').nth(1) {
-                        release_date = Some(date.trim().to_string());
-                    }
+            }
+            // Look for firmware build date or similar
+            if line.contains("Build Time") || line.contains("Build Date") || line.contains("Firmware Build") {
+                if let Some(date) = line.split(':').nth(1) {
+                    release_date = Some(date.trim().to_string());
                 }
             }
+        }
 
-            // Try to get network info
-            let (ip_address, mac_address) = get_ipmi_network_info();
+        // Try to get network info
+        let (ip_address, mac_address) = get_ipmi_network_info();
 
-            return Some(BmcInfo {
-                ip_address,
-                mac_address,
-                firmware_version,
-                release_date,
-            });
-        }
+        return Some(BmcInfo {
+            ip_address,
+            mac_address,
+            firmware_version,
+            release_date,
+        });
     }
 
     None
@@ -321,33 +456,26 @@ fn get_ipmi_network_info() -> (Option<String>, Option<String>) {
     let mut mac_address = None;
 
     // Try to get LAN configuration from ipmitool
-    if let Ok(output) = Command::new("ipmitool")
-        .args(&["lan", "print", "1"])
-        .output()
-    {
-        if output.status.success() {
-            let lan_output = String::from_utf8_lossy(&output.stdout);
-            
-            for line in lan_output.lines() {
-                if line.contains("IP Address") && !line.contains("Source") {
-                    if let Some(ip) = line.split(':').nth(1) {
-                        let ip_str = ip.trim();
-                        if ip_str != "0.0.0.0" && !ip_str.is_empty() {
-                            ip_address = Some(ip_str.to_string());
-                        }
+    if let Some(output) = ipmitool_retried(&["lan", "print", "1"]) {
+        for line in output.lines() {
+            if line.contains("IP Address") && !line.contains("Source") {
+                if let Some(ip) = line.split(':').nth(1) {
+                    let ip_str = ip.trim();
+                    if ip_str != "0.0.0.0" && !ip_str.is_empty() {
+                        ip_address = Some(ip_str.to_string());
                     }
-                } else if line.contains("MAC Address") {
-                    if let Some(mac) = line.split(':').nth(1) {
-                        // MAC address format in ipmitool output: "aa:bb:cc:dd:ee:ff"
-                        // We need to handle the rest of the line after the first colon
-                        let mac_str = line.split_once(':')
-                            .and_then(|(_, rest)| rest.split_once(':'))
-                            .map(|(first_octet, rest)| format!("{}:{}", first_octet.trim(), rest))
-                            .unwrap_or_else(|| mac.trim().to_string());
-                        
-                        if !mac_str.is_empty() && mac_str != "00:00:00:00:00:00" {
-                            mac_address = Some(mac_str);
-                        }
+                }
+            } else if line.contains("MAC Address") {
+                if let Some(mac) = line.split(':').nth(1) {
+                    // MAC address format in ipmitool output: "aa:bb:cc:dd:ee:ff"
+                    // We need to handle the rest of the line after the first colon
+                    let mac_str = line.split_once(':')
+                        .and_then(|(_, rest)| rest.split_once(':'))
+                        .map(|(first_octet, rest)| format!("{}:{}", first_octet.trim(), rest))
+                        .unwrap_or_else(|| mac.trim().to_string());
+                    
+                    if !mac_str.is_empty() && mac_str != "00:00:00:00:00:00" {
+                        mac_address = Some(mac_str);
                     }
                 }
             }
@@ -371,16 +499,25 @@ fn collect_redfish_bmc() -> Option<BmcInfo> {
         "/redfish/v1/Managers",
     ];
 
+    if !tool_on_path("curl") {
+        return None;
+    }
+
     // Try curl to localhost with common Redfish paths
     for indicator in &redfish_indicators {
         let url = format!("https://localhost{}", indicator);
         
-        if let Ok(output) = Command::new("curl")
-            .args(&["-k", "-s", "--connect-timeout", "2", &url])
-            .output()
-        {
-            if output.status.success() {
-                let response = String::from_utf8_lossy(&output.stdout);
+        // Only a connect timeout (curl exit 28) is worth retrying; a refused
+        // connection means there is no local Redfish service. -k because a
+        // BMC's self-signed certificate rarely matches "localhost"; nothing
+        // is sent, the probe only looks for a Redfish-shaped response.
+        if let Some(output) = retry_tool(|| {
+            run_capture(Command::new("curl").args(["-k", "-s", "--connect-timeout", "2", &url]))
+                .ok()
+                .filter(|c| c.code != Some(28))
+        }) {
+            if output.success {
+                let response = &output.stdout;
                 if response.contains("@odata") || response.contains("redfish") {
                     return Some(BmcInfo {
                         ip_address: Some("localhost".to_string()),
@@ -394,4 +531,35 @@ fn collect_redfish_bmc() -> Option<BmcInfo> {
     }
 
     None
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_container_from_cgroup() {
+        assert_eq!(container_from_cgroup("12:pids:/docker/3f1c2a9b\n0::/docker/3f1c2a9b\n"), Some("docker"));
+        assert_eq!(container_from_cgroup("0::/system.slice/docker-3f1c2a9b.scope\n"), Some("docker"));
+        assert_eq!(container_from_cgroup("0::/machine.slice/libpod-3f1c2a9b.scope\n"), Some("podman"));
+        assert_eq!(container_from_cgroup("11:cpu:/kubepods/burstable/pod1234/3f1c2a9b\n"), Some("container-other"));
+        assert_eq!(container_from_cgroup("0::/lxc.payload.web01\n"), Some("lxc"));
+        // cgroup v2 namespaces hide the path, and the host's PID 1 sits at the root
+        assert_eq!(container_from_cgroup("0::/\n"), None);
+        assert_eq!(container_from_cgroup("0::/init.scope\n"), None);
+    }
+
+    #[test]
+    fn test_vm_from_dmi() {
+        assert_eq!(vm_from_dmi(Some("Standard PC (Q35 + ICH9, 2009)"), Some("QEMU")), Some("kvm"));
+        assert_eq!(vm_from_dmi(Some("KVM"), Some("Red Hat")), Some("kvm"));
+        assert_eq!(vm_from_dmi(Some("VMware Virtual Platform"), Some("VMware, Inc.")), Some("vmware"));
+        assert_eq!(vm_from_dmi(Some("VirtualBox"), Some("innotek GmbH")), Some("oracle"));
+        assert_eq!(vm_from_dmi(Some("HVM domU"), Some("Xen")), Some("xen"));
+        assert_eq!(vm_from_dmi(Some("Virtual Machine"), Some("Microsoft Corporation")), Some("microsoft"));
+        assert_eq!(vm_from_dmi(Some("c5.large"), Some("Amazon EC2")), Some("amazon"));
+        assert_eq!(vm_from_dmi(Some("c5.metal"), Some("Amazon EC2")), None);
+        assert_eq!(vm_from_dmi(Some("PowerEdge R750"), Some("Dell Inc.")), None);
+        assert_eq!(vm_from_dmi(None, None), None);
+    }
+}
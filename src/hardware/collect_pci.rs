@@ -0,0 +1,67 @@
+use std::fs;
+use std::path::Path;
+
+use crate::hardware::{lookup_pci_class, lookup_pci_ids, parse_pci_id, read_trim};
+use crate::hardware::types::PciDeviceInfo;
+
+/// Enumerate every device under /sys/bus/pci/devices.
+pub fn collect_pci_devices() -> Vec<PciDeviceInfo> {
+    let entries = match fs::read_dir("/sys/bus/pci/devices") {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut devices: Vec<PciDeviceInfo> = entries
+        .flatten()
+        .map(|entry| create_pci_device_info(&entry.path()))
+        .collect();
+    devices.sort_by(|a, b| a.address.cmp(&b.address));
+    devices
+}
+
+fn create_pci_device_info(device_path: &Path) -> PciDeviceInfo {
+    let address = device_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+        .to_string();
+
+    let vendor_id = read_trim(device_path.join("vendor")).as_deref().and_then(parse_pci_id);
+    let device_id = read_trim(device_path.join("device")).as_deref().and_then(parse_pci_id);
+    let (vendor, device) = match vendor_id.zip(device_id).and_then(|(v, d)| lookup_pci_ids(v, d)) {
+        Some((v, d)) => (Some(v), Some(d)),
+        None => (None, None),
+    };
+
+    // class is 0xCCSSPP (base class, subclass, programming interface)
+    let class_id = read_trim(device_path.join("class"));
+    let class = class_id
+        .as_deref()
+        .and_then(|c| u32::from_str_radix(c.strip_prefix("0x").unwrap_or(c), 16).ok())
+        .and_then(lookup_pci_class);
+
+    let driver = fs::read_link(device_path.join("driver"))
+        .ok()
+        .and_then(|link| link.file_name().and_then(|n| n.to_str()).map(|s| s.to_string()));
+
+    PciDeviceInfo {
+        address,
+        vendor_id: vendor_id.map(|v| format!("{:04x}", v)),
+        device_id: device_id.map(|d| format!("{:04x}", d)),
+        vendor,
+        device,
+        class_id,
+        class,
+        driver,
+        link_speed: read_link_attr(device_path, "current_link_speed"),
+        link_width: read_link_attr(device_path, "current_link_width").and_then(|w| w.parse().ok()),
+        max_link_speed: read_link_attr(device_path, "max_link_speed"),
+        max_link_width: read_link_attr(device_path, "max_link_width").and_then(|w| w.parse().ok()),
+    }
+}
+
+/// Link attributes only exist on PCIe devices; conventional PCI and some
+/// bridges report "Unknown" or 0.
+fn read_link_attr(device_path: &Path, attr: &str) -> Option<String> {
+    read_trim(device_path.join(attr)).filter(|v| !v.starts_with("Unknown") && v != "0")
+}
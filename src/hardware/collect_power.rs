@@ -1,14 +1,27 @@
 use std::process::Command;
 use std::fs;
+use crate::hardware::{ipmi_host, ipmitool, ipmitool_retried, tool_on_path, Collected};
+use crate::util::run_capture;
 use crate::hardware::types::PowerSupplyInfo;
 
 pub fn collect_power_supplies() -> Vec<PowerSupplyInfo> {
+    collect_power_supplies_checked().data
+}
+
+/// Like [`collect_power_supplies`], but also reports why the result may be incomplete.
+pub(crate) fn collect_power_supplies_checked() -> Collected<Vec<PowerSupplyInfo>> {
     let mut power_supplies = Vec::new();
+    let mut errors = Vec::new();
+    
+    // A remote BMC is only reachable over IPMI; the other sources read this machine
+    if ipmi_host().is_some() {
+        return Collected { data: collect_power_supplies_ipmi().unwrap_or_default(), errors };
+    }
     
     // Try multiple methods to detect power supplies
     
     // 1. Try dmidecode for power supply information (requires root)
-    if let Some(mut psu_vec) = collect_power_supplies_dmidecode() {
+    if let Some(mut psu_vec) = collect_power_supplies_dmidecode(&mut errors) {
         power_supplies.append(&mut psu_vec);
     }
     
@@ -32,21 +45,74 @@ pub fn collect_power_supplies() -> Vec<PowerSupplyInfo> {
         power_supplies.append(&mut sysfs_vec);
     }
     
-    power_supplies
+    Collected { data: power_supplies, errors }
+}
+
+/// Total node power draw in watts. The BMC's DCMI reading covers the whole
+/// node, so it wins; otherwise sum PSU output power (volts x amps) from the
+/// supplies that report both.
+pub fn collect_total_power(power_supplies: &[PowerSupplyInfo]) -> Option<u32> {
+    dcmi_power_reading().or_else(|| summed_psu_power(power_supplies))
+}
+
+fn dcmi_power_reading() -> Option<u32> {
+    if !tool_on_path("ipmitool") {
+        return None;
+    }
+    // No retries: many BMCs simply lack DCMI, and retrying that on every
+    // inventory run only adds delay before falling back to the PSU sum
+    let output = ipmitool(&["dcmi", "power", "reading"])?;
+    parse_dcmi_power_reading(&output)
+}
+
+/// Parse `ipmitool dcmi power reading`, e.g.
+/// "Instantaneous power reading:   220 Watts". BMCs that have power
+/// monitoring switched off report "Power reading state is: deactivated"
+/// with a meaningless zero reading.
+fn parse_dcmi_power_reading(text: &str) -> Option<u32> {
+    let mut watts = None;
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key.trim() {
+            "Instantaneous power reading" => {
+                watts = value.split_whitespace().next().and_then(|w| w.parse().ok());
+            }
+            "Power reading state is" if value.trim() != "activated" => return None,
+            _ => {}
+        }
+    }
+    watts
+}
+
+fn summed_psu_power(power_supplies: &[PowerSupplyInfo]) -> Option<u32> {
+    let readings: Vec<f32> = power_supplies
+        .iter()
+        .filter_map(|psu| Some(psu.output_voltage? * psu.output_current?))
+        .collect();
+    if readings.is_empty() {
+        return None;
+    }
+    Some(readings.iter().sum::<f32>().round() as u32)
 }
 
 /// Collect power supply information using dmidecode
-fn collect_power_supplies_dmidecode() -> Option<Vec<PowerSupplyInfo>> {
-    let output = Command::new("dmidecode")
-        .args(&["-t", "power", "-t", "powersupply"])
-        .output()
-        .ok()?;
+fn collect_power_supplies_dmidecode(errors: &mut Vec<String>) -> Option<Vec<PowerSupplyInfo>> {
+    let output = match run_capture(Command::new("dmidecode").args(["-t", "power", "-t", "powersupply"])) {
+        Ok(output) => output,
+        Err(e) => {
+            errors.push(format!("cannot run dmidecode: {}", e));
+            return None;
+        }
+    };
     
-    if !output.status.success() {
+    if !output.success {
+        errors.push(format!("dmidecode failed: {}", output.stderr.trim()));
         return None;
     }
     
-    let text = String::from_utf8_lossy(&output.stdout);
+    let text = output.stdout;
     let mut power_supplies = Vec::new();
     let mut current_psu = PowerSupplyInfo {
         name: None,
@@ -132,16 +198,14 @@ fn collect_power_supplies_dmidecode() -> Option<Vec<PowerSupplyInfo>> {
 
 /// Collect power supply information using IPMI
 fn collect_power_supplies_ipmi() -> Option<Vec<PowerSupplyInfo>> {
-    let output = Command::new("ipmitool")
-        .args(&["sdr", "list", "full"])
-        .output()
-        .ok()?;
-    
-    if !output.status.success() {
+    if !tool_on_path("ipmitool") {
         return None;
     }
     
-    let text = String::from_utf8_lossy(&output.stdout);
+    // The BMC often refuses the first connection, so give it a few tries
+    let output = ipmitool_retried(&["sdr", "list", "full"])?;
+    
+    let text = output;
     let mut power_supplies = Vec::new();
     
     for line in text.lines() {
@@ -195,16 +259,9 @@ fn collect_power_supplies_ipmi() -> Option<Vec<PowerSupplyInfo>> {
 /// Get detailed IPMI information for a specific PSU
 fn get_ipmi_psu_details(psu_name: &str) -> Option<PowerSupplyInfo> {
     // Try to get sensor readings for this PSU
-    let output = Command::new("ipmitool")
-        .args(&["sdr", "get", psu_name])
-        .output()
-        .ok()?;
-    
-    if !output.status.success() {
-        return None;
-    }
+    let output = ipmitool_retried(&["sdr", "get", psu_name])?;
     
-    let text = String::from_utf8_lossy(&output.stdout);
+    let text = output;
     let mut temperature_c = None;
     let mut voltage = None;
     
@@ -254,16 +311,13 @@ fn get_ipmi_psu_details(psu_name: &str) -> Option<PowerSupplyInfo> {
 
 /// Collect power supply information using lshw
 fn collect_power_supplies_lshw() -> Option<Vec<PowerSupplyInfo>> {
-    let output = Command::new("lshw")
-        .args(&["-class", "power"])
-        .output()
-        .ok()?;
+    let output = run_capture(Command::new("lshw").args(["-class", "power"])).ok()?;
     
-    if !output.status.success() {
+    if !output.success {
         return None;
     }
     
-    let text = String::from_utf8_lossy(&output.stdout);
+    let text = output.stdout;
     
     // Basic parsing - lshw doesn't usually show much PSU info
     if text.contains("power") {
@@ -306,16 +360,13 @@ fn collect_ups_information() -> Option<Vec<PowerSupplyInfo>> {
 
 /// Collect APC UPS information via apcupsd
 fn collect_apcupsd_info() -> Option<PowerSupplyInfo> {
-    let output = Command::new("apcaccess")
-        .args(&["status"])
-        .output()
-        .ok()?;
+    let output = run_capture(Command::new("apcaccess").arg("status")).ok()?;
     
-    if !output.status.success() {
+    if !output.success {
         return None;
     }
     
-    let text = String::from_utf8_lossy(&output.stdout);
+    let text = output.stdout;
     let mut ups = PowerSupplyInfo {
         name: Some("UPS".to_string()),
         manufacturer: Some("APC".to_string()),
@@ -373,16 +424,13 @@ fn collect_apcupsd_info() -> Option<PowerSupplyInfo> {
 
 /// Collect NUT (Network UPS Tools) information
 fn collect_nut_info() -> Option<PowerSupplyInfo> {
-    let output = Command::new("upsc")
-        .args(&["ups"])
-        .output()
-        .ok()?;
+    let output = run_capture(Command::new("upsc").arg("ups")).ok()?;
     
-    if !output.status.success() {
+    if !output.success {
         return None;
     }
     
-    let text = String::from_utf8_lossy(&output.stdout);
+    let text = output.stdout;
     let mut ups = PowerSupplyInfo {
         name: Some("UPS".to_string()),
         manufacturer: None,
@@ -492,4 +540,54 @@ fn collect_power_supplies_sysfs() -> Option<Vec<PowerSupplyInfo>> {
     } else {
         Some(power_supplies)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dcmi_power_reading() {
+        let text = "
+    Instantaneous power reading:                   412 Watts
+    Minimum during sampling period:                 96 Watts
+    Maximum during sampling period:                845 Watts
+    Average power reading over sample period:      398 Watts
+    IPMI timestamp:                           Thu Oct 16 18:00:00 2025
+    Sampling period:                          00000001 Seconds.
+    Power reading state is:                   activated
+";
+        assert_eq!(parse_dcmi_power_reading(text), Some(412));
+    }
+
+    #[test]
+    fn test_parse_dcmi_power_reading_deactivated() {
+        let text = "
+    Instantaneous power reading:                     0 Watts
+    Power reading state is:                   deactivated
+";
+        assert_eq!(parse_dcmi_power_reading(text), None);
+    }
+
+    #[test]
+    fn test_summed_psu_power_uses_complete_readings() {
+        let psu = |volts: Option<f32>, amps: Option<f32>| PowerSupplyInfo {
+            name: None,
+            manufacturer: None,
+            model: None,
+            serial_number: None,
+            part_number: None,
+            max_power_watts: None,
+            efficiency_rating: None,
+            status: None,
+            input_voltage: None,
+            input_current: None,
+            output_voltage: volts,
+            output_current: amps,
+            temperature_c: None,
+            fan_speed_rpm: None,
+        };
+        assert_eq!(summed_psu_power(&[psu(Some(12.0), Some(20.5)), psu(Some(12.1), Some(19.0)), psu(Some(12.0), None)]), Some(476));
+        assert_eq!(summed_psu_power(&[psu(None, None)]), None);
+    }
+}
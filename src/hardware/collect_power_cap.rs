@@ -0,0 +1,107 @@
+use crate::hardware::ipmitool_command;
+use crate::hardware::types::PowerCapInfo;
+use crate::util::run_capture;
+
+/// Read the BMC's DCMI power limit via `ipmitool dcmi power get_limit`.
+pub fn collect_power_cap() -> Result<PowerCapInfo, Box<dyn std::error::Error>> {
+    let output = run_dcmi(&["power", "get_limit"])?;
+    Ok(parse_power_limit(&output))
+}
+
+/// Set the DCMI power limit to `watts` and activate it. The BMC keeps its
+/// existing exception action (what happens when the cap can't be held).
+pub fn set_power_cap(watts: u32) -> Result<(), Box<dyn std::error::Error>> {
+    run_dcmi(&["power", "set_limit", "limit", &watts.to_string()])?;
+    run_dcmi(&["power", "activate"])?;
+    Ok(())
+}
+
+fn run_dcmi(args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    let output = run_capture(ipmitool_command().arg("dcmi").args(args))
+        .map_err(|e| format!("Failed to run ipmitool (is it installed?): {}", e))?;
+
+    if !output.success {
+        let stderr = &output.stderr;
+        if stderr.contains("Could not open device") {
+            return Err("Cannot access the IPMI device; run as root and ensure the ipmi_devintf/ipmi_si modules are loaded".into());
+        }
+        // BMCs without DCMI power management reject the command outright
+        if stderr.contains("Invalid command") || stderr.contains("not supported") || stderr.contains("DCMI request failed") {
+            return Err(format!("This BMC does not support DCMI power management: {}", stderr.trim()).into());
+        }
+        return Err(format!("ipmitool dcmi {} failed: {}", args.join(" "), stderr.trim()).into());
+    }
+
+    Ok(output.stdout)
+}
+
+/// Parse `ipmitool dcmi power get_limit`:
+///
+/// ```text
+///     Current Limit State: Power Limit Active
+///     Exception actions:   Hard Power Off & Log Event to SEL
+///     Power Limit:         1200   Watts
+///     Correction time:     1000 milliseconds
+///     Sampling period:     1 seconds
+/// ```
+fn parse_power_limit(output: &str) -> PowerCapInfo {
+    let mut cap = PowerCapInfo::default();
+    let number = |value: &str| value.split_whitespace().next().and_then(|n| n.parse().ok());
+
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "Current Limit State" => {
+                cap.active = value == "Power Limit Active";
+                cap.limit_state = Some(value.to_string());
+            }
+            "Exception actions" => cap.exception_action = Some(value.to_string()),
+            "Power Limit" => cap.limit_watts = number(value),
+            "Correction time" => cap.correction_time_ms = number(value),
+            "Sampling period" => cap.sampling_period_secs = number(value),
+            _ => {}
+        }
+    }
+
+    cap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_power_limit_active() {
+        let cap = parse_power_limit(
+            "
+    Current Limit State: Power Limit Active
+    Exception actions:   Hard Power Off & Log Event to SEL
+    Power Limit:         1200   Watts
+    Correction time:     1000 milliseconds
+    Sampling period:     1 seconds
+",
+        );
+        assert!(cap.active);
+        assert_eq!(cap.limit_watts, Some(1200));
+        assert_eq!(cap.exception_action.as_deref(), Some("Hard Power Off & Log Event to SEL"));
+        assert_eq!(cap.correction_time_ms, Some(1000));
+        assert_eq!(cap.sampling_period_secs, Some(1));
+    }
+
+    #[test]
+    fn test_parse_power_limit_inactive() {
+        let cap = parse_power_limit(
+            "
+    Current Limit State: No Active Power Limit
+    Exception actions:   Log Event to SEL
+    Power Limit:         0   Watts
+",
+        );
+        assert!(!cap.active);
+        assert_eq!(cap.limit_state.as_deref(), Some("No Active Power Limit"));
+        assert_eq!(cap.limit_watts, Some(0));
+    }
+}
@@ -0,0 +1,99 @@
+use crate::hardware::ipmitool_command;
+use crate::hardware::types::SelEntry;
+use crate::util::run_capture;
+
+/// Read the BMC System Event Log via `ipmitool sel elist`.
+///
+/// `elist` resolves sensor names from the SDR; older BMCs that choke on it
+/// fall back to the plain `sel list`.
+pub fn collect_sel() -> Result<Vec<SelEntry>, Box<dyn std::error::Error>> {
+    let output = match run_ipmitool(&["sel", "elist"]) {
+        Ok(output) => output,
+        Err(_) => run_ipmitool(&["sel", "list"])?,
+    };
+
+    Ok(parse_sel_list(&output))
+}
+
+/// Clear the BMC System Event Log.
+pub fn clear_sel() -> Result<(), Box<dyn std::error::Error>> {
+    run_ipmitool(&["sel", "clear"])?;
+    Ok(())
+}
+
+fn run_ipmitool(args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    let output = run_capture(ipmitool_command().args(args))
+        .map_err(|e| format!("Failed to run ipmitool (is it installed?): {}", e))?;
+    let (stdout, stderr) = (output.stdout, output.stderr);
+
+    if !output.success {
+        // An empty SEL is not an error
+        if stderr.contains("no entries") || stdout.contains("no entries") {
+            return Ok(String::new());
+        }
+        if stderr.contains("Could not open device") {
+            return Err("Cannot access the IPMI device; run as root and ensure the ipmi_devintf/ipmi_si modules are loaded".into());
+        }
+        return Err(format!("ipmitool {} failed: {}", args.join(" "), stderr.trim()).into());
+    }
+
+    Ok(stdout)
+}
+
+/// Parse `ipmitool sel list`/`elist` output:
+/// `   1 | 10/14/2025 | 10:22:41 | Memory #0x53 | Correctable ECC | Asserted`
+fn parse_sel_list(output: &str) -> Vec<SelEntry> {
+    let mut entries = Vec::new();
+
+    for line in output.lines() {
+        let parts: Vec<&str> = line.split('|').map(|p| p.trim()).collect();
+        if parts.len() < 5 {
+            continue;
+        }
+
+        let non_empty = |s: &str| if s.is_empty() { None } else { Some(s.to_string()) };
+
+        // Entries logged before the BMC clock was set show "Pre-Init"
+        let timestamp = match (parts[1], parts[2]) {
+            ("", "") => None,
+            (date, time) => Some(format!("{} {}", date, time).trim().to_string()),
+        };
+
+        entries.push(SelEntry {
+            id: parts[0].to_string(),
+            timestamp,
+            sensor: non_empty(parts[3]),
+            event: non_empty(parts[4]),
+            direction: parts.get(5).and_then(|d| non_empty(d)),
+        });
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sel_elist() {
+        let entries = parse_sel_list(include_str!("testdata/ipmitool_sel_elist.txt"));
+
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[1].id, "2");
+        assert_eq!(entries[1].timestamp.as_deref(), Some("10/14/2025 11:05:09"));
+        assert_eq!(entries[1].sensor.as_deref(), Some("Memory CPU1_DIMM_A1"));
+        assert_eq!(entries[1].event.as_deref(), Some("Correctable ECC"));
+        assert_eq!(entries[1].direction.as_deref(), Some("Asserted"));
+        assert_eq!(entries[3].direction.as_deref(), Some("Deasserted"));
+        // No timestamp and no direction column
+        assert_eq!(entries[4].timestamp, None);
+        assert_eq!(entries[4].direction, None);
+    }
+
+    #[test]
+    fn test_parse_sel_list_skips_noise() {
+        assert!(parse_sel_list("").is_empty());
+        assert!(parse_sel_list("SEL has no entries\n").is_empty());
+    }
+}
@@ -1,17 +1,38 @@
 use std::fs;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
 
+use serde_json::Value;
+
+use crate::hardware::{tool_on_path, Collected};
 use crate::hardware::types::{DiskInfo, SmartInfo};
+use crate::util::run_capture;
+
+/// Upper bound on disks probed concurrently; each probe spawns smartctl/hdparm.
+const DISK_WORKERS: usize = 8;
 
 /// Entry point: collect all disks on this machine.
 pub fn collect_disks() -> Vec<DiskInfo> {
-    let mut disks = Vec::new();
+    collect_disks_checked().data
+}
+
+/// Like [`collect_disks`], but also reports why the result may be incomplete.
+pub(crate) fn collect_disks_checked() -> Collected<Vec<DiskInfo>> {
+    let mut candidates: Vec<(String, PathBuf, String)> = Vec::new();
     let sys_block = Path::new("/sys/block");
 
     let entries = match fs::read_dir(sys_block) {
         Ok(e) => e,
-        Err(_) => return disks,
+        Err(e) => {
+            return Collected {
+                data: Vec::new(),
+                errors: vec![format!("cannot read /sys/block: {}", e)],
+            }
+        }
     };
 
     for entry in entries.flatten() {
@@ -29,6 +50,12 @@ pub fn collect_disks() -> Vec<DiskInfo> {
             continue;
         }
 
+        // nvmeXcYnZ is one controller's path to a multipath namespace; the
+        // namespace itself is counted once, through its nvmeXnZ head
+        if parse_nvme_name(&name).is_some_and(|n| n.path_controller.is_some()) {
+            continue;
+        }
+
         let sys_path = entry.path();
         let dev_path = format!("/dev/{}", name);
 
@@ -37,11 +64,46 @@ pub fn collect_disks() -> Vec<DiskInfo> {
             continue;
         }
 
-        let disk = collect_single_disk(&name, &sys_path, &dev_path);
-        disks.push(disk);
+        candidates.push((name, sys_path, dev_path));
+    }
+
+    let mut errors = Vec::new();
+    if !candidates.is_empty() && !tool_on_path("smartctl") {
+        errors.push("smartctl not found on PATH; SMART data is missing".to_string());
     }
 
-    disks
+    let mut disks = collect_disks_parallel(&candidates);
+    disks.sort_by(|a, b| a.name.cmp(&b.name));
+    Collected { data: disks, errors }
+}
+
+/// Probe disks on a bounded pool of worker threads. A disk whose probe panics
+/// is skipped rather than taking the whole scan down with it.
+fn collect_disks_parallel(candidates: &[(String, PathBuf, String)]) -> Vec<DiskInfo> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(candidates.len()));
+    let workers = DISK_WORKERS.min(candidates.len());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let (name, sys_path, dev_path) = match candidates.get(index) {
+                    Some(c) => c,
+                    None => break,
+                };
+
+                let disk = panic::catch_unwind(AssertUnwindSafe(|| {
+                    collect_single_disk(name, sys_path, dev_path)
+                }));
+                if let Ok(disk) = disk {
+                    results.lock().unwrap_or_else(|e| e.into_inner()).push(disk);
+                }
+            });
+        }
+    });
+
+    results.into_inner().unwrap_or_else(|e| e.into_inner())
 }
 
 /// Collect detailed info for a single disk.
@@ -63,18 +125,40 @@ fn collect_single_disk(name: &str, sys_path: &Path, dev_path: &str) -> DiskInfo
     // Rotational: 1 = HDD, 0 = SSD/NVMe
     let rotational = read_to_u64(sys_path.join("queue/rotational")).map(|v| v == 1);
 
+    // Discard (TRIM/UNMAP): a zero max size or granularity means the device doesn't support it
+    let discard_max_bytes = read_to_u64(sys_path.join("queue/discard_max_bytes"));
+    let discard_granularity = read_to_u64(sys_path.join("queue/discard_granularity"));
+    let discard_supported = match (discard_max_bytes, discard_granularity) {
+        (None, None) => None,
+        (max, granularity) => Some(max.unwrap_or(0) > 0 && granularity != Some(0)),
+    };
+
     // Bus type / NVMe extras / PCI address
     let mut bus_type: Option<String> = None;
     let mut firmware_version: Option<String> = None;
+    let mut controller: Option<String> = None;
+    let mut namespace_id: Option<u32> = None;
+    let mut multipath: Option<bool> = None;
 
-    if name.starts_with("nvme") {
-        // NVMe namespace: "nvme0n1" → controller "nvme0"
-        let controller = name.split('n').next().unwrap_or(name);
+    if let Some(nvme) = parse_nvme_name(name) {
         bus_type = Some("nvme".to_string());
 
-        let nvme_ctrl_path = PathBuf::from("/sys/class/nvme").join(controller);
-
-        // Firmware / serial from controller sysfs
+        // A multipath namespace belongs to the subsystem (its "device" link
+        // points there) and lists one path per controller under multipath/
+        let paths = read_dir_names(sys_path.join("multipath"));
+        let nvme_ctrl_path = if paths.is_empty() {
+            PathBuf::from("/sys/class/nvme").join(format!("nvme{}", nvme.instance))
+        } else {
+            device_path.clone()
+        };
+        controller = fs::canonicalize(&nvme_ctrl_path)
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .or_else(|| Some(format!("nvme{}", nvme.instance)));
+        namespace_id = read_to_u64(sys_path.join("nsid")).map(|id| id as u32).or(Some(nvme.namespace));
+        multipath = Some(paths.len() > 1);
+
+        // Firmware / serial from controller (or subsystem) sysfs
         firmware_version = read_to_string_trim(nvme_ctrl_path.join("firmware_rev"));
 
         if serial.is_none() {
@@ -85,18 +169,35 @@ fn collect_single_disk(name: &str, sys_path: &Path, dev_path: &str) -> DiskInfo
         bus_type = detect_bus_type(&sys_path.join("device"));
     }
 
-    // Try to get firmware version from hdparm/smartctl if not found in sysfs
+    // One smartctl call per disk feeds firmware, serial and SMART data
+    let report = smartctl_report(dev_path, bus_type.as_deref());
+
+    // Try to get firmware version from smartctl/hdparm if not found in sysfs
     if firmware_version.is_none() {
-        firmware_version = get_firmware_version(dev_path, bus_type.as_deref());
+        firmware_version = report.as_ref().and_then(|r| r.firmware_version.clone());
+    }
+    if firmware_version.is_none() && bus_type.as_deref() == Some("scsi") {
+        firmware_version = get_firmware_from_hdparm(dev_path);
     }
 
-    // Try to get serial number from hdparm/smartctl if not found in sysfs
+    // Try to get serial number from smartctl/hdparm if not found in sysfs
     if serial.is_none() {
-        serial = get_serial_number(dev_path, bus_type.as_deref());
+        serial = report.as_ref().and_then(|r| r.serial.clone());
+    }
+    if serial.is_none() && bus_type.as_deref() == Some("scsi") {
+        serial = get_serial_from_hdparm(dev_path);
     }
 
+    // Physical location: only meaningful for SCSI-attached (SATA/SAS) disks
+    let (hctl, enclosure, slot) = if bus_type.as_deref() == Some("scsi") {
+        let (enclosure, slot) = detect_enclosure_slot(&device_path);
+        (read_hctl(&device_path), enclosure, slot)
+    } else {
+        (None, None, None)
+    };
+
     // SMART / health info (optional, best effort)
-    let smart = collect_smart_info(dev_path, bus_type.as_deref());
+    let smart = collect_smart_info(dev_path, bus_type.as_deref(), report);
 
     DiskInfo {
         name: name.to_string(),
@@ -106,7 +207,15 @@ fn collect_single_disk(name: &str, sys_path: &Path, dev_path: &str) -> DiskInfo
         size_bytes,
         rotational,
         bus_type,
+        controller,
+        namespace_id,
+        multipath,
         firmware_version,
+        hctl,
+        enclosure,
+        slot,
+        discard_supported,
+        discard_max_bytes,
         smart,
     }
 }
@@ -124,80 +233,48 @@ fn read_to_u64<P: AsRef<Path>>(path: P) -> Option<u64> {
     s.parse::<u64>().ok()
 }
 
-/// Get firmware version using hdparm -I or smartctl
-fn get_firmware_version(dev_path: &str, bus_type: Option<&str>) -> Option<String> {
-    // Try smartctl first (works for most devices including NVMe)
-    if let Some(version) = get_firmware_from_smartctl(dev_path, bus_type) {
-        return Some(version);
-    }
-
-    // Try hdparm for SATA drives
-    if let Some("scsi") = bus_type {
-        if let Some(version) = get_firmware_from_hdparm(dev_path) {
-            return Some(version);
-        }
-    }
-
-    None
+fn read_dir_names<P: AsRef<Path>>(path: P) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(path)
+        .map(|entries| entries.flatten().filter_map(|e| e.file_name().into_string().ok()).collect())
+        .unwrap_or_default();
+    names.sort();
+    names
 }
 
-/// Get firmware version from smartctl
-fn get_firmware_from_smartctl(dev_path: &str, bus_type: Option<&str>) -> Option<String> {
-    let mut args = vec!["-i"];  // info flag
-    
-    if let Some("nvme") = bus_type {
-        args.extend_from_slice(&["-d", "nvme"]);
-    }
-    args.push(dev_path);
-
-    let output = Command::new("smartctl")
-        .args(&args)
-        .output()
-        .ok()?;
-
-    if !output.status.success() {
-        return None;
-    }
-
-    let text = String::from_utf8_lossy(&output.stdout);
-    for line in text.lines() {
-        let line = line.trim();
-        // Look for firmware version lines in smartctl output
-        if line.starts_with("Firmware Version:") {
-            if let Some(version) = line.split(':').nth(1) {
-                let version = version.trim().to_string();
-                if !version.is_empty() {
-                    return Some(version);
-                }
-            }
-        }
-        // Alternative patterns
-        if line.starts_with("Firmware Revision:") {
-            if let Some(version) = line.split(':').nth(1) {
-                let version = version.trim().to_string();
-                if !version.is_empty() {
-                    return Some(version);
-                }
-            }
-        }
-    }
+/// An NVMe block device name, split into its numbers.
+#[derive(Debug, PartialEq)]
+struct NvmeName {
+    instance: u32,               // controller, or subsystem when multipath is on
+    path_controller: Option<u32>, // set for nvmeXcYnZ path devices
+    namespace: u32,
+}
 
-    None
+/// Parse "nvme0n1" (namespace 1 of instance 0) and "nvme0c1n1" (the path to
+/// that namespace through controller 1). Partitions such as "nvme0n1p1" and
+/// anything else give None.
+fn parse_nvme_name(name: &str) -> Option<NvmeName> {
+    let rest = name.strip_prefix("nvme")?;
+    let (head, namespace) = rest.split_once('n')?;
+    let (instance, path_controller) = match head.split_once('c') {
+        Some((instance, ctrl)) => (instance.parse().ok()?, Some(ctrl.parse().ok()?)),
+        None => (head.parse().ok()?, None),
+    };
+    Some(NvmeName {
+        instance,
+        path_controller,
+        namespace: namespace.parse().ok()?,
+    })
 }
 
 /// Get firmware version from hdparm -I (for SATA drives)
 fn get_firmware_from_hdparm(dev_path: &str) -> Option<String> {
-    let output = Command::new("hdparm")
-        .args(["-I", dev_path])
-        .output()
-        .ok()?;
+    let output = run_capture(Command::new("hdparm").args(["-I", dev_path])).ok()?;
 
-    if !output.status.success() {
+    if !output.success {
         return None;
     }
 
-    let text = String::from_utf8_lossy(&output.stdout);
-    for line in text.lines() {
+    for line in output.stdout.lines() {
         let line = line.trim();
         // Look for firmware revision in hdparm output
         if line.contains("Firmware Revision:") || line.contains("FW Revision:") {
@@ -213,71 +290,15 @@ fn get_firmware_from_hdparm(dev_path: &str) -> Option<String> {
     None
 }
 
-/// Get serial number using hdparm -I or smartctl
-fn get_serial_number(dev_path: &str, bus_type: Option<&str>) -> Option<String> {
-    // Try smartctl first (works for most devices including NVMe)
-    if let Some(serial) = get_serial_from_smartctl(dev_path, bus_type) {
-        return Some(serial);
-    }
-
-    // Try hdparm for SATA drives
-    if let Some("scsi") = bus_type {
-        if let Some(serial) = get_serial_from_hdparm(dev_path) {
-            return Some(serial);
-        }
-    }
-
-    None
-}
-
-/// Get serial number from smartctl
-fn get_serial_from_smartctl(dev_path: &str, bus_type: Option<&str>) -> Option<String> {
-    let mut args = vec!["-i"];  // info flag
-    
-    if let Some("nvme") = bus_type {
-        args.extend_from_slice(&["-d", "nvme"]);
-    }
-    args.push(dev_path);
-
-    let output = Command::new("smartctl")
-        .args(&args)
-        .output()
-        .ok()?;
-
-    if !output.status.success() {
-        return None;
-    }
-
-    let text = String::from_utf8_lossy(&output.stdout);
-    for line in text.lines() {
-        let line = line.trim();
-        // Look for serial number lines in smartctl output
-        if line.starts_with("Serial Number:") || line.starts_with("Serial number:") {
-            if let Some(serial) = line.split(':').nth(1) {
-                let serial = serial.trim().to_string();
-                if !serial.is_empty() {
-                    return Some(serial);
-                }
-            }
-        }
-    }
-
-    None
-}
-
 /// Get serial number from hdparm -I (for SATA drives)
 fn get_serial_from_hdparm(dev_path: &str) -> Option<String> {
-    let output = Command::new("hdparm")
-        .args(["-I", dev_path])
-        .output()
-        .ok()?;
+    let output = run_capture(Command::new("hdparm").args(["-I", dev_path])).ok()?;
 
-    if !output.status.success() {
+    if !output.success {
         return None;
     }
 
-    let text = String::from_utf8_lossy(&output.stdout);
-    for line in text.lines() {
+    for line in output.stdout.lines() {
         let line = line.trim();
         // Look for serial number in hdparm output
         if line.contains("Serial Number:") {
@@ -313,17 +334,13 @@ fn detect_bus_type(device_path: &Path) -> Option<String> {
 
 /// Read udev properties for a device node, e.g. /dev/sda.
 fn read_udev_property(dev_path: &str, key: &str) -> Option<String> {
-    let output = Command::new("udevadm")
-        .args(["info", "--query=property", "--name", dev_path])
-        .output()
-        .ok()?;
+    let output = run_capture(Command::new("udevadm").args(["info", "--query=property", "--name", dev_path])).ok()?;
 
-    if !output.status.success() {
+    if !output.success {
         return None;
     }
 
-    let text = String::from_utf8_lossy(&output.stdout);
-    for line in text.lines() {
+    for line in output.stdout.lines() {
         if let Some(rest) = line.strip_prefix(key) {
             if let Some(val) = rest.strip_prefix('=') {
                 return Some(val.trim().to_string());
@@ -355,53 +372,260 @@ fn find_dev_node_for_sys_device(device_path: &Path) -> Option<String> {
 }
 
 //
-// SMART / health
+// Enclosure / slot
 //
 
-fn collect_smart_info(dev_path: &str, bus_type: Option<&str>) -> Option<SmartInfo> {
-    // Try smartctl first (works for SATA/SAS, and also NVMe with -d nvme)
-    if let Some(smart) = smartctl_health(dev_path, bus_type) {
-        return Some(smart);
+/// Read the SCSI address (host:channel:target:lun) of a disk.
+///
+/// `/sys/block/<dev>/device` links to `.../targetH:C:T/H:C:T:L`, so the
+/// basename of the resolved link is the HCTL.
+fn read_hctl(device_path: &Path) -> Option<String> {
+    let real_path = fs::canonicalize(device_path).ok()?;
+    let name = real_path.file_name()?.to_str()?;
+
+    let is_hctl = name.split(':').count() == 4
+        && name.split(':').all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+    if is_hctl {
+        Some(name.to_string())
+    } else {
+        None
     }
+}
+
+/// Find the SES enclosure and slot a disk sits in.
+///
+/// When the kernel's `ses` driver is bound, the SCSI device has an
+/// `enclosure_device:<component>` link into `/sys/class/enclosure/<encl>/<component>`.
+fn detect_enclosure_slot(device_path: &Path) -> (Option<String>, Option<u32>) {
+    let entries = match fs::read_dir(device_path) {
+        Ok(e) => e,
+        Err(_) => return (None, None),
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let component = match name.to_str().and_then(|n| n.strip_prefix("enclosure_device:")) {
+            Some(c) => c.to_string(),
+            None => continue,
+        };
 
-    // Optionally: you can also try nvme-cli if smartctl is not available.
-    if let Some(smart) = nvme_cli_smart(dev_path, bus_type) {
-        return Some(smart);
+        let component_path = match fs::canonicalize(entry.path()) {
+            Ok(p) => p,
+            Err(_) => return (None, slot_from_component_name(&component)),
+        };
+
+        let enclosure = component_path
+            .parent()
+            .and_then(|p| p.file_name())
+            .and_then(|n| n.to_str())
+            .map(|s| s.to_string());
+
+        // Newer kernels expose the slot number directly; otherwise parse "Slot 05"
+        let slot = read_to_string_trim(component_path.join("slot"))
+            .and_then(|s| s.parse::<u32>().ok())
+            .or_else(|| slot_from_component_name(&component));
+
+        return (enclosure, slot);
     }
 
-    None
+    (None, None)
+}
+
+fn slot_from_component_name(component: &str) -> Option<u32> {
+    let digits: String = component
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse::<u32>().ok()
 }
 
-/// Use smartctl to get basic health info.
+//
+// SMART / health
+//
+
+fn collect_smart_info(dev_path: &str, bus_type: Option<&str>, report: Option<SmartctlReport>) -> Option<SmartInfo> {
+    // smartctl first (works for SATA/SAS, and also NVMe with -d nvme),
+    // then nvme-cli if smartctl is not available.
+    let mut smart = match report {
+        Some(r) => SmartInfo {
+            health: r.health,
+            temperature_c: r.temperature_c,
+            wear_percent_used: r.wear_percent_used,
+            critical_warning: r.nvme.critical_warning,
+            available_spare_percent: r.nvme.available_spare_percent,
+            media_errors: r.nvme.media_errors,
+            unsafe_shutdowns: r.nvme.unsafe_shutdowns,
+        },
+        None => nvme_cli_smart(dev_path, bus_type)?,
+    };
+
+    // hddtemp as a last resort for drive temperature
+    if smart.temperature_c.is_none() {
+        smart.temperature_c = hddtemp_temperature(dev_path);
+    }
+
+    Some(smart)
+}
+
+/// Everything we read from a single smartctl invocation.
+#[derive(Debug, Default, PartialEq)]
+struct SmartctlReport {
+    health: Option<String>,
+    firmware_version: Option<String>,
+    serial: Option<String>,
+    temperature_c: Option<i32>,
+    wear_percent_used: Option<u32>,
+    nvme: NvmeHealth,
+}
+
+/// NVMe health log counters, as reported by both smartctl and nvme-cli.
+#[derive(Debug, Default, PartialEq)]
+struct NvmeHealth {
+    critical_warning: Option<u8>,
+    available_spare_percent: Option<u32>,
+    media_errors: Option<u64>,
+    unsafe_shutdowns: Option<u64>,
+}
+
+/// Read the counters from an NVMe health log object. smartctl and nvme-cli
+/// name the spare field differently, and newer nvme-cli nests the warning
+/// bitmask as `{"value": N, ...}`.
+fn parse_nvme_health(log: &Value) -> NvmeHealth {
+    let number = |keys: &[&str]| keys.iter().find_map(|k| log.get(*k).and_then(|v| v.as_u64()));
+    NvmeHealth {
+        critical_warning: number(&["critical_warning"])
+            .or_else(|| log.pointer("/critical_warning/value").and_then(|v| v.as_u64()))
+            .map(|v| v as u8),
+        available_spare_percent: number(&["available_spare", "avail_spare"]).map(|v| v as u32),
+        media_errors: number(&["media_errors"]),
+        unsafe_shutdowns: number(&["unsafe_shutdowns"]),
+    }
+}
+
+/// Run `smartctl -x --json=c` once and parse it, falling back to the plain
+/// text output for smartctl releases that predate JSON support (< 7.0).
 /// Requires smartmontools installed, might require root.
-fn smartctl_health(dev_path: &str, bus_type: Option<&str>) -> Option<SmartInfo> {
+fn smartctl_report(dev_path: &str, bus_type: Option<&str>) -> Option<SmartctlReport> {
     // For NVMe drives, smartctl needs "-d nvme"
-    let mut args: Vec<&str> = vec!["-H"]; // basic health
+    let mut args: Vec<&str> = vec!["-x", "--json=c"];
     if let Some("nvme") = bus_type {
         args.extend_from_slice(&["-d", "nvme"]);
     }
     args.push(dev_path);
 
-    let output = Command::new("smartctl").args(&args).output().ok()?;
-    if !output.status.success() {
-        return None;
+    // smartctl's exit status is a bitmask that is non-zero for many benign
+    // conditions, so parse whatever JSON it produced instead of checking it.
+    let output = run_capture(Command::new("smartctl").args(&args)).ok()?;
+    if let Ok(json) = serde_json::from_str::<Value>(&output.stdout) {
+        return parse_smartctl_json(&json);
     }
 
-    let text = String::from_utf8_lossy(&output.stdout).to_string();
-    let health = if text.contains("PASSED") {
-        Some("PASSED".to_string())
-    } else if text.contains("FAILED") {
-        Some("FAILED".to_string())
-    } else {
-        None
+    args.retain(|a| *a != "--json=c");
+    let output = run_capture(Command::new("smartctl").args(&args)).ok()?;
+    parse_smartctl_text(&output.stdout)
+}
+
+/// Parse `smartctl -x --json` output. Returns None when smartctl could not
+/// open the device at all.
+fn parse_smartctl_json(json: &Value) -> Option<SmartctlReport> {
+    json.get("device")?;
+
+    let non_empty = |ptr: &str| {
+        json.pointer(ptr)
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
     };
 
-    // If you want more info (temp, hours, wear) you can parse `smartctl -a` instead.
-    Some(SmartInfo {
+    let health = json
+        .pointer("/smart_status/passed")
+        .and_then(|v| v.as_bool())
+        .map(|passed| if passed { "PASSED" } else { "FAILED" }.to_string());
+
+    Some(SmartctlReport {
         health,
+        firmware_version: non_empty("/firmware_version"),
+        serial: non_empty("/serial_number"),
+        temperature_c: parse_smartctl_temperature(json),
+        wear_percent_used: parse_smartctl_wear(json),
+        nvme: json
+            .get("nvme_smart_health_information_log")
+            .map(parse_nvme_health)
+            .unwrap_or_default(),
+    })
+}
+
+/// Extract SSD wear (percentage of rated endurance used) from smartctl JSON.
+///
+/// Uses smartctl's normalized `endurance_used` when present, then the NVMe
+/// health log, the SCSI endurance indicator, and finally vendor ATA attributes
+/// whose normalized value counts down from 100.
+fn parse_smartctl_wear(json: &Value) -> Option<u32> {
+    for ptr in [
+        "/endurance_used/current_percent",
+        "/nvme_smart_health_information_log/percentage_used",
+        "/scsi_percentage_used_endurance_indicator",
+    ] {
+        if let Some(used) = json.pointer(ptr).and_then(|v| v.as_u64()) {
+            return Some(used as u32);
+        }
+    }
+
+    // 177 Wear_Leveling_Count (Samsung), 233 Media_Wearout_Indicator (Intel), 231 SSD_Life_Left
+    let table = json.pointer("/ata_smart_attributes/table")?.as_array()?;
+    [177, 233, 231].iter().find_map(|id| {
+        table
+            .iter()
+            .find(|a| a.get("id").and_then(|v| v.as_u64()) == Some(*id))
+            .and_then(|a| a.get("value").and_then(|v| v.as_u64()))
+            .map(|remaining| 100u32.saturating_sub(remaining as u32))
     })
 }
 
+/// Parse plain-text `smartctl -x` output from old smartmontools.
+fn parse_smartctl_text(text: &str) -> Option<SmartctlReport> {
+    let mut report = SmartctlReport::default();
+    let mut recognized = false;
+
+    for line in text.lines() {
+        let line = line.trim();
+        let (key, value) = match line.split_once(':') {
+            Some((k, v)) => (k.trim(), v.trim()),
+            None => continue,
+        };
+
+        match key {
+            "Firmware Version" | "Firmware Revision" | "Revision" if !value.is_empty() => {
+                report.firmware_version.get_or_insert_with(|| value.to_string());
+                recognized = true;
+            }
+            "Serial Number" | "Serial number" if !value.is_empty() => {
+                report.serial.get_or_insert_with(|| value.to_string());
+                recognized = true;
+            }
+            "SMART overall-health self-assessment test result" | "SMART Health Status" => {
+                // ATA reports PASSED/FAILED!, SCSI reports OK
+                report.health = Some(if value.contains("PASSED") || value == "OK" {
+                    "PASSED".to_string()
+                } else {
+                    "FAILED".to_string()
+                });
+                recognized = true;
+            }
+            "Current Drive Temperature" | "Temperature" | "Current Temperature" if report.temperature_c.is_none() => {
+                report.temperature_c = value.split_whitespace().next().and_then(|t| t.parse().ok());
+            }
+            "Percentage Used" | "Percentage used endurance indicator" => {
+                report.wear_percent_used = value.trim_end_matches('%').parse().ok();
+            }
+            _ => {}
+        }
+    }
+
+    recognized.then_some(report)
+}
+
 /// Use `nvme smart-log` as a fallback for NVMe drives.
 /// Requires nvme-cli, likely root.
 fn nvme_cli_smart(dev_path: &str, bus_type: Option<&str>) -> Option<SmartInfo> {
@@ -409,16 +633,232 @@ fn nvme_cli_smart(dev_path: &str, bus_type: Option<&str>) -> Option<SmartInfo> {
         return None;
     }
 
-    let output = Command::new("nvme")
-        .args(["smart-log", dev_path])
-        .output()
-        .ok()?;
+    let output = run_capture(Command::new("nvme").args(["smart-log", dev_path, "--output-format=json"])).ok()?;
 
-    if !output.status.success() {
+    if !output.success {
         return None;
     }
 
-    Some(SmartInfo {
-        health: None, // nvme-cli doesn't give a simple PASSED/FAILED string
-    })
+    let json: Value = serde_json::from_str(&output.stdout).ok()?;
+    Some(parse_nvme_smart_log(&json))
+}
+
+/// Parse `nvme smart-log --output-format=json`. nvme-cli reports the composite
+/// temperature in Kelvin.
+fn parse_nvme_smart_log(json: &Value) -> SmartInfo {
+    let nvme = parse_nvme_health(json);
+    SmartInfo {
+        // Same rule smartctl uses for NVMe: any critical warning bit fails the drive
+        health: nvme
+            .critical_warning
+            .map(|w| if w == 0 { "PASSED" } else { "FAILED" }.to_string()),
+        temperature_c: json
+            .get("temperature")
+            .and_then(|v| v.as_i64())
+            .map(|kelvin| (kelvin - 273) as i32),
+        wear_percent_used: ["percent_used", "percentage_used"]
+            .iter()
+            .find_map(|k| json.get(*k).and_then(|v| v.as_u64()))
+            .map(|v| v as u32),
+        critical_warning: nvme.critical_warning,
+        available_spare_percent: nvme.available_spare_percent,
+        media_errors: nvme.media_errors,
+        unsafe_shutdowns: nvme.unsafe_shutdowns,
+    }
+}
+
+/// Extract a temperature in °C from smartctl JSON output.
+///
+/// Checks the normalized `temperature.current` first, then the NVMe health log,
+/// then ATA attributes 194 (Temperature_Celsius) and 190 (Airflow_Temperature_Cel).
+fn parse_smartctl_temperature(json: &Value) -> Option<i32> {
+    if let Some(temp) = json.pointer("/temperature/current").and_then(|v| v.as_i64()) {
+        return Some(temp as i32);
+    }
+
+    if let Some(temp) = json
+        .pointer("/nvme_smart_health_information_log/temperature")
+        .and_then(|v| v.as_i64())
+    {
+        return Some(temp as i32);
+    }
+
+    let table = json.pointer("/ata_smart_attributes/table")?.as_array()?;
+    for id in [194, 190] {
+        let attr = match table.iter().find(|a| a.get("id").and_then(|v| v.as_u64()) == Some(id)) {
+            Some(a) => a,
+            None => continue,
+        };
+
+        // The raw string looks like "35 (Min/Max 20/45)"; the packed raw value
+        // carries min/max in its upper bytes, so only the low byte is the reading.
+        let from_string = attr
+            .pointer("/raw/string")
+            .and_then(|v| v.as_str())
+            .and_then(|s| s.split_whitespace().next())
+            .and_then(|s| s.parse::<i32>().ok());
+        let from_value = attr
+            .pointer("/raw/value")
+            .and_then(|v| v.as_i64())
+            .map(|v| (v & 0xFF) as i32);
+
+        if let Some(temp) = from_string.or(from_value) {
+            return Some(temp);
+        }
+    }
+
+    None
+}
+
+/// Fallback temperature reading via hddtemp (SATA only, mostly legacy systems).
+fn hddtemp_temperature(dev_path: &str) -> Option<i32> {
+    let output = run_capture(Command::new("hddtemp").args(["-n", dev_path])).ok()?;
+
+    if !output.success {
+        return None;
+    }
+
+    output.stdout.trim().parse::<i32>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nvme_name_namespace() {
+        assert_eq!(parse_nvme_name("nvme0n1"), Some(NvmeName { instance: 0, path_controller: None, namespace: 1 }));
+        assert_eq!(parse_nvme_name("nvme12n3"), Some(NvmeName { instance: 12, path_controller: None, namespace: 3 }));
+    }
+
+    #[test]
+    fn test_parse_nvme_name_multipath_path() {
+        assert_eq!(parse_nvme_name("nvme0c1n1"), Some(NvmeName { instance: 0, path_controller: Some(1), namespace: 1 }));
+        assert_eq!(parse_nvme_name("nvme2c10n4"), Some(NvmeName { instance: 2, path_controller: Some(10), namespace: 4 }));
+    }
+
+    #[test]
+    fn test_parse_nvme_name_rejects_other_devices() {
+        assert_eq!(parse_nvme_name("nvme0n1p1"), None);
+        assert_eq!(parse_nvme_name("nvme0"), None);
+        assert_eq!(parse_nvme_name("sda"), None);
+        assert_eq!(parse_nvme_name("nvme-fabrics"), None);
+    }
+
+    #[test]
+    fn test_parse_temperature_sata_attribute_194() {
+        let json: Value = serde_json::from_str(r#"{
+            "ata_smart_attributes": {
+                "table": [
+                    {"id": 9, "name": "Power_On_Hours", "raw": {"value": 12345, "string": "12345"}},
+                    {"id": 194, "name": "Temperature_Celsius", "raw": {"value": 193274314787, "string": "35 (Min/Max 20/45)"}}
+                ]
+            }
+        }"#).unwrap();
+        assert_eq!(parse_smartctl_temperature(&json), Some(35));
+    }
+
+    #[test]
+    fn test_parse_temperature_sata_attribute_190_raw_value() {
+        let json: Value = serde_json::from_str(r#"{
+            "ata_smart_attributes": {
+                "table": [
+                    {"id": 190, "name": "Airflow_Temperature_Cel", "raw": {"value": 41}}
+                ]
+            }
+        }"#).unwrap();
+        assert_eq!(parse_smartctl_temperature(&json), Some(41));
+    }
+
+    #[test]
+    fn test_parse_temperature_nvme() {
+        let json: Value = serde_json::from_str(r#"{
+            "nvme_smart_health_information_log": {
+                "critical_warning": 0,
+                "temperature": 48,
+                "available_spare": 100
+            }
+        }"#).unwrap();
+        assert_eq!(parse_smartctl_temperature(&json), Some(48));
+    }
+
+    #[test]
+    fn test_parse_smartctl_json_sata_fixture() {
+        let json: Value = serde_json::from_str(include_str!("testdata/smartctl_x_sata.json")).unwrap();
+        let report = parse_smartctl_json(&json).unwrap();
+        assert_eq!(report.health.as_deref(), Some("PASSED"));
+        assert_eq!(report.firmware_version.as_deref(), Some("RVT04B6Q"));
+        assert_eq!(report.serial.as_deref(), Some("S4EVNX0N123456A"));
+        assert_eq!(report.temperature_c, Some(31));
+        assert_eq!(report.wear_percent_used, Some(2));
+    }
+
+    #[test]
+    fn test_parse_smartctl_json_nvme_fixture() {
+        let json: Value = serde_json::from_str(include_str!("testdata/smartctl_x_nvme.json")).unwrap();
+        let report = parse_smartctl_json(&json).unwrap();
+        assert_eq!(report.health.as_deref(), Some("PASSED"));
+        assert_eq!(report.firmware_version.as_deref(), Some("GDC7302Q"));
+        assert_eq!(report.serial.as_deref(), Some("S6EWNJ0T700123"));
+        assert_eq!(report.temperature_c, Some(42));
+        assert_eq!(report.wear_percent_used, Some(7));
+        assert_eq!(report.nvme, NvmeHealth {
+            critical_warning: Some(0),
+            available_spare_percent: Some(100),
+            media_errors: Some(0),
+            unsafe_shutdowns: Some(37),
+        });
+    }
+
+    #[test]
+    fn test_parse_nvme_smart_log_fixture() {
+        let json: Value = serde_json::from_str(include_str!("testdata/nvme_smart_log.json")).unwrap();
+        let smart = parse_nvme_smart_log(&json);
+        assert_eq!(smart.health.as_deref(), Some("FAILED"));
+        assert_eq!(smart.critical_warning, Some(1));
+        assert_eq!(smart.temperature_c, Some(38));
+        assert_eq!(smart.wear_percent_used, Some(91));
+        assert_eq!(smart.available_spare_percent, Some(4));
+        assert_eq!(smart.media_errors, Some(3));
+        assert_eq!(smart.unsafe_shutdowns, Some(112));
+    }
+
+    #[test]
+    fn test_parse_nvme_health_nested_critical_warning() {
+        let json: Value = serde_json::from_str(r#"{"critical_warning": {"value": 0}, "avail_spare": 100}"#).unwrap();
+        let health = parse_nvme_health(&json);
+        assert_eq!(health.critical_warning, Some(0));
+        assert_eq!(health.available_spare_percent, Some(100));
+    }
+
+    #[test]
+    fn test_parse_smartctl_json_open_failure() {
+        let json: Value = serde_json::from_str(r#"{
+            "smartctl": {"exit_status": 2, "messages": [{"string": "Smartctl open device: /dev/sdz failed", "severity": "error"}]}
+        }"#).unwrap();
+        assert_eq!(parse_smartctl_json(&json), None);
+    }
+
+    #[test]
+    fn test_parse_smartctl_text_fallback() {
+        let text = "\
+=== START OF INFORMATION SECTION ===
+Device Model:     ST4000NM0033-9ZM170
+Serial Number:    Z1Z0ABCD
+Firmware Version: SN04
+=== START OF READ SMART DATA SECTION ===
+SMART overall-health self-assessment test result: PASSED
+";
+        let report = parse_smartctl_text(text).unwrap();
+        assert_eq!(report.health.as_deref(), Some("PASSED"));
+        assert_eq!(report.firmware_version.as_deref(), Some("SN04"));
+        assert_eq!(report.serial.as_deref(), Some("Z1Z0ABCD"));
+        assert_eq!(parse_smartctl_text("smartctl: command not understood"), None);
+    }
+
+    #[test]
+    fn test_parse_temperature_missing() {
+        let json: Value = serde_json::from_str(r#"{"device": {"name": "/dev/sda"}}"#).unwrap();
+        assert_eq!(parse_smartctl_temperature(&json), None);
+    }
 }
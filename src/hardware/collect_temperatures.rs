@@ -0,0 +1,51 @@
+use std::fs;
+
+use crate::hardware::read_trim;
+use crate::hardware::types::TemperatureInfo;
+
+/// Collect every hwmon temperature sensor (CPU, board, NVMe, ambient, ...).
+pub fn collect_temperatures() -> Vec<TemperatureInfo> {
+    let mut temps = Vec::new();
+
+    let entries = match fs::read_dir("/sys/class/hwmon") {
+        Ok(e) => e,
+        Err(_) => return temps,
+    };
+
+    let mut hwmons: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+    hwmons.sort();
+
+    for hwmon in hwmons {
+        let source = read_trim(hwmon.join("name")).unwrap_or_else(|| "hwmon".to_string());
+
+        let mut indices: Vec<u32> = match fs::read_dir(&hwmon) {
+            Ok(files) => files
+                .flatten()
+                .filter_map(|f| f.file_name().into_string().ok())
+                .filter_map(|n| n.strip_prefix("temp")?.strip_suffix("_input")?.parse().ok())
+                .collect(),
+            Err(_) => continue,
+        };
+        indices.sort_unstable();
+
+        for index in indices {
+            // sysfs reports millidegrees Celsius
+            let millidegrees = |suffix: &str| {
+                read_trim(hwmon.join(format!("temp{}_{}", index, suffix)))
+                    .and_then(|v| v.parse::<f64>().ok())
+                    .map(|v| v / 1000.0)
+            };
+
+            temps.push(TemperatureInfo {
+                source: source.clone(),
+                label: read_trim(hwmon.join(format!("temp{}_label", index)))
+                    .unwrap_or_else(|| format!("temp{}", index)),
+                value_c: millidegrees("input"),
+                max: millidegrees("max"),
+                crit: millidegrees("crit"),
+            });
+        }
+    }
+
+    temps
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::hardware::read_trim;
+use crate::hardware::types::UsbDeviceInfo;
+
+const USB_IDS_PATHS: &[&str] = &[
+    "/usr/share/hwdata/usb.ids",
+    "/usr/share/misc/usb.ids",
+    "/usr/share/usb.ids",
+    "/var/lib/usbutils/usb.ids",
+];
+
+// bDeviceClass for hubs (including the per-bus root hubs)
+const USB_CLASS_HUB: &str = "09";
+
+/// Vendor id -> (vendor name, product id -> product name)
+type UsbIds = HashMap<u16, (String, HashMap<u16, String>)>;
+
+/// Enumerate attached USB devices from /sys/bus/usb/devices.
+pub fn collect_usb_devices(include_hubs: bool) -> Vec<UsbDeviceInfo> {
+    let entries = match fs::read_dir("/sys/bus/usb/devices") {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut devices: Vec<UsbDeviceInfo> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            // Interface entries look like "1-1:1.0"; only devices carry idVendor
+            if name.contains(':') {
+                return None;
+            }
+            create_usb_device_info(&name, &entry.path())
+        })
+        .filter(|dev| include_hubs || dev.device_class.as_deref() != Some(USB_CLASS_HUB))
+        .collect();
+    devices.sort_by(|a, b| a.bus_path.cmp(&b.bus_path));
+    devices
+}
+
+fn create_usb_device_info(name: &str, device_path: &Path) -> Option<UsbDeviceInfo> {
+    let vendor_id = read_trim(device_path.join("idVendor"))?;
+    let product_id = read_trim(device_path.join("idProduct"))?;
+
+    let (vendor_name, product_name) = match (u16::from_str_radix(&vendor_id, 16), u16::from_str_radix(&product_id, 16)) {
+        (Ok(v), Ok(p)) => lookup_usb_ids(v, p),
+        _ => (None, None),
+    };
+
+    Some(UsbDeviceInfo {
+        bus_path: name.to_string(),
+        vendor_id,
+        product_id,
+        vendor_name,
+        product_name,
+        manufacturer: read_trim(device_path.join("manufacturer")),
+        product: read_trim(device_path.join("product")),
+        serial: read_trim(device_path.join("serial")),
+        device_class: read_trim(device_path.join("bDeviceClass")),
+        speed_mbps: read_trim(device_path.join("speed")).and_then(|s| s.parse().ok()),
+    })
+}
+
+fn lookup_usb_ids(vendor_id: u16, product_id: u16) -> (Option<String>, Option<String>) {
+    match usb_ids().and_then(|db| db.get(&vendor_id)) {
+        Some((vendor, products)) => (Some(vendor.clone()), products.get(&product_id).cloned()),
+        None => (None, None),
+    }
+}
+
+/// Load usb.ids once; without it devices are reported with raw ids only.
+fn usb_ids() -> Option<&'static UsbIds> {
+    static USB_IDS: OnceLock<Option<UsbIds>> = OnceLock::new();
+    USB_IDS
+        .get_or_init(|| {
+            USB_IDS_PATHS
+                .iter()
+                .find_map(|path| fs::read(path).ok())
+                .map(|raw| parse_usb_ids(&String::from_utf8_lossy(&raw)))
+        })
+        .as_ref()
+}
+
+/// Parse the vendor/product section of usb.ids:
+/// `046d  Logitech, Inc.` followed by tab-indented `\tc52b  Unifying Receiver`.
+/// Parsing stops at the first trailing section (device classes, HID tables, ...).
+fn parse_usb_ids(text: &str) -> UsbIds {
+    let mut ids = UsbIds::new();
+    let mut current: Option<u16> = None;
+
+    for line in text.lines() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix('\t') {
+            // Interface lines are double-tab indented
+            if rest.starts_with('\t') {
+                continue;
+            }
+            if let (Some(vendor), Some((id, name))) = (current, split_id_line(rest)) {
+                if let Some((_, products)) = ids.get_mut(&vendor) {
+                    products.insert(id, name);
+                }
+            }
+            continue;
+        }
+
+        match split_id_line(line) {
+            Some((id, name)) => {
+                ids.insert(id, (name, HashMap::new()));
+                current = Some(id);
+            }
+            None => break,
+        }
+    }
+
+    ids
+}
+
+fn split_id_line(line: &str) -> Option<(u16, String)> {
+    let (id, name) = line.split_once("  ")?;
+    let id = u16::from_str_radix(id, 16).ok()?;
+    Some((id, name.trim().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_usb_ids() {
+        let ids = parse_usb_ids(include_str!("testdata/usb.ids"));
+
+        assert_eq!(ids.len(), 3);
+        let (vendor, products) = &ids[&0x046d];
+        assert_eq!(vendor, "Logitech, Inc.");
+        assert_eq!(products.get(&0xc52b).map(String::as_str), Some("Unifying Receiver"));
+        // The interface line under c52b is not a product
+        assert_eq!(products.len(), 1);
+        assert_eq!(ids[&0x1d6b].1.get(&0x0003).map(String::as_str), Some("3.0 root hub"));
+        // Parsing stops at the device class section
+        assert!(!ids.contains_key(&0x0009));
+    }
+
+    #[test]
+    fn test_split_id_line() {
+        assert_eq!(split_id_line("0403  Future Technology Devices International, Ltd"), Some((0x0403, "Future Technology Devices International, Ltd".to_string())));
+        assert_eq!(split_id_line("C 09  Hub"), None);
+        assert_eq!(split_id_line("046d Logitech"), None);
+    }
+}
@@ -1,19 +1,43 @@
-use crate::hardware::types::Inventory;
-use crate::hardware;
+use std::collections::HashMap;
+use std::time::Instant;
 
-const AGENT_VERSION: &str = "1.0.0";
+use crate::hardware::types::{CollectorError, Inventory};
+use crate::hardware::{self, Collected};
 
-pub fn collect_full_inventory() -> Inventory {
-    let node = hardware::collect_node_info();
-    let cpu = hardware::collect_cpu_info();
-    let memory = hardware::collect_memory_info();
-    let disks = hardware::collect_disks();
-    let network = hardware::collect_network_info();
-    let gpus = hardware::collect_gpus();
-    let power_supplies = hardware::collect_power_supplies();
+const AGENT_VERSION: &str = crate::version::VERSION;
+
+/// Collect every hardware section. A collector that fails keeps whatever it
+/// gathered and is listed in `collection_errors` instead of aborting the run.
+/// With `record_timings`, the inventory also carries how long each collector
+/// took (plus "total"), in milliseconds.
+///
+/// With a remote BMC (`--bmc-host`) only the IPMI-backed sections are filled
+/// in; the CPU, memory, disk, network and GPU collectors read this machine.
+pub fn collect_full_inventory(record_timings: bool) -> Inventory {
+    let started = Instant::now();
+    let mut run = CollectionRun {
+        remote: hardware::ipmi_host().is_some(),
+        ..Default::default()
+    };
+
+    let collected_at = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let node = run.collect("node", hardware::collect_node::collect_node_info_checked);
+    let cpu = run.collect_local("cpu", hardware::collect_cpu::collect_cpu_info_checked);
+    let memory = run.collect_local("memory", hardware::collect_memory::collect_memory_info_checked);
+    let disks = run.collect_local("disks", hardware::collect_storage::collect_disks_checked);
+    let network = run.collect_local("network", hardware::collect_network::collect_network_info_checked);
+    let gpus = run.collect_local("gpus", hardware::collect_gpus::collect_gpus_checked);
+    let power_supplies = run.collect("power_supplies", hardware::collect_power::collect_power_supplies_checked);
+    let total_power_watts = run.collect("total_power", || Collected {
+        data: hardware::collect_total_power(&power_supplies),
+        errors: Vec::new(),
+    });
+    let fans = run.collect("fans", hardware::collect_fans::collect_fans_checked);
+    run.timings.insert("total".to_string(), started.elapsed().as_millis() as u64);
 
     Inventory {
         agent_version: AGENT_VERSION.to_string(),
+        collected_at,
         node,
         cpu,
         memory,
@@ -21,5 +45,40 @@ pub fn collect_full_inventory() -> Inventory {
         network,
         gpus,
         power_supplies,
+        total_power_watts,
+        fans,
+        collection_timings_ms: if record_timings { Some(run.timings) } else { None },
+        collection_errors: run.errors,
+    }
+}
+
+#[derive(Default)]
+struct CollectionRun {
+    timings: HashMap<String, u64>,
+    errors: Vec<CollectorError>,
+    remote: bool, // inventorying a remote BMC rather than this machine
+}
+
+impl CollectionRun {
+    /// Run one collector, timing it and recording any errors under its name.
+    fn collect<T>(&mut self, name: &str, collector: impl FnOnce() -> Collected<T>) -> T {
+        let started = Instant::now();
+        let collected = collector();
+        self.timings.insert(name.to_string(), started.elapsed().as_millis() as u64);
+
+        self.errors.extend(collected.errors.into_iter().map(|message| CollectorError {
+            collector: name.to_string(),
+            message,
+        }));
+        collected.data
+    }
+
+    /// [`collect`](Self::collect) for a collector that can only read this
+    /// machine; skipped, and left empty, when the target is a remote BMC.
+    fn collect_local<T: Default>(&mut self, name: &str, collector: impl FnOnce() -> Collected<T>) -> T {
+        if self.remote {
+            return T::default();
+        }
+        self.collect(name, collector)
     }
-}
\ No newline at end of file
+}
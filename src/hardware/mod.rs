@@ -1,20 +1,214 @@
 // Hardware inventory collection modules
+use std::sync::OnceLock;
+
+use pciid_parser::Database;
+use smbioslib::SMBiosData;
+
 pub mod types;
 pub mod collect_memory;
 pub mod collect_cpu;
 pub mod collect_network;
 pub mod collect_storage;
+pub mod collect_filesystems;
 pub mod collect_gpus;
+pub mod collect_pci;
+pub mod collect_usb;
 pub mod collect_node;
 pub mod collect_power;
+pub mod collect_power_cap;
+pub mod collect_fans;
+pub mod collect_temperatures;
+pub mod collect_sel;
+pub mod collect_dmi;
+pub mod chassis;
 pub mod collector;
 
 // Re-export main collection functions
 pub use collect_memory::collect_memory_info;
-pub use collect_cpu::collect_cpu_info;
+pub use collect_cpu::{collect_cpu_info, collect_cpu_freq_info};
 pub use collect_network::collect_network_info;
 pub use collect_storage::collect_disks;
-pub use collect_gpus::collect_gpus;
+pub use collect_filesystems::collect_filesystems;
+pub use collect_pci::collect_pci_devices;
+pub use collect_usb::collect_usb_devices;
 pub use collect_node::collect_node_info;
-pub use collect_power::collect_power_supplies;
-pub use collector::collect_full_inventory;
\ No newline at end of file
+pub use collect_power::{collect_power_supplies, collect_total_power};
+pub use collect_power_cap::{collect_power_cap, set_power_cap};
+pub use collect_fans::collect_fans;
+pub use collect_temperatures::collect_temperatures;
+pub use collect_sel::{collect_sel, clear_sel};
+pub use collect_dmi::collect_dmi_structures;
+pub use chassis::{boot_device, chassis_identify, chassis_power, set_boot_device};
+pub use collector::collect_full_inventory;
+
+const DMI_TABLE_PATH: &str = "/sys/firmware/dmi/tables/DMI";
+
+/// What a collector gathered, plus the problems that left it incomplete.
+/// An empty `errors` means the data can be trusted as the whole picture.
+pub(crate) struct Collected<T> {
+    pub data: T,
+    pub errors: Vec<String>,
+}
+
+/// Load the SMBIOS table from sysfs, falling back to parsing the raw table.
+pub(crate) fn load_smbios() -> Result<SMBiosData, String> {
+    match SMBiosData::try_load_from_file(DMI_TABLE_PATH, None) {
+        Ok(data) => Ok(data),
+        Err(_) => std::fs::read(DMI_TABLE_PATH)
+            .map(|table_data| SMBiosData::from_vec_and_version(table_data, None))
+            .map_err(|e| format!("cannot read {}: {}", DMI_TABLE_PATH, e)),
+    }
+}
+
+/// BMC that ipmitool talks to. With no host it uses the local BMC in-band;
+/// with one, every IPMI collector reads that BMC over the network instead.
+#[derive(Debug, Default)]
+pub struct IpmiTarget {
+    pub host: Option<String>,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub interface: Option<String>, // ipmitool -I; remote hosts default to lanplus
+}
+
+static IPMI_TARGET: OnceLock<IpmiTarget> = OnceLock::new();
+
+/// Point all subsequent ipmitool invocations at `target`.
+pub fn set_ipmi_target(target: IpmiTarget) {
+    let _ = IPMI_TARGET.set(target);
+}
+
+/// The remote BMC host, if ipmitool is not using the local one.
+pub(crate) fn ipmi_host() -> Option<&'static str> {
+    IPMI_TARGET.get().and_then(|t| t.host.as_deref())
+}
+
+/// An ipmitool command already carrying the target's connection options.
+/// The password goes through IPMI_PASSWORD (`-E`) rather than `-P` so it
+/// doesn't show up in the process list.
+pub(crate) fn ipmitool_command() -> std::process::Command {
+    let mut cmd = std::process::Command::new("ipmitool");
+    let Some(target) = IPMI_TARGET.get() else {
+        return cmd;
+    };
+
+    if let Some(interface) = target.interface.as_deref().or(target.host.as_ref().map(|_| "lanplus")) {
+        cmd.args(["-I", interface]);
+    }
+    if let Some(host) = &target.host {
+        cmd.args(["-H", host]);
+        if let Some(user) = &target.user {
+            cmd.args(["-U", user]);
+        }
+        if let Some(password) = &target.password {
+            cmd.env("IPMI_PASSWORD", password);
+        }
+        cmd.arg("-E");
+    }
+    cmd
+}
+
+/// Run ipmitool and return its stdout, treating a failed run as no output so
+/// callers can retry it.
+pub(crate) fn ipmitool(args: &[&str]) -> Option<String> {
+    crate::util::run_capture(ipmitool_command().args(args))
+        .ok()
+        .filter(|c| c.success)
+        .map(|c| c.stdout)
+}
+
+/// [`ipmitool`] with the retries from `FARM_MANAGER_TOOL_ATTEMPTS`, but only
+/// for failures that can clear up on their own (a timeout, a refused LAN
+/// session, a busy BMC). Bad credentials or a command the BMC doesn't support
+/// fail on the first try.
+pub(crate) fn ipmitool_retried(args: &[&str]) -> Option<String> {
+    crate::util::retry_tool(|| match crate::util::run_capture(ipmitool_command().args(args)) {
+        Ok(capture) if capture.success => Some(Some(capture.stdout)),
+        Ok(capture) if ipmi_transient(&capture) => None,
+        _ => Some(None),
+    })
+    .flatten()
+}
+
+fn ipmi_transient(capture: &crate::util::Capture) -> bool {
+    if capture.timed_out {
+        return true;
+    }
+    let stderr = capture.stderr.to_lowercase();
+    // lanplus reports rejected credentials as a failed session too
+    let bad_credentials = ["unauthorized name", "invalid user name", "password invalid", "hmac is invalid", "invalid role"]
+        .iter()
+        .any(|marker| stderr.contains(marker));
+    !bad_credentials
+        && ["timeout", "timed out", "no response", "unable to establish", "insufficient resources", "node busy"]
+            .iter()
+            .any(|marker| stderr.contains(marker))
+}
+
+/// Whether `tool` resolves to a file on PATH, without running it.
+pub(crate) fn tool_on_path(tool: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(tool).is_file()))
+        .unwrap_or(false)
+}
+
+/// A sysfs attribute with surrounding whitespace removed; missing or empty
+/// files read as `None`.
+pub(crate) fn read_trim<P: AsRef<std::path::Path>>(path: P) -> Option<String> {
+    std::fs::read_to_string(path).ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+/// Load pci.ids once per process; every collector shares the parsed database.
+fn pci_database() -> Option<&'static Database> {
+    static PCI_DB: OnceLock<Option<Database>> = OnceLock::new();
+    PCI_DB.get_or_init(|| Database::read().ok()).as_ref()
+}
+
+/// Parse a sysfs PCI id such as "0x10de" (the 0x prefix is optional).
+pub(crate) fn parse_pci_id(hex: &str) -> Option<u16> {
+    u16::from_str_radix(hex.strip_prefix("0x").unwrap_or(hex), 16).ok()
+}
+
+/// Look up vendor and device names using the PCI database
+pub(crate) fn lookup_pci_ids(vendor_id: u16, device_id: u16) -> Option<(String, String)> {
+    let db = pci_database()?;
+
+    // Get vendor - this should always work if the vendor exists
+    let vendor = db.vendors.get(&vendor_id)?;
+    let vendor_name = vendor.name.clone();
+
+    // Get device - fallback to "Unknown Device" if device not found
+    let device_name = vendor.devices.get(&device_id)
+        .map(|d| d.name.clone())
+        .unwrap_or_else(|| format!("Unknown Device [0x{:04x}]", device_id));
+
+    Some((vendor_name, device_name))
+}
+
+/// Resolve a 24-bit PCI class code to its subclass name, falling back to the base class name.
+pub(crate) fn lookup_pci_class(class_code: u32) -> Option<String> {
+    let db = pci_database()?;
+
+    let class = db.classes.get(&((class_code >> 16) as u8))?;
+    let subclass = class.subclasses.get(&((class_code >> 8) as u8));
+    Some(subclass.map(|s| s.name.clone()).unwrap_or_else(|| class.name.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::Capture;
+
+    fn failed(stderr: &str) -> Capture {
+        Capture { success: false, timed_out: false, code: Some(1), stdout: String::new(), stderr: stderr.to_string() }
+    }
+
+    #[test]
+    fn test_ipmi_transient() {
+        assert!(ipmi_transient(&failed("Error: Unable to establish IPMI v2 / RMCP+ session\n")));
+        assert!(ipmi_transient(&failed("Get Device ID command failed: 0xc3 Timeout\n")));
+        assert!(ipmi_transient(&failed("Get SDR 0x0042 command failed: Node busy\n")));
+        assert!(!ipmi_transient(&failed("> RAKP 2 HMAC is invalid\nError: Unable to establish IPMI v2 / RMCP+ session\n")));
+        assert!(!ipmi_transient(&failed("Invalid command\n")));
+        assert!(!ipmi_transient(&failed("Could not open device at /dev/ipmi0 or /dev/ipmi/0 or /dev/ipmidev/0: No such file or directory\n")));
+    }
+}
@@ -1,9 +1,14 @@
-use serde::Serialize;
-use std::collections::HashMap;
-
-#[derive(Debug, Serialize)]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// The full hardware payload. It round-trips through JSON; sections missing
+/// from older snapshots load as empty so `merge` and fixtures keep working.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
 pub struct Inventory {
     pub agent_version: String,
+    pub collected_at: String,      // RFC 3339, UTC
     pub node: NodeInfo,
     pub cpu: CpuInfo,
     pub memory: MemoryInfo,
@@ -11,12 +16,75 @@ pub struct Inventory {
     pub network: NetworkInfo,
     pub gpus: Vec<GpuInfo>,
     pub power_supplies: Vec<PowerSupplyInfo>,
+    pub total_power_watts: Option<u32>, // node draw: BMC DCMI reading, else summed PSU output
+    pub fans: Vec<FanInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection_timings_ms: Option<HashMap<String, u64>>, // per collector, with --timings
+    pub collection_errors: Vec<CollectorError>,                // empty when every collector succeeded
+}
+
+/// Cross-node rollup produced by `farm-manager merge --summary`; each map
+/// counts how many nodes (or devices, for GPUs and NICs) report a value.
+#[derive(Debug, Serialize)]
+pub struct FleetSummary {
+    pub nodes: usize,
+    pub cpu_models: BTreeMap<String, usize>,
+    pub gpu_models: BTreeMap<String, usize>,
+    pub kernel_versions: BTreeMap<String, usize>,
+    pub bios_versions: BTreeMap<String, usize>,
+    pub bmc_firmware_versions: BTreeMap<String, usize>,
+    pub nic_firmware_versions: BTreeMap<String, usize>, // keyed "<driver> <firmware>"
+    pub nodes_with_collection_errors: usize,
 }
 
+/// Field-aware comparison of two inventory snapshots (`farm-manager diff`).
 #[derive(Debug, Serialize)]
+pub struct InventoryDiff {
+    pub before_collected_at: String,
+    pub after_collected_at: String,
+    pub changes: Vec<InventoryChange>, // grouped by subsystem
+}
+
+#[derive(Debug, Serialize)]
+pub struct InventoryChange {
+    pub subsystem: String, // "node", "cpu", "memory", "disks", "gpus", "network", "power_supplies"
+    pub change: String,    // "added", "removed" or "changed"
+    pub item: String,      // disk serial, GPU UUID, DIMM slot, or a field such as "bios.version"
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Result of `hardware inventory --expect`: each rule with the count found.
+#[derive(Debug, Serialize)]
+pub struct InventoryValidation {
+    pub hostname: String,
+    pub passed: bool, // every rule held
+    pub results: Vec<ExpectationResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExpectationResult {
+    pub rule: String, // normalized, e.g. "gpus=8" or "disks>=4"
+    pub actual: u64,
+    pub passed: bool,
+}
+
+/// A collector that ran into a real problem (permission denied, tool missing),
+/// so its section of the inventory may be incomplete.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CollectorError {
+    pub collector: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct NodeInfo {
     pub hostname: String,
     pub architecture: String,
+    pub kernel_version: Option<String>, // uname -r
+    pub virtualization: Option<String>, // systemd-detect-virt id: "none" on bare metal, "kvm", "docker", ...
     pub product_name: Option<String>,
     pub manufacturer: Option<String>,
     pub serial_number: Option<String>,
@@ -27,7 +95,8 @@ pub struct NodeInfo {
     pub bmc: Option<BmcInfo>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MotherboardInfo {
     pub manufacturer: Option<String>,
     pub product_name: Option<String>,
@@ -35,7 +104,8 @@ pub struct MotherboardInfo {
     pub serial_number: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BiosInfo {
     pub vendor: Option<String>,
     pub version: Option<String>,
@@ -43,7 +113,8 @@ pub struct BiosInfo {
 }
 
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BmcInfo {
     pub ip_address: Option<String>,
     pub mac_address: Option<String>,
@@ -51,7 +122,8 @@ pub struct BmcInfo {
     pub release_date: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CpuInfo {
     pub sockets: Option<u32>,
     pub cores: Option<u32>,
@@ -59,7 +131,8 @@ pub struct CpuInfo {
     pub cpus: Vec<CpuSocket>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CpuSocket {
     pub socket: u32,
     pub manufacturer: Option<String>,
@@ -74,12 +147,37 @@ pub struct CpuSocket {
 }
 
 #[derive(Debug, Serialize)]
+pub struct CpuFreqInfo {
+    pub cpufreq_available: bool,
+    pub governors: Vec<String>, // distinct governors in use across all CPUs
+    pub sockets: Vec<CpuFreqSocket>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CpuFreqSocket {
+    pub socket: u32,
+    pub cpus: Vec<CoreFreqInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CoreFreqInfo {
+    pub cpu: u32,
+    pub core_id: Option<u32>,
+    pub scaling_cur_freq_mhz: Option<u32>,
+    pub scaling_min_freq_mhz: Option<u32>,
+    pub scaling_max_freq_mhz: Option<u32>,
+    pub scaling_governor: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MemoryInfo {
     pub total_bytes: Option<u64>,
     pub dimms: Vec<DimmInfo>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DimmInfo {
     pub slot: Option<String>,
     pub size_bytes: Option<u64>,
@@ -88,9 +186,14 @@ pub struct DimmInfo {
     pub manufacturer: Option<String>,
     pub serial_number: Option<String>,
     pub part_number: Option<String>,
+    pub rank: Option<u8>,          // ranks per module (1 = single, 2 = dual, ...)
+    pub voltage_v: Option<f32>,    // configured voltage
+    pub form_factor: Option<String>, // DIMM, SODIMM, ...
+    pub technology: Option<String>,  // DRAM, NVDIMM-N, ...
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DiskInfo {
     pub name: String,
     pub dev_path: String,
@@ -99,32 +202,98 @@ pub struct DiskInfo {
     pub size_bytes: Option<u64>,
     pub rotational: Option<bool>,
     pub bus_type: Option<String>, // "nvme", "scsi", "virtio", etc.
+    pub controller: Option<String>,  // NVMe controller ("nvme0") or, for multipath, subsystem ("nvme-subsys0")
+    pub namespace_id: Option<u32>,   // NVMe namespace id
+    pub multipath: Option<bool>,     // NVMe namespace reachable through more than one controller
     pub firmware_version: Option<String>,
+    pub hctl: Option<String>,      // SCSI host:channel:target:lun
+    pub enclosure: Option<String>, // SES enclosure id, when behind a backplane
+    pub slot: Option<u32>,
+    pub discard_supported: Option<bool>, // TRIM/UNMAP support
+    pub discard_max_bytes: Option<u64>,
     pub smart: Option<SmartInfo>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SmartInfo {
     pub health: Option<String>,
+    pub temperature_c: Option<i32>,
+    pub wear_percent_used: Option<u32>, // SSD endurance consumed (0-100+)
+    pub critical_warning: Option<u8>,   // NVMe critical warning bitmask; non-zero means trouble
+    pub available_spare_percent: Option<u32>, // NVMe spare blocks left
+    pub media_errors: Option<u64>,      // NVMe unrecovered data integrity errors
+    pub unsafe_shutdowns: Option<u64>,  // NVMe power losses without a clean shutdown
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FanInfo {
+    pub name: String,
+    pub speed_rpm: Option<u32>,
+    pub status: Option<String>, // "ok", "alarm", "fault", "stopped", or the IPMI sensor status
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemperatureInfo {
+    pub source: String,      // hwmon chip name (coretemp, k10temp, nvme, ...)
+    pub label: String,
+    pub value_c: Option<f64>,
+    pub max: Option<f64>,    // Celsius
+    pub crit: Option<f64>,   // Celsius
+}
+
+#[derive(Debug, Serialize)]
+pub struct DmiStructure {
+    pub dmi_type: u8,
+    pub handle: String,            // hex, as printed by dmidecode
+    pub data: serde_json::Value,   // decoded structure from smbios-lib
+}
+
+#[derive(Debug, Serialize)]
+pub struct SelEntry {
+    pub id: String,                // SEL record id (hex, as printed by ipmitool)
+    pub timestamp: Option<String>, // "MM/DD/YYYY HH:MM:SS" or "Pre-Init ..."
+    pub sensor: Option<String>,
+    pub event: Option<String>,
+    pub direction: Option<String>, // "Asserted" / "Deasserted"
 }
 
 #[derive(Debug, Serialize)]
+pub struct FilesystemInfo {
+    pub device: String,
+    pub mountpoint: String,
+    pub fstype: String,
+    pub read_only: bool,
+    pub total_bytes: Option<u64>,
+    pub used_bytes: Option<u64>,
+    pub available_bytes: Option<u64>,
+    pub inodes_total: Option<u64>,
+    pub inodes_used: Option<u64>,
+    pub inodes_free: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct NetworkInfo {
     pub interfaces: Vec<NetInterface>,
     pub routes: Vec<RouteInfo>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct NetInterface {
     pub name: String,
     pub mac_address: Option<String>,
     pub mtu: Option<u32>,
     pub speed_mbps: Option<u32>,
     pub driver: Option<String>,
+    pub driver_version: Option<String>, // ethtool -i "version:"
     pub firmware_version: Option<String>,
     pub vendor_name: Option<String>,
     pub device_name: Option<String>,
     pub pci_address: Option<String>,
+    pub numa_node: Option<i32>, // None when the NIC has no NUMA affinity
     pub addresses: Vec<IpAddress>,
     
     // Bond/Team configuration
@@ -133,21 +302,60 @@ pub struct NetInterface {
     pub bond_master: Option<String>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct IpAddress {
     pub family: String, // "IPv4" or "IPv6"
     pub address: String,
     pub prefix: u8,
+    pub scope: Option<String>,            // "global", "link", "host", ...
+    pub valid_lifetime_secs: Option<u64>, // None when the address never expires
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RouteInfo {
-    pub dst: String,     // CIDR
-    pub gateway: String, // IP
+    #[serde(default)]
+    pub family: String,          // "inet" or "inet6"; empty in snapshots that predate it
+    pub dst: String,             // CIDR
+    pub gateway: String,         // IP
     pub iface: String,
+    pub prefsrc: Option<String>, // preferred source address
+    pub metric: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PciDeviceInfo {
+    pub address: String,                // 0000:3b:00.0
+    pub vendor_id: Option<String>,      // 4-digit hex, e.g. "10de"
+    pub device_id: Option<String>,
+    pub vendor: Option<String>,
+    pub device: Option<String>,
+    pub class_id: Option<String>,       // raw sysfs class, e.g. "0x030200"
+    pub class: Option<String>,          // e.g. "3D controller"
+    pub driver: Option<String>,
+    pub link_speed: Option<String>,     // e.g. "16.0 GT/s PCIe"
+    pub link_width: Option<u32>,
+    pub max_link_speed: Option<String>,
+    pub max_link_width: Option<u32>,
 }
 
 #[derive(Debug, Serialize)]
+pub struct UsbDeviceInfo {
+    pub bus_path: String,               // sysfs name, e.g. "1-1.2"
+    pub vendor_id: String,              // 4-digit hex, e.g. "1050"
+    pub product_id: String,
+    pub vendor_name: Option<String>,    // from usb.ids
+    pub product_name: Option<String>,   // from usb.ids
+    pub manufacturer: Option<String>,   // device-reported strings
+    pub product: Option<String>,
+    pub serial: Option<String>,
+    pub device_class: Option<String>,   // bDeviceClass (hex)
+    pub speed_mbps: Option<f64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GpuInfo {
     pub vendor: Option<String>,
     pub model: Option<String>,
@@ -157,6 +365,33 @@ pub struct GpuInfo {
     pub uuid: Option<String>,
 }
 
+/// Health normalized across DCGM and NVML results. Results parsed from tool
+/// output keep the original wording alongside it in `raw_status`.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStatus {
+    Ok,
+    Warning,
+    Critical,
+    #[default]
+    Unknown,
+}
+
+impl HealthStatus {
+    /// Map a tool's status text ("Pass", "Healthy", "Warning", "Failure", ...).
+    pub fn from_raw(raw: &str) -> Self {
+        let raw = raw.to_lowercase();
+        if raw.contains("fail") || raw.contains("error") || raw.contains("critical") {
+            HealthStatus::Critical
+        } else if raw.contains("warn") {
+            HealthStatus::Warning
+        } else if raw.contains("pass") || raw.contains("healthy") || raw == "ok" {
+            HealthStatus::Ok
+        } else {
+            HealthStatus::Unknown
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct GpuErrorInfo {
     pub device_index: u32,
@@ -168,9 +403,10 @@ pub struct GpuErrorInfo {
     pub thermal_violations: Option<String>,
     pub power_violations: Option<String>,
     pub has_errors: bool,
+    pub status: HealthStatus,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize)]
 pub struct GpuHealthInfo {
     pub device_index: u32,
     pub device_name: String,
@@ -178,7 +414,10 @@ pub struct GpuHealthInfo {
     pub temperature_celsius: Option<u32>,
     pub power_usage_watts: Option<u32>,
     pub power_limit_watts: Option<u32>,
-    pub fan_speed_percent: Option<u32>,
+    pub power_limit_min_watts: Option<u32>, // enforced power-limit constraints
+    pub power_limit_max_watts: Option<u32>,
+    pub fan_speed_percent: Option<u32>,     // first fan, kept for compatibility
+    pub fan_speeds_percent: Vec<u32>,       // every fan reported by NVML
     pub utilization_gpu_percent: Option<u32>,
     pub utilization_memory_percent: Option<u32>,
     pub memory_used_mb: Option<u32>,
@@ -186,7 +425,45 @@ pub struct GpuHealthInfo {
     pub clock_graphics_mhz: Option<u32>,
     pub clock_memory_mhz: Option<u32>,
     pub throttle_reasons: Vec<String>,
+    pub total_throttle_ms: Option<u64>,     // power + thermal violation time since driver load
     pub performance_state: Option<String>,
+    pub persistence_mode: Option<bool>,
+    pub ecc_enabled: Option<bool>,          // current ECC mode (a pending change needs a reset)
+    pub status: HealthStatus,               // from throttle reasons
+}
+
+/// Tunable GPU settings as read back from NVML (`test gpu-config`).
+#[derive(Debug, Serialize)]
+pub struct GpuConfigState {
+    pub power_limit_watts: Option<u32>,
+    pub application_clock_graphics_mhz: Option<u32>,
+    pub application_clock_memory_mhz: Option<u32>,
+    pub persistence_mode: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GpuConfigChange {
+    pub device_index: u32,
+    pub device_name: String,
+    pub before: GpuConfigState,
+    pub after: GpuConfigState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>, // why a requested setting was not applied
+}
+
+#[derive(Debug, Serialize)]
+pub struct GpuTopology {
+    pub devices: Vec<GpuTopologyDevice>,
+    pub matrix: Vec<Vec<String>>, // matrix[i][j]: X, NV<n>, BOARD, PIX, PXB, PHB, NODE, SYS
+}
+
+#[derive(Debug, Serialize)]
+pub struct GpuTopologyDevice {
+    pub index: u32,
+    pub name: String,
+    pub pci_bus_id: String,
+    pub cpu_affinity: Option<String>, // CPU list, e.g. "0-15,32-47"
+    pub numa_node: Option<i32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -196,6 +473,10 @@ pub struct NcclInfo {
     pub num_gpus: u32,
     pub nccl_available: bool,
     pub nccl_tests_available: bool,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>, // NCCL_* variables set in the environment
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ib_hcas: Vec<String>, // InfiniBand HCAs from /sys/class/infiniband
     pub error: Option<String>,
 }
 
@@ -209,8 +490,21 @@ pub struct NcclTestResult {
     pub time_us: Option<f64>,
     pub bandwidth_gbps: Option<f64>,
     pub bus_bandwidth_gbps: Option<f64>,
+    pub bandwidth_bytes_per_sec: Option<u64>, // bandwidth_gbps normalized to bytes/s
     pub error: Option<String>,
     pub gpu_results: Vec<NcclGpuResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p2p_matrix: Option<Vec<NcclP2pLink>>, // NVML peer access, reported when nccl-tests is missing
+}
+
+#[derive(Debug, Serialize)]
+pub struct NcclP2pLink {
+    pub source_index: u32,
+    pub peer_index: u32,
+    pub p2p_read: bool,
+    pub p2p_write: bool,
+    pub nvlink: bool,
+    pub link_type: Option<String>, // nvidia-smi topo style: NV, PIX, PXB, PHB, NODE, SYS
 }
 
 #[derive(Debug, Serialize)]
@@ -232,6 +526,39 @@ pub struct MpiInfo {
     pub error: Option<String>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct IbBwResult {
+    pub test_type: String,                    // "write" or "read"
+    pub role: String,                         // "server" (listened) or "client"
+    pub server: Option<String>,               // host the client connected to
+    pub device: Option<String>,               // RDMA device, e.g. mlx5_0
+    pub size_bytes: u64,
+    pub iterations: u32,
+    pub success: bool,
+    pub bw_peak_mbps: Option<f64>,            // perftest MB/sec (2^20 bytes)
+    pub bw_average_mbps: Option<f64>,
+    pub bandwidth_bytes_per_sec: Option<u64>, // bw_average_mbps normalized to bytes/s
+    pub msg_rate_mpps: Option<f64>,           // million messages per second
+    pub error: Option<String>,
+    pub raw_output: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NetBwResult {
+    pub protocol: String,                    // "tcp" or "udp"
+    pub role: String,                        // "server" (listened) or "client"
+    pub server: Option<String>,              // host the client connected to
+    pub parallel: u32,                       // iperf3 streams
+    pub duration_secs: u32,
+    pub success: bool,
+    pub sent_bits_per_sec: Option<f64>,
+    pub received_bits_per_sec: Option<f64>,
+    pub retransmits: Option<u64>,            // TCP only
+    pub jitter_ms: Option<f64>,              // UDP only
+    pub lost_percent: Option<f64>,           // UDP only
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct MpiTestResult {
     pub test_type: String,
@@ -241,6 +568,7 @@ pub struct MpiTestResult {
     pub success: bool,
     pub latency_us: Option<f64>,
     pub bandwidth_mbps: Option<f64>,
+    pub bandwidth_bytes_per_sec: Option<u64>, // bandwidth_mbps normalized to bytes/s
     pub min_latency_us: Option<f64>,
     pub max_latency_us: Option<f64>,
     pub avg_latency_us: Option<f64>,
@@ -278,10 +606,75 @@ pub struct HashcatTestResult {
     pub time_seconds: Option<f64>,
     pub recovered: Option<u32>,
     pub total: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>, // hashcat --session name, for resumable runs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hashcat_status: Option<String>, // e.g. "Cracked", "Exhausted", "Aborted"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restore_point: Option<u64>, // keyspace position a resumed run continues from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restore_keyspace: Option<u64>,
+    pub all_in_potfile: bool, // nothing left to crack: every hash was already in the potfile
+    pub truncated: bool, // stopped by --max-runtime before finishing
     pub error: Option<String>,
     pub raw_output: Option<String>,
 }
 
+impl HashcatTestResult {
+    /// A not-yet-successful result that a run fills in as it goes.
+    pub fn new(test_type: &str, hash_type: Option<String>, device_ids: Vec<u32>) -> Self {
+        HashcatTestResult {
+            test_type: test_type.to_string(),
+            hash_type,
+            device_ids,
+            success: false,
+            hash_speed: None,
+            time_seconds: None,
+            recovered: None,
+            total: None,
+            session: None,
+            hashcat_status: None,
+            restore_point: None,
+            restore_keyspace: None,
+            all_in_potfile: false,
+            truncated: false,
+            error: None,
+            raw_output: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CpuTestResult {
+    pub method: String,                         // "stress-ng", "sysbench" or "builtin"
+    pub threads: u32,
+    pub requested_seconds: u64,
+    pub elapsed_seconds: f64,
+    pub success: bool,
+    pub events_per_sec: Option<f64>,            // in the method's own units (stress-ng bogo ops)
+    pub total_events: Option<u64>,
+    pub samples: u32,                           // CPU temperature samples taken
+    pub peak_temperature_celsius: Option<f64>,  // hottest CPU sensor in any sample
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HashcatStressResult {
+    pub hash_type: String,
+    pub device_ids: Vec<u32>,
+    pub requested_seconds: u64,
+    pub elapsed_seconds: f64,
+    pub iterations: u32,              // benchmark passes completed
+    pub min_hash_speed: Option<f64>,  // H/s, slowest pass
+    pub max_hash_speed: Option<f64>,  // H/s, fastest pass
+    pub samples: u32,                 // NVML health samples taken
+    pub peak_temperature_celsius: Option<u32>, // hottest GPU in any sample
+    pub peak_power_watts: Option<u32>,
+    pub thermal_throttling: bool,
+    pub throttle_reasons: Vec<String>, // distinct reasons seen during the run
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct DcgmInfo {
     pub dcgm_version: Option<String>,
@@ -298,8 +691,10 @@ pub struct DcgmDiagResult {
     pub test_name: String,
     pub success: bool,
     pub gpu_results: Vec<DcgmGpuDiagResult>,
-    pub overall_result: String, // "Pass", "Fail", "Warning"
+    pub status: HealthStatus,
+    pub raw_status: String, // "Pass", "Fail", "Warning", or DCGM's own wording
     pub time_seconds: Option<f64>,
+    pub truncated: bool, // killed by --max-runtime before finishing
     pub error: Option<String>,
     pub raw_output: Option<String>,
 }
@@ -308,15 +703,33 @@ pub struct DcgmDiagResult {
 pub struct DcgmGpuDiagResult {
     pub device_index: u32,
     pub device_name: Option<String>,
-    pub result: String, // "Pass", "Fail", "Skip", "Warning"
+    pub status: HealthStatus, // a skipped test is Unknown
+    pub raw_status: String,   // "Pass", "Fail", "Skip"
     pub info: Option<String>,
 }
 
+/// One check in `test all`.
+#[derive(Debug, Serialize)]
+pub struct DiagnosticCheck {
+    pub name: String,                      // the matching `test` subcommand, e.g. "dcgm-health"
+    pub status: HealthStatus,
+    pub skipped: bool,                     // prerequisites (NVML, dcgmi, ...) missing
+    pub message: Option<String>,           // why it was skipped or could not run
+    pub result: Option<serde_json::Value>, // what the subcommand on its own would print
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiagnosticReport {
+    pub status: HealthStatus, // worst status among the checks that ran
+    pub checks: Vec<DiagnosticCheck>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct DcgmHealthCheck {
     pub device_index: u32,
     pub device_name: Option<String>,
-    pub health_status: String, // "Healthy", "Warning", "Failure"
+    pub status: HealthStatus,
+    pub raw_status: String, // "Healthy", "Warning", "Failure"
     pub incidents: Vec<DcgmIncident>,
 }
 
@@ -334,7 +747,8 @@ pub struct Timestamps {
     pub agent_version: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PowerSupplyInfo {
     pub name: Option<String>,
     pub manufacturer: Option<String>,
@@ -352,6 +766,43 @@ pub struct PowerSupplyInfo {
     pub fan_speed_rpm: Option<u32>,
 }
 
+/// DCMI power limit from the BMC (`hardware power-cap`).
+#[derive(Debug, Default, Serialize)]
+pub struct PowerCapInfo {
+    pub active: bool,                     // the limit is being enforced
+    pub limit_state: Option<String>,      // BMC wording, e.g. "No Active Power Limit"
+    pub limit_watts: Option<u32>,
+    pub exception_action: Option<String>, // what the BMC does when the cap can't be held
+    pub correction_time_ms: Option<u32>,
+    pub sampling_period_secs: Option<u32>,
+}
+
+/// Boot device override from the BMC (`hardware boot-device`).
+#[derive(Debug, Default, Serialize)]
+pub struct BootDeviceInfo {
+    pub override_active: bool,     // the BMC will apply `device` on the next boot
+    pub persistent: bool,          // applies to all future boots, not just the next one
+    pub device: Option<String>,    // pxe, disk, bios, cdrom, none, or the BMC's wording
+    pub selector: Option<String>,  // BMC wording, e.g. "Force PXE"
+    pub boot_mode: Option<String>, // legacy or UEFI
+}
+
+/// Output of `hardware power`: the PSUs plus one number for power budgeting.
+#[derive(Debug, Serialize)]
+pub struct PowerReport {
+    pub total_power_watts: Option<u32>, // node draw: BMC DCMI reading, else summed PSU output
+    pub power_supplies: Vec<PowerSupplyInfo>,
+}
+
+/// One reading from `hardware power --watch`.
+#[derive(Debug, Serialize)]
+pub struct PowerSample {
+    pub timestamp: String, // RFC 3339, UTC
+    pub sample: u32,       // 1-based
+    pub total_power_watts: Option<u32>,
+    pub power_supplies: Vec<PowerSupplyInfo>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct RawBlobs {
     pub lshw: Option<serde_json::Value>,
@@ -360,3 +811,60 @@ pub struct RawBlobs {
     pub dmidecode: Option<serde_json::Value>,
     pub extra: HashMap<String, serde_json::Value>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inventory_round_trips_through_json() {
+        let mut inventory = Inventory {
+            agent_version: "1.0.0".to_string(),
+            collected_at: "2025-01-01T00:00:00Z".to_string(),
+            ..Default::default()
+        };
+        inventory.node.hostname = "gpu-node-01".to_string();
+        inventory.gpus.push(GpuInfo {
+            model: Some("H100 SXM5 80GB".to_string()),
+            vram_mb: Some(81559),
+            ..Default::default()
+        });
+        inventory.collection_errors.push(CollectorError {
+            collector: "disks".to_string(),
+            message: "smartctl not found on PATH; SMART data is missing".to_string(),
+        });
+
+        let json = serde_json::to_string(&inventory).unwrap();
+        let loaded: Inventory = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&inventory).unwrap());
+    }
+
+    #[test]
+    fn test_older_snapshot_without_new_sections_still_loads() {
+        let loaded: Inventory = serde_json::from_str(r#"{
+            "agent_version": "0.9.0",
+            "node": {"hostname": "old-node", "architecture": "x86_64"},
+            "disks": []
+        }"#).unwrap();
+
+        assert_eq!(loaded.node.hostname, "old-node");
+        assert!(loaded.fans.is_empty());
+        assert!(loaded.collection_errors.is_empty());
+        assert!(loaded.collection_timings_ms.is_none());
+    }
+
+    #[test]
+    fn test_baseline_snapshot_loads() {
+        let loaded: Inventory = serde_json::from_str(include_str!("testdata/inventory_baseline.json")).unwrap();
+
+        assert_eq!(loaded.node.hostname, "gpu-node-07");
+        assert_eq!(loaded.memory.dimms.len(), 1);
+        assert_eq!(loaded.disks[0].dev_path, "/dev/nvme0n1");
+        assert_eq!(loaded.network.routes.len(), 2);
+        assert_eq!(loaded.network.routes[0].family, "");
+        assert_eq!(loaded.network.routes[0].gateway, "10.20.1.1");
+        assert!(loaded.network.routes[0].metric.is_none());
+        assert_eq!(loaded.network.interfaces[0].addresses.len(), 2);
+    }
+}
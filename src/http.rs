@@ -0,0 +1,251 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::hardware::types::Inventory;
+
+// Attempts for transient failures (connection errors, timeouts, 5xx) on keyed posts
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Join a FarmCore base URL and an API route, tolerating a trailing slash on
+/// the base and a missing leading slash on the route. A `--path` override
+/// replaces the configured `default_path`.
+pub fn api_url(base_url: &str, default_path: &str, path: Option<&str>) -> String {
+    let path = path.unwrap_or(default_path);
+    format!("{}/{}", base_url.trim_end_matches('/'), path.trim_start_matches('/'))
+}
+
+/// POST a JSON body to FarmCore using the configured timeout and token.
+/// Keyed posts retry transient failures with a short backoff; every attempt
+/// carries the same `Idempotency-Key` so FarmCore can drop duplicates. A POST
+/// without a key is sent once, since a retry could apply it twice.
+pub fn post_json<T: Serialize>(
+    config: &Config,
+    url: &str,
+    body: &T,
+    idempotency_key: Option<&str>,
+) -> Result<reqwest::blocking::Response, Box<dyn std::error::Error>> {
+    let client = build_client(config)?;
+    let max_attempts = if idempotency_key.is_some() { MAX_ATTEMPTS } else { 1 };
+
+    let mut attempt = 1;
+    loop {
+        let mut request = client.post(url).json(body);
+        if let Some(token) = &config.token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(key) = idempotency_key {
+            request = request.header("Idempotency-Key", key);
+        }
+
+        match request.send() {
+            Ok(response) if response.status().is_server_error() && attempt < max_attempts => {
+                eprintln!("  HTTP {} from {}, retrying ({}/{})...", response.status(), url, attempt, max_attempts);
+            }
+            Ok(response) => return Ok(response),
+            Err(e) if attempt < max_attempts => {
+                eprintln!("  Request to {} failed: {}, retrying ({}/{})...", url, e, attempt, max_attempts);
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        thread::sleep(Duration::from_secs(1 << (attempt - 1)));
+        attempt += 1;
+    }
+}
+
+/// HTTP client with the configured timeout and, when set, the proxy, mTLS
+/// identity, extra CA roots and `--insecure`. Without an explicit proxy,
+/// reqwest picks one up from HTTPS_PROXY/HTTP_PROXY/ALL_PROXY.
+fn build_client(config: &Config) -> Result<reqwest::blocking::Client, Box<dyn std::error::Error>> {
+    let mut builder = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .danger_accept_invalid_certs(config.insecure);
+
+    if let Some(proxy_url) = &config.proxy {
+        let proxy = reqwest::Proxy::all(proxy_url)
+            .map_err(|e| format!("Invalid proxy URL {}: {}", proxy_url, e))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+
+    match (&config.client_cert, &config.client_key) {
+        (Some(cert_path), Some(key_path)) => {
+            let cert = read_pem(cert_path, "client certificate")?;
+            let key = read_pem(key_path, "client key")?;
+            let identity = reqwest::Identity::from_pkcs8_pem(&cert, &key).map_err(|e| {
+                format!("Invalid client certificate {} / key {} (the key must be PKCS#8 PEM): {}", cert_path, key_path, e)
+            })?;
+            builder = builder.identity(identity);
+        }
+        (Some(_), None) => return Err("client_cert is set but client_key is not".into()),
+        (None, Some(_)) => return Err("client_key is set but client_cert is not".into()),
+        (None, None) => {}
+    }
+
+    if let Some(ca_path) = &config.ca_cert {
+        let bundle = read_pem(ca_path, "CA certificate")?;
+        let roots = reqwest::Certificate::from_pem_bundle(&bundle)
+            .map_err(|e| format!("Invalid CA certificate {}: {}", ca_path, e))?;
+        if roots.is_empty() {
+            return Err(format!("No certificates found in CA file {}", ca_path).into());
+        }
+        for root in roots {
+            builder = builder.add_root_certificate(root);
+        }
+    }
+
+    Ok(builder.build()?)
+}
+
+fn read_pem(path: &str, what: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    std::fs::read(path).map_err(|e| format!("Cannot read {} {}: {}", what, path, e).into())
+}
+
+/// Idempotency key for an inventory snapshot: `fm1-<16 hex digits>`, the
+/// FNV-1a 64-bit hash of `<node id>|<collected_at>`. The node id is the
+/// system serial (or hostname) for hardware inventory and the host MAC for VM
+/// inventory. FNV is used because it is stable across builds and Rust versions.
+pub fn idempotency_key(node_id: &str, collected_at: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in format!("{}|{}", node_id, collected_at).bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("fm1-{:016x}", hash)
+}
+
+pub fn inventory_idempotency_key(inventory: &Inventory) -> String {
+    let node_id = inventory.node.serial_number.as_deref().unwrap_or(&inventory.node.hostname);
+    idempotency_key(node_id, &inventory.collected_at)
+}
+
+/// A non-2xx FarmCore response, with the backend's own message when it sent one.
+#[derive(Debug)]
+pub struct FarmCoreError {
+    pub status: reqwest::StatusCode,
+    pub message: Option<String>,
+    pub code: Option<String>,
+    pub body: Option<String>, // raw response body, only kept in --verbose mode
+}
+
+impl fmt::Display for FarmCoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HTTP {}", self.status)?;
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        if let Some(code) = &self.code {
+            write!(f, " (code: {})", code)?;
+        }
+        if let Some(body) = &self.body {
+            write!(f, "\nResponse body:\n{}", body)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FarmCoreError {}
+
+/// Turn a FarmCore response into its JSON body, or a `FarmCoreError` for non-2xx statuses.
+pub fn check_response(
+    response: reqwest::blocking::Response,
+    config: &Config,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let status = response.status();
+    let text = response.text()?;
+
+    if status.is_success() {
+        return Ok(serde_json::from_str(&text).unwrap_or(Value::String(text)));
+    }
+
+    let (message, code) = match serde_json::from_str::<Value>(&text) {
+        Ok(json) => parse_error_body(&json),
+        // Not JSON (e.g. a proxy error page) - fall back to the first line of text
+        Err(_) => (text.lines().map(str::trim).find(|l| !l.is_empty()).map(str::to_string), None),
+    };
+
+    Err(Box::new(FarmCoreError {
+        status,
+        message,
+        code,
+        body: if config.verbose && !text.is_empty() { Some(text) } else { None },
+    }))
+}
+
+/// Extract a message and code from the common error shapes:
+/// `{"message": .., "code": ..}`, `{"error": ".."}`, `{"error": {"message": ..}}`,
+/// `{"detail": ..}` and field lists like `{"errors": [{"field": .., "message": ..}]}`.
+fn parse_error_body(json: &Value) -> (Option<String>, Option<String>) {
+    let obj = match json.get("error") {
+        Some(inner) if inner.is_object() => inner,
+        _ => json,
+    };
+
+    let as_text = |v: &Value| match v {
+        Value::String(s) => Some(s.clone()),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    };
+
+    let mut message = ["message", "error", "detail"]
+        .iter()
+        .find_map(|key| obj.get(*key).and_then(as_text));
+
+    if let Some(Value::Array(errors)) = obj.get("errors") {
+        let fields: Vec<String> = errors
+            .iter()
+            .filter_map(|e| {
+                let msg = e.get("message").and_then(as_text).or_else(|| as_text(e))?;
+                Some(match e.get("field").and_then(as_text) {
+                    Some(field) => format!("{}: {}", field, msg),
+                    None => msg,
+                })
+            })
+            .collect();
+        if !fields.is_empty() {
+            let joined = fields.join("; ");
+            message = Some(match message {
+                Some(m) => format!("{} ({})", m, joined),
+                None => joined,
+            });
+        }
+    }
+
+    (message, obj.get("code").and_then(as_text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_url() {
+        let default = "/api/v1/servers/inventory";
+
+        assert_eq!(api_url("https://farmcore:8080", default, None), "https://farmcore:8080/api/v1/servers/inventory");
+        assert_eq!(api_url("https://farmcore:8080/", default, None), "https://farmcore:8080/api/v1/servers/inventory");
+        assert_eq!(api_url("https://farmcore:8080", default, Some("/api/v2/nodes")), "https://farmcore:8080/api/v2/nodes");
+        assert_eq!(api_url("https://farmcore:8080/", default, Some("api/v2/nodes")), "https://farmcore:8080/api/v2/nodes");
+    }
+
+    #[test]
+    fn test_parse_error_body() {
+        let parse = |body: &str| parse_error_body(&serde_json::from_str(body).unwrap());
+        let text = |s: &str| Some(s.to_string());
+
+        assert_eq!(parse(r#"{"message": "Node not registered", "code": "NODE_UNKNOWN"}"#), (text("Node not registered"), text("NODE_UNKNOWN")));
+        assert_eq!(parse(r#"{"error": "unauthorized"}"#), (text("unauthorized"), None));
+        assert_eq!(parse(r#"{"error": {"message": "rate limited", "code": 429}}"#), (text("rate limited"), text("429")));
+        assert_eq!(parse(r#"{"detail": "Not Found"}"#), (text("Not Found"), None));
+        assert_eq!(
+            parse(r#"{"message": "Validation failed", "errors": [{"field": "node.hostname", "message": "is required"}, "disks: too many entries"]}"#),
+            (text("Validation failed (node.hostname: is required; disks: too many entries)"), None)
+        );
+        assert_eq!(parse(r#"{"errors": [{"message": "bad serial"}]}"#), (text("bad serial"), None));
+        assert_eq!(parse(r#"{"status": "error"}"#), (None, None));
+    }
+}
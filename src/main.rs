@@ -3,29 +3,197 @@ mod testing;
 mod cli;
 mod commands;
 mod output;
+mod config;
+mod http;
+mod version;
+mod util;
 
-use clap::Parser;
-use cli::{Cli, Commands};
+use clap::{CommandFactory, FromArgMatches};
+use cli::{Cli, Commands, HardwareCommands, TestArgs, TestCommands};
 use commands::{
     handle_hardware_command,
     handle_test_command,
     handle_vm_command,
     handle_k8s_command,
+    handle_version_command,
+    handle_agent_command,
+    handle_merge_command,
+    handle_diff_command,
+    handle_man_command,
 };
-use output::print_error;
+use config::Config;
+use output::{print_error, print_warning};
+
+const ROOT_WARNING: &str = "Running without root; memory/BIOS/SMART data may be incomplete. Re-run with sudo for full inventory.";
 
 fn main() {
-    let cli = Cli::parse();
+    // Parse once without the config so --help and usage errors never depend on it,
+    // then again with the config supplying the --url/--format defaults
+    let matches = Cli::command().get_matches();
+    let mut config = match Config::load(matches.get_one::<String>("config").map(String::as_str)) {
+        Ok(config) => config,
+        Err(e) => {
+            print_error(&e.to_string());
+            std::process::exit(1);
+        }
+    };
+    let matches = config.apply_defaults(Cli::command()).get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    output::set_color(cli.color);
+    // Progress lines would corrupt json/yaml meant for automation
+    output::set_quiet(cli.quiet || output_format(&matches).is_some_and(|format| format != "pretty"));
+    config.verbose = cli.verbose;
+    util::set_verbose(cli.verbose);
+    if cli.client_cert.is_some() {
+        config.client_cert = cli.client_cert.clone();
+        config.client_key = cli.client_key.clone();
+    }
+    if cli.ca_cert.is_some() {
+        config.ca_cert = cli.ca_cert.clone();
+    }
+    if cli.proxy.is_some() {
+        config.proxy = cli.proxy.clone();
+    }
+    if cli.insecure {
+        // Deliberately not silenced by --quiet
+        print_warning("--insecure: TLS certificate verification is DISABLED; FarmCore's identity is not checked");
+        config.insecure = true;
+    }
+
+    // The rest read only this machine, so a remote BMC would be silently ignored
+    if cli.bmc_host.is_some() && !is_bmc_command(&cli.command) {
+        print_error("--bmc-host only applies to hardware inventory, post-inventory, node, power, fans, sel, power-cap, chassis and boot-device");
+        std::process::exit(1);
+    }
+    if cli.bmc_host.is_some() || cli.bmc_interface.is_some() {
+        hardware::set_ipmi_target(hardware::IpmiTarget {
+            host: cli.bmc_host.clone(),
+            user: cli.bmc_user.clone(),
+            password: cli.bmc_pass.clone(),
+            interface: cli.bmc_interface.clone(),
+        });
+    }
+
+    if let Some(template) = &cli.output {
+        output::set_output_file(template, &command_name(&matches));
+    }
+
+    if let Commands::Test(TestArgs { results_dir: Some(dir), .. }) = &cli.command {
+        output::set_results_dir(dir, &command_name(&matches));
+    }
+
+    if let Some(query) = &cli.query {
+        if let Err(e) = output::set_query(query) {
+            print_error(&e);
+            std::process::exit(1);
+        }
+    }
+
+    if needs_root(&cli) && !util::is_root() {
+        if cli.require_root {
+            print_error("This command needs root (--require-root). Re-run with sudo.");
+            std::process::exit(1);
+        }
+        if !cli.quiet {
+            print_warning(ROOT_WARNING);
+        }
+    }
 
     let result = match &cli.command {
-        Commands::Hardware(cmd) => handle_hardware_command(cmd),
-        Commands::Test(cmd) => handle_test_command(cmd),
-        Commands::Vm(cmd) => handle_vm_command(cmd),
+        Commands::Hardware(cmd) => handle_hardware_command(cmd, &config),
+        Commands::Test(args) => handle_test_command(&args.command),
+        Commands::Vm(cmd) => handle_vm_command(cmd, &config),
         Commands::K8s(cmd) => handle_k8s_command(cmd),
+        Commands::Version { format } => handle_version_command(format),
+        Commands::Merge { inputs, summary, format } => handle_merge_command(inputs, *summary, format),
+        Commands::Diff { before, after, fail_on_change, format } => {
+            handle_diff_command(before, after, *fail_on_change, format)
+        }
+        Commands::Man { dir } => handle_man_command(dir.as_deref()),
+        #[cfg(feature = "schema")]
+        Commands::Schema => commands::schema::handle_schema_command(),
+        Commands::Agent { url, hypervisor, skip_vms, path, vm_path } => {
+            handle_agent_command(url, *hypervisor, *skip_vms, path.as_deref(), vm_path.as_deref(), &config)
+        }
     };
 
     if let Err(e) = result {
-        print_error(&e.to_string());
+        output::save_error(&e.to_string());
+        match output_format(&matches).as_deref() {
+            Some("json" | "ndjson") => output::print_error_json(&e.to_string(), &command_name(&matches)),
+            _ => print_error(&e.to_string()),
+        }
         std::process::exit(1);
     }
+}
+
+// Subcommand path for the {command} output token, e.g. "hardware-inventory"
+fn command_name(matches: &clap::ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    names.join("-")
+}
+
+// --format of the invoked subcommand, for commands that have one
+fn output_format(matches: &clap::ArgMatches) -> Option<String> {
+    let mut current = matches;
+    while let Some((_, sub)) = current.subcommand() {
+        current = sub;
+    }
+    current.try_get_one::<String>("format").ok().flatten().cloned()
+}
+
+// Commands with IPMI-backed collectors. With a remote BMC they read only
+// from it and skip the sources local to this machine.
+fn is_bmc_command(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Hardware(
+            HardwareCommands::Inventory { .. }
+                | HardwareCommands::PostInventory { .. }
+                | HardwareCommands::Node { .. }
+                | HardwareCommands::Power { .. }
+                | HardwareCommands::Fans { .. }
+                | HardwareCommands::Sel { .. }
+                | HardwareCommands::PowerCap { .. }
+                | HardwareCommands::Chassis { .. }
+                | HardwareCommands::BootDevice { .. }
+        )
+    )
+}
+
+// Commands whose collectors read root-only sources (SMBIOS, smartctl, ipmitool, dmidecode)
+fn needs_root(cli: &Cli) -> bool {
+    // A remote BMC is reached over the network; only in-band IPMI needs root
+    if cli.bmc_host.is_some() && is_bmc_command(&cli.command) {
+        return false;
+    }
+
+    match &cli.command {
+        Commands::Hardware(cmd) => matches!(
+            cmd,
+            HardwareCommands::Inventory { .. }
+                | HardwareCommands::PostInventory { .. }
+                | HardwareCommands::Cpu { .. }
+                | HardwareCommands::Memory { .. }
+                | HardwareCommands::Storage { .. }
+                | HardwareCommands::Node { .. }
+                | HardwareCommands::Power { .. }
+                | HardwareCommands::PowerCap { .. }
+                | HardwareCommands::Fans { .. }
+                | HardwareCommands::Dmi { .. }
+                | HardwareCommands::Sel { .. }
+                | HardwareCommands::LocateDisk { .. }
+                | HardwareCommands::DiskTest { .. }
+                | HardwareCommands::Chassis { .. }
+                | HardwareCommands::BootDevice { .. }
+        ),
+        Commands::Test(TestArgs { command: TestCommands::GpuConfig { .. }, .. }) => true,
+        Commands::Agent { .. } => true,
+        _ => false,
+    }
 }
\ No newline at end of file
@@ -1,34 +1,280 @@
 use serde::Serialize;
+use serde_json_path::JsonPath;
+use std::fs::{self, OpenOptions};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
 
-pub fn output_data<T: Serialize>(data: &T, format: &str) -> Result<(), Box<dyn std::error::Error>> {
-    match format {
-        "json" => {
-            println!("{}", serde_json::to_string_pretty(data)?);
+use crate::cli::ColorChoice;
+
+// Set from --output; when present, output_data writes here instead of stdout
+static OUTPUT_PATH: OnceLock<String> = OnceLock::new();
+static OUTPUT_STARTED: AtomicBool = AtomicBool::new(false);
+// Resolved from --color; messages printed before parsing use the auto rule
+static COLOR: OnceLock<bool> = OnceLock::new();
+// Set from --quiet; silences status!, print_success and print_info
+static QUIET: AtomicBool = AtomicBool::new(false);
+// Set from --query; output_data prints only what it selects
+static QUERY: OnceLock<JsonPath> = OnceLock::new();
+// Set from `test --results-dir`; output_data also keeps a copy of each result there
+static RESULTS_DIR: OnceLock<(PathBuf, String)> = OnceLock::new();
+static RESULT_SAVED: AtomicBool = AtomicBool::new(false);
+
+/// A saved test result: the command's full output, or the error it failed
+/// with, plus where and when it ran.
+#[derive(Serialize)]
+struct ResultRecord<'a, T: Serialize> {
+    timestamp: String,
+    hostname: Option<String>,
+    command: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<&'a T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+}
+
+/// `println!` for progress and confirmation chatter ("Creating VM...",
+/// "✓ Done"); --quiet drops it so only data and errors are printed.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            println!($($arg)*);
         }
-        "yaml" => {
-            println!("{}", serde_yaml::to_string(data)?);
+    };
+}
+pub(crate) use status;
+
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Filter every subsequent `output_data` result through the JSONPath `expr`.
+pub fn set_query(expr: &str) -> Result<(), String> {
+    let _ = QUERY.set(parse_query(expr)?);
+    Ok(())
+}
+
+fn parse_query(expr: &str) -> Result<JsonPath, String> {
+    JsonPath::parse(expr).map_err(|e| format!("Invalid --query '{}': {}", expr, e))
+}
+
+/// Save every subsequent `output_data` result of `command` (e.g. "test-nccl-test")
+/// as its own JSON file in `dir`, named like `20260101T120000Z-nccl-test.json`.
+pub fn set_results_dir(dir: &str, command: &str) {
+    let _ = RESULTS_DIR.set((PathBuf::from(dir), command.to_string()));
+}
+
+/// Record a failed test in the results directory, unless it already saved a
+/// result before failing.
+pub fn save_error(message: &str) {
+    let Some((dir, command)) = RESULTS_DIR.get() else {
+        return;
+    };
+    if RESULT_SAVED.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Err(e) = save_result::<()>(dir, command, None, Some(message)) {
+        print_warning(&e.to_string());
+    }
+}
+
+/// Write one record as `<timestamp>-<test>.json`. Millisecond timestamps keep
+/// back-to-back runs apart; a counter suffix covers anything closer than that.
+fn save_result<T: Serialize>(dir: &Path, command: &str, result: Option<&T>, error: Option<&str>) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create results directory {}: {}", dir.display(), e))?;
+
+    let now = chrono::Utc::now();
+    let test = command.strip_prefix("test-").unwrap_or(command);
+    let stem = format!("{}-{}", now.format("%Y%m%dT%H%M%S%.3fZ"), test);
+    let record = ResultRecord {
+        timestamp: now.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        hostname: sysinfo::System::host_name(),
+        command,
+        result,
+        error,
+    };
+    let text = serde_json::to_string_pretty(&record)?;
+
+    for n in 0.. {
+        let path = match n {
+            0 => dir.join(format!("{}.json", stem)),
+            n => dir.join(format!("{}-{}.json", stem, n)),
+        };
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(text.as_bytes())
+                    .map_err(|e| format!("Failed to write result file {}: {}", path.display(), e))?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("Failed to write result file {}: {}", path.display(), e).into()),
         }
-        "pretty" | _ => {
-            println!("{}", serde_json::to_string_pretty(data)?);
+    }
+    unreachable!()
+}
+
+pub fn output_data<T: Serialize>(data: &T, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // The saved copy is the whole result, whatever --query selects for display
+    if let Some((dir, command)) = RESULTS_DIR.get() {
+        save_result(dir, command, Some(data), None)?;
+        RESULT_SAVED.store(true, Ordering::Relaxed);
+    }
+
+    let text = apply_query(QUERY.get(), data, format)?;
+
+    match OUTPUT_PATH.get() {
+        Some(path) => write_output_file(path, &text),
+        None => {
+            println!("{}", text);
+            Ok(())
         }
     }
+}
+
+/// For output printed as a tool gave it instead of through [`output_data`],
+/// which --query has nothing to select from.
+pub fn require_structured_output() -> Result<(), Box<dyn std::error::Error>> {
+    if QUERY.get().is_some() {
+        return Err("--query needs structured output (use -f json/yaml)".into());
+    }
     Ok(())
 }
 
+/// Render `data`, or with a query, the array of every node it matches
+/// (`[]` when nothing does).
+fn apply_query<T: Serialize>(query: Option<&JsonPath>, data: &T, format: &str) -> Result<String, Box<dyn std::error::Error>> {
+    match query {
+        Some(query) => {
+            let value = serde_json::to_value(data)?;
+            render(&query.query(&value).all(), format)
+        }
+        None => render(data, format),
+    }
+}
+
+fn render<T: Serialize>(data: &T, format: &str) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(match format {
+        "json" => serde_json::to_string_pretty(data)?,
+        "ndjson" => serde_json::to_string(data)?, // one record per line, for watch modes
+        "yaml" => serde_yaml::to_string(data)?,
+        "pretty" | _ => serde_json::to_string_pretty(data)?,
+    })
+}
+
+/// Whether `output_data` is printing to an interactive terminal, so a watch
+/// mode can redraw the screen instead of scrolling.
+pub fn output_is_terminal() -> bool {
+    OUTPUT_PATH.get().is_none() && std::io::stdout().is_terminal()
+}
+
+/// Send all subsequent `output_data` results to `template`, expanding
+/// `{hostname}`, `{serial}`, `{date}` and `{command}` (e.g. `/data/{hostname}-inventory.json`).
+pub fn set_output_file(template: &str, command: &str) {
+    let _ = OUTPUT_PATH.set(expand_output_template(template, command));
+}
+
+fn expand_output_template(template: &str, command: &str) -> String {
+    let mut path = template
+        .replace("{command}", command)
+        .replace("{date}", &chrono::Local::now().format("%Y-%m-%d").to_string());
+
+    if path.contains("{hostname}") {
+        let hostname = sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string());
+        path = path.replace("{hostname}", &sanitize_path_token(&hostname));
+    }
+
+    // The serial needs SMBIOS, so only collect node info when the token asks for it
+    if path.contains("{serial}") {
+        let serial = crate::hardware::collect_node_info().serial_number.unwrap_or_else(|| "unknown".to_string());
+        path = path.replace("{serial}", &sanitize_path_token(&serial));
+    }
+
+    path
+}
+
+// Keep node-provided values from introducing path separators
+fn sanitize_path_token(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect()
+}
+
+fn write_output_file(path: &str, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = Path::new(path).parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+
+    // First write of the run truncates; later ones (watch modes) append
+    let first = !OUTPUT_STARTED.swap(true, Ordering::SeqCst);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(first)
+        .append(!first)
+        .open(path)
+        .map_err(|e| format!("Failed to open output file {}: {}", path, e))?;
+    writeln!(file, "{}", text)?;
+
+    if first {
+        status!("✓ Output written to {}", path);
+    }
+    Ok(())
+}
+
+/// Decide once whether error and warning messages get ANSI colors.
+/// An explicit `always`/`never` wins over NO_COLOR, as https://no-color.org asks.
+pub fn set_color(choice: ColorChoice) {
+    let _ = COLOR.set(match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => auto_color(),
+    });
+}
+
+// Errors and warnings go to stderr, so that's the stream that must be a terminal
+fn auto_color() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && std::io::stderr().is_terminal()
+}
+
+// Wrap `text` in the ANSI color `code` when color is enabled
+fn paint(code: &str, text: &str) -> String {
+    if *COLOR.get_or_init(auto_color) {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
+
 pub fn print_success(message: &str) {
-    println!("✅ {}", message);
+    status!("✅ {}", message);
 }
 
 pub fn print_error(message: &str) {
-    eprintln!("\x1b[31m❌ Error: {}\x1b[0m", message);
+    eprintln!("{}", paint("31", &format!("❌ Error: {}", message)));
 }
 
+/// Report a failure as one line of JSON on stderr, for callers that asked
+/// for `--format json` and parse errors the same way as data.
+pub fn print_error_json(message: &str, command: &str) {
+    let error = serde_json::json!({ "error": { "message": message, "command": command } });
+    eprintln!("{}", error);
+}
+
+/// Writes to stderr so data on stdout stays parseable.
 pub fn print_warning(message: &str) {
-    println!("\x1b[33m⚠️  Warning: {}\x1b[0m", message);
+    eprintln!("{}", paint("33", &format!("⚠️  Warning: {}", message)));
 }
 
 pub fn print_info(message: &str) {
-    println!("ℹ️  {}", message);
+    status!("ℹ️  {}", message);
 }
 
 pub fn confirm_action(message: &str) -> bool {
@@ -41,4 +287,93 @@ pub fn confirm_action(message: &str) -> bool {
     io::stdin().read_line(&mut input).unwrap();
     
     matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("farm-manager-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn inventory() -> serde_json::Value {
+        serde_json::json!({
+            "node": {"hostname": "gpu-a"},
+            "gpus": [{"model": "H100 SXM5 80GB", "vram_mb": 81559}, {"model": "L40S", "vram_mb": 46068}],
+            "disks": [{"name": "nvme0n1", "size_gb": 3840}, {"name": "sda", "size_gb": 64}]
+        })
+    }
+
+    fn query(expr: &str, format: &str) -> String {
+        apply_query(Some(&parse_query(expr).unwrap()), &inventory(), format).unwrap()
+    }
+
+    #[test]
+    fn test_parse_query_error() {
+        let err = parse_query("$.gpus[").unwrap_err();
+        assert!(err.starts_with("Invalid --query '$.gpus[': "), "{}", err);
+    }
+
+    #[test]
+    fn test_apply_query() {
+        assert_eq!(query("$.gpus[*].model", "ndjson"), r#"["H100 SXM5 80GB","L40S"]"#);
+        assert_eq!(query("$.disks[?@.size_gb > 100].name", "ndjson"), r#"["nvme0n1"]"#);
+        assert_eq!(query("$.node.hostname", "yaml"), "- gpu-a\n");
+        assert_eq!(query("$.fans[*]", "json"), "[]");
+        assert_eq!(apply_query(None, &inventory(), "ndjson").unwrap(), serde_json::to_string(&inventory()).unwrap());
+    }
+
+    #[test]
+    fn test_expand_output_template() {
+        // Dates from both sides of the call, in case it runs across midnight
+        let today = || chrono::Local::now().format("%Y-%m-%d").to_string();
+        let before = today();
+        let path = expand_output_template("/var/log/fm/{command}-{date}.json", "hardware-inventory");
+        let after = today();
+        assert!(
+            [before, after].iter().any(|date| path == format!("/var/log/fm/hardware-inventory-{}.json", date)),
+            "unexpected path {}",
+            path
+        );
+        assert_eq!(expand_output_template("out.json", "version"), "out.json");
+
+        let hostname = sanitize_path_token(&sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string()));
+        assert_eq!(expand_output_template("{hostname}/{command}.json", "vm-list"), format!("{}/vm-list.json", hostname));
+    }
+
+    #[test]
+    fn test_sanitize_path_token() {
+        assert_eq!(sanitize_path_token(" gpu-node-01.lab "), "gpu-node-01.lab");
+        assert_eq!(sanitize_path_token("../etc/passwd"), ".._etc_passwd");
+        assert_eq!(sanitize_path_token("SN 1234/A"), "SN_1234_A");
+    }
+
+    #[test]
+    fn test_save_result_keeps_runs_apart() {
+        let dir = results_dir("results");
+        let first = save_result(&dir, "test-gpu-health", Some(&serde_json::json!({"ok": true})), None).unwrap();
+        let second = save_result(&dir, "test-gpu-health", Some(&serde_json::json!({"ok": false})), None).unwrap();
+
+        assert_ne!(first, second);
+        assert!(first.file_name().unwrap().to_string_lossy().ends_with("-gpu-health.json"));
+        let record: serde_json::Value = serde_json::from_str(&fs::read_to_string(&second).unwrap()).unwrap();
+        assert_eq!(record["command"], "test-gpu-health");
+        assert_eq!(record["result"]["ok"], false);
+        assert!(record.get("error").is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_save_result_records_errors() {
+        let dir = results_dir("errors");
+        let path = save_result::<()>(&dir, "test-dcgm-diag", None, Some("dcgmi not found")).unwrap();
+
+        let record: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(record["error"], "dcgmi not found");
+        assert!(record.get("result").is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::hardware::collect_temperatures;
+use crate::hardware::tool_on_path;
+use crate::hardware::types::CpuTestResult;
+use crate::util::{run_capture, sample_while};
+use std::hint::black_box;
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// hwmon chips that report CPU package/core/die temperatures
+const CPU_TEMP_SOURCES: &[&str] = &["coretemp", "k10temp", "zenpower", "cpu_thermal"];
+
+/// Load every requested CPU thread for `duration_secs` with stress-ng, or
+/// sysbench, or a built-in integer loop when neither is installed, sampling
+/// CPU temperatures once a second while it runs.
+pub fn run_cpu_test(duration_secs: u64, threads: Option<u32>) -> Result<CpuTestResult, Box<dyn std::error::Error>> {
+    let threads = threads.unwrap_or_else(|| {
+        thread::available_parallelism().map(|n| n.get() as u32).unwrap_or(1)
+    });
+    let method = ["stress-ng", "sysbench"]
+        .into_iter()
+        .find(|tool| tool_on_path(tool))
+        .unwrap_or("builtin");
+    
+    let mut result = CpuTestResult {
+        method: method.to_string(),
+        threads,
+        requested_seconds: duration_secs,
+        elapsed_seconds: 0.0,
+        success: false,
+        events_per_sec: None,
+        total_events: None,
+        samples: 0,
+        peak_temperature_celsius: None,
+        error: None,
+    };
+    
+    let start_time = Instant::now();
+    let (mut samples, mut peak) = (0u32, None::<f64>);
+    
+    let outcome = sample_while(
+        Duration::from_secs(1),
+        || {
+            let hottest = collect_temperatures()
+                .into_iter()
+                .filter(|t| CPU_TEMP_SOURCES.contains(&t.source.as_str()))
+                .filter_map(|t| t.value_c)
+                .reduce(f64::max);
+            if let Some(hottest) = hottest {
+                samples += 1;
+                peak = Some(peak.map_or(hottest, |p| p.max(hottest)));
+            }
+        },
+        || match method {
+            "stress-ng" => run_stress_ng(duration_secs, threads),
+            "sysbench" => run_sysbench(duration_secs, threads),
+            _ => Ok(run_builtin(duration_secs, threads)),
+        },
+    );
+    
+    result.elapsed_seconds = start_time.elapsed().as_secs_f64();
+    result.samples = samples;
+    result.peak_temperature_celsius = peak;
+    match outcome {
+        Ok((events_per_sec, total_events)) => {
+            result.success = true;
+            result.events_per_sec = Some(events_per_sec);
+            result.total_events = total_events;
+        }
+        Err(e) => result.error = Some(e),
+    }
+    
+    Ok(result)
+}
+
+/// `stress-ng --cpu` with brief metrics; events are its bogo ops.
+fn run_stress_ng(duration_secs: u64, threads: u32) -> Result<(f64, Option<u64>), String> {
+    let output = run_capture(Command::new("stress-ng").args([
+        "--cpu", &threads.to_string(),
+        "--timeout", &format!("{}s", duration_secs),
+        "--metrics-brief",
+    ])).map_err(|e| format!("Failed to run stress-ng: {}", e))?;
+    if !output.success {
+        return Err(format!("stress-ng failed: {}", output.stderr.trim()));
+    }
+    parse_stress_ng_metrics(&output.combined())
+        .map(|(ops, ops_per_sec)| (ops_per_sec, Some(ops)))
+        .ok_or_else(|| "No cpu metrics in stress-ng output".to_string())
+}
+
+/// `sysbench cpu`, which counts prime-search events.
+fn run_sysbench(duration_secs: u64, threads: u32) -> Result<(f64, Option<u64>), String> {
+    let output = run_capture(Command::new("sysbench").args([
+        "cpu",
+        &format!("--threads={}", threads),
+        &format!("--time={}", duration_secs),
+        "run",
+    ])).map_err(|e| format!("Failed to run sysbench: {}", e))?;
+    if !output.success {
+        return Err(format!("sysbench failed: {}", output.stderr.trim()));
+    }
+    let events_per_sec = sysbench_value(&output.stdout, "events per second:")
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| "No events per second in sysbench output".to_string())?;
+    let total_events = sysbench_value(&output.stdout, "total number of events:").and_then(|v| v.parse().ok());
+    Ok((events_per_sec, total_events))
+}
+
+/// Integer busy loop on every thread; one event is a fixed block of
+/// multiply/xorshift rounds. Only comparable with other builtin runs.
+fn run_builtin(duration_secs: u64, threads: u32) -> (f64, Option<u64>) {
+    const ROUNDS_PER_EVENT: u32 = 10_000;
+    let deadline = Instant::now() + Duration::from_secs(duration_secs);
+    let events = AtomicU64::new(0);
+    let start = Instant::now();
+    
+    thread::scope(|scope| {
+        for seed in 0..threads {
+            let events = &events;
+            scope.spawn(move || {
+                let mut x = u64::from(seed) | 1;
+                while Instant::now() < deadline {
+                    for _ in 0..ROUNDS_PER_EVENT {
+                        x ^= x << 13;
+                        x ^= x >> 7;
+                        x ^= x << 17;
+                        x = x.wrapping_mul(0x2545_f491_4f6c_dd1d);
+                    }
+                    black_box(x);
+                    events.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    
+    let total = events.load(Ordering::Relaxed);
+    (total as f64 / start.elapsed().as_secs_f64().max(f64::EPSILON), Some(total))
+}
+
+/// Parse the cpu row of `--metrics-brief`:
+/// stress-ng: metrc: [4242] stressor  bogo ops real time  usr time  sys time   bogo ops/s     bogo ops/s
+/// stress-ng: metrc: [4242] cpu         612345     10.00     79.91      0.04     61234.12        7659.07
+/// Returns (bogo ops, bogo ops/s in real time).
+fn parse_stress_ng_metrics(output: &str) -> Option<(u64, f64)> {
+    output.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let cpu = fields.iter().position(|f| *f == "cpu")?;
+        let ops = fields.get(cpu + 1)?.parse().ok()?;
+        let ops_per_sec = fields.get(cpu + 5)?.parse().ok()?;
+        Some((ops, ops_per_sec))
+    })
+}
+
+fn sysbench_value<'a>(output: &'a str, label: &str) -> Option<&'a str> {
+    output.lines().find_map(|line| line.trim().strip_prefix(label).map(str::trim))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stress_ng_metrics() {
+        let output = "\
+stress-ng: info:  [4242] setting to a 10 secs run per stressor
+stress-ng: info:  [4242] dispatching hogs: 8 cpu
+stress-ng: metrc: [4242] stressor       bogo ops real time  usr time  sys time   bogo ops/s     bogo ops/s
+stress-ng: metrc: [4242]                           (secs)    (secs)    (secs)   (real time) (usr+sys time)
+stress-ng: metrc: [4242] cpu              612345     10.00     79.91      0.04     61234.12        7659.07
+stress-ng: info:  [4242] successful run completed in 10.02 secs
+";
+        assert_eq!(parse_stress_ng_metrics(output), Some((612345, 61234.12)));
+        assert_eq!(parse_stress_ng_metrics("stress-ng: info:  [4242] dispatching hogs: 8 cpu\n"), None);
+    }
+
+    #[test]
+    fn test_sysbench_value() {
+        let output = "\
+CPU speed:
+    events per second:  9870.41
+
+General statistics:
+    total time:                          10.0004s
+    total number of events:              98711
+";
+        assert_eq!(sysbench_value(output, "events per second:"), Some("9870.41"));
+        assert_eq!(sysbench_value(output, "total number of events:"), Some("98711"));
+    }
+}
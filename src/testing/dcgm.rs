@@ -1,5 +1,11 @@
-use crate::hardware::types::{DcgmInfo, DcgmDiagResult, DcgmGpuDiagResult, DcgmHealthCheck, DcgmIncident};
+use crate::hardware::tool_on_path;
+use crate::hardware::types::{DcgmInfo, DcgmDiagResult, DcgmGpuDiagResult, DcgmHealthCheck, DcgmIncident, HealthStatus};
+use crate::util::{run_capture, run_capture_timeout};
 use std::process::Command;
+use std::time::Duration;
+
+/// How long dcgmi gets to abort a diag that hit --max-runtime before it is killed.
+const DIAG_ABORT_GRACE: Duration = Duration::from_secs(30);
 
 /// Get DCGM installation information and version
 pub fn collect_dcgm_info() -> DcgmInfo {
@@ -14,19 +20,16 @@ pub fn collect_dcgm_info() -> DcgmInfo {
     };
     
     // Check if dcgmi is available
-    if let Ok(output) = Command::new("which").arg("dcgmi").output() {
-        if !output.status.success() {
-            info.error = Some("dcgmi not found. Please install DCGM (Data Center GPU Manager).".to_string());
-            return info;
-        }
-        info.dcgmi_available = true;
+    if !tool_on_path("dcgmi") {
+        info.error = Some("dcgmi not found. Please install DCGM (Data Center GPU Manager).".to_string());
+        return info;
     }
+    info.dcgmi_available = true;
     
     // Get DCGM version
-    if let Ok(output) = Command::new("dcgmi").arg("--version").output() {
-        if output.status.success() {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            if let Some(version) = parse_dcgm_version(&output_str) {
+    if let Ok(output) = run_capture(Command::new("dcgmi").arg("--version")) {
+        if output.success {
+            if let Some(version) = parse_dcgm_version(&output.stdout) {
                 info.dcgm_version = Some(version);
                 info.dcgm_available = true;
             }
@@ -35,28 +38,25 @@ pub fn collect_dcgm_info() -> DcgmInfo {
     
     // Get GPU count and driver version using dcgmi discovery
     if info.dcgmi_available {
-        if let Ok(output) = Command::new("dcgmi").arg("discovery").arg("-l").output() {
-            if output.status.success() {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                info.num_gpus = count_gpus_in_discovery(&output_str);
+        if let Ok(output) = run_capture(Command::new("dcgmi").args(["discovery", "-l"])) {
+            if output.success {
+                info.num_gpus = count_gpus_in_discovery(&output.stdout);
             }
         }
         
         // Get driver version
-        if let Ok(output) = Command::new("nvidia-smi").arg("--query-gpu=driver_version").arg("--format=csv,noheader").output() {
-            if output.status.success() {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                if let Some(version) = output_str.lines().next() {
+        if let Ok(output) = run_capture(Command::new("nvidia-smi").args(["--query-gpu=driver_version", "--format=csv,noheader"])) {
+            if output.success {
+                if let Some(version) = output.stdout.lines().next() {
                     info.driver_version = Some(version.trim().to_string());
                 }
             }
         }
         
         // Get CUDA driver version
-        if let Ok(output) = Command::new("nvidia-smi").output() {
-            if output.status.success() {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                if let Some(version) = parse_cuda_version(&output_str) {
+        if let Ok(output) = run_capture(&mut Command::new("nvidia-smi")) {
+            if output.success {
+                if let Some(version) = parse_cuda_version(&output.stdout) {
                     info.cuda_driver_version = Some(version);
                 }
             }
@@ -131,25 +131,23 @@ fn parse_cuda_version(output: &str) -> Option<String> {
 /// 
 /// Note: This command will create NVVS (NVIDIA Validation Suite) log files
 /// in the current directory as DCGM uses NVVS as its underlying diagnostic engine.
-pub fn run_dcgm_diag(level: u32, gpu_ids: Option<Vec<u32>>) 
+pub fn run_dcgm_diag(level: u32, gpu_ids: Option<Vec<u32>>, max_runtime_secs: Option<u64>) 
     -> Result<DcgmDiagResult, Box<dyn std::error::Error>> {
     
     let mut result = DcgmDiagResult {
         test_name: format!("DCGM Diagnostics Level {}", level),
         success: false,
         gpu_results: Vec::new(),
-        overall_result: "Unknown".to_string(),
+        status: HealthStatus::Unknown,
+        raw_status: "Unknown".to_string(),
         time_seconds: None,
+        truncated: false,
         error: None,
         raw_output: None,
     };
     
     // Check if dcgmi is available
-    if !Command::new("which")
-        .arg("dcgmi")
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+    if !tool_on_path("dcgmi")
     {
         result.error = Some("dcgmi not found. Please install DCGM.".to_string());
         return Ok(result);
@@ -179,16 +177,28 @@ pub fn run_dcgm_diag(level: u32, gpu_ids: Option<Vec<u32>>)
         }
     }
     
-    // Run the diagnostic
+    // Run the diagnostic; levels 3 and 4 can take hours, so honor the cap.
+    // The tests run inside nv-hostengine, so killing dcgmi alone would leave
+    // them going; on SIGINT dcgmi asks the host engine to stop the diag first.
     let start_time = std::time::Instant::now();
-    let output = cmd.output()?;
+    let output = match max_runtime_secs {
+        Some(secs) => run_capture_timeout(&mut cmd, Duration::from_secs(secs), DIAG_ABORT_GRACE)?,
+        None => run_capture(&mut cmd)?,
+    };
     let elapsed = start_time.elapsed().as_secs_f64();
     
     result.time_seconds = Some(elapsed);
+    result.truncated = output.timed_out;
+    result.raw_output = Some(output.combined());
     
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    let error_str = String::from_utf8_lossy(&output.stderr);
-    result.raw_output = Some(format!("{}\n{}", output_str, error_str));
+    let output_str = &output.stdout;
+    let error_str = &output.stderr;
+    
+    if result.truncated {
+        result.error = Some(format!("Diagnostic killed after reaching --max-runtime of {}s", max_runtime_secs.unwrap_or_default()));
+        result.raw_status = "Truncated".to_string();
+        return Ok(result);
+    }
     
     // Check for errors in stdout (DCGM often reports errors there)
     let has_stdout_error = output_str.to_lowercase().contains("error") 
@@ -196,13 +206,13 @@ pub fn run_dcgm_diag(level: u32, gpu_ids: Option<Vec<u32>>)
         || output_str.to_lowercase().contains("failed");
     
     // Parse results
-    if output.status.success() && !has_stdout_error {
+    if output.success && !has_stdout_error {
         result.success = true;
-        parse_diag_results(&output_str, &mut result);
+        parse_diag_results(output_str, &mut result);
     } else {
         // Capture error from either stdout or stderr
         let error_msg = if !error_str.trim().is_empty() {
-            error_str.to_string()
+            error_str.clone()
         } else if has_stdout_error {
             // Extract error lines from stdout
             output_str.lines()
@@ -217,7 +227,8 @@ pub fn run_dcgm_diag(level: u32, gpu_ids: Option<Vec<u32>>)
         };
         
         result.error = Some(format!("Diagnostic failed: {}", error_msg.trim()));
-        result.overall_result = "Fail".to_string();
+        result.status = HealthStatus::Critical;
+        result.raw_status = "Fail".to_string();
     }
     
     Ok(result)
@@ -235,15 +246,8 @@ fn parse_diag_results(output: &str, result: &mut DcgmDiagResult) {
         if trimmed.contains("Overall Result:") || trimmed.contains("Result:") {
             if let Some(result_part) = trimmed.split(':').nth(1) {
                 let status = result_part.trim();
-                if status.to_lowercase().contains("pass") {
-                    result.overall_result = "Pass".to_string();
-                } else if status.to_lowercase().contains("fail") {
-                    result.overall_result = "Fail".to_string();
-                } else if status.to_lowercase().contains("warn") {
-                    result.overall_result = "Warning".to_string();
-                } else {
-                    result.overall_result = status.to_string();
-                }
+                result.status = HealthStatus::from_raw(status);
+                result.raw_status = status.to_string();
             }
         }
         
@@ -272,7 +276,8 @@ fn parse_diag_results(output: &str, result: &mut DcgmDiagResult) {
                 result.gpu_results.push(DcgmGpuDiagResult {
                     device_index: idx,
                     device_name: current_gpu_name.clone(),
-                    result: gpu_result.to_string(),
+                    status: HealthStatus::from_raw(gpu_result),
+                    raw_status: gpu_result.to_string(),
                     info: Some(trimmed.to_string()),
                 });
                 
@@ -283,39 +288,31 @@ fn parse_diag_results(output: &str, result: &mut DcgmDiagResult) {
     }
     
     // If no overall result was found but all GPU results passed
-    if result.overall_result == "Unknown" && !result.gpu_results.is_empty() {
-        let all_pass = result.gpu_results.iter().all(|r| r.result == "Pass");
-        result.overall_result = if all_pass { "Pass".to_string() } else { "Fail".to_string() };
+    if result.status == HealthStatus::Unknown && !result.gpu_results.is_empty() {
+        let all_pass = result.gpu_results.iter().all(|r| r.status == HealthStatus::Ok);
+        let raw_status = if all_pass { "Pass" } else { "Fail" };
+        result.status = HealthStatus::from_raw(raw_status);
+        result.raw_status = raw_status.to_string();
     }
 }
 
 /// Run DCGM health check
 pub fn run_dcgm_health_check() -> Result<Vec<DcgmHealthCheck>, Box<dyn std::error::Error>> {
     // Check if dcgmi is available
-    if !Command::new("which")
-        .arg("dcgmi")
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+    if !tool_on_path("dcgmi")
     {
         return Err("dcgmi not found. Please install DCGM.".into());
     }
     
     // Run dcgmi health check
-    let output = Command::new("dcgmi")
-        .arg("health")
-        .arg("-c")
-        .output()?;
-    
-    let output_str = String::from_utf8_lossy(&output.stdout);
+    let output = run_capture(Command::new("dcgmi").args(["health", "-c"]))?;
     
-    if !output.status.success() {
-        let error_str = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Health check failed: {}", error_str).into());
+    if !output.success {
+        return Err(format!("Health check failed: {}", output.stderr.trim()).into());
     }
     
     // Parse health check results
-    let health_results = parse_health_check(&output_str);
+    let health_results = parse_health_check(&output.stdout);
     
     Ok(health_results)
 }
@@ -344,7 +341,8 @@ fn parse_health_check(output: &str) -> Vec<DcgmHealthCheck> {
                 results.push(DcgmHealthCheck {
                     device_index: idx,
                     device_name: current_gpu_name.clone(),
-                    health_status: health_status.to_string(),
+                    status: HealthStatus::from_raw(health_status),
+                    raw_status: health_status.to_string(),
                     incidents: current_incidents.clone(),
                 });
                 
@@ -399,7 +397,8 @@ fn parse_health_check(output: &str) -> Vec<DcgmHealthCheck> {
         results.push(DcgmHealthCheck {
             device_index: idx,
             device_name: current_gpu_name,
-            health_status: health_status.to_string(),
+            status: HealthStatus::from_raw(health_status),
+            raw_status: health_status.to_string(),
             incidents: current_incidents,
         });
     }
@@ -424,4 +423,48 @@ mod tests {
         let count = count_gpus_in_discovery(output);
         assert_eq!(count, 2);
     }
+    
+    #[test]
+    fn test_health_status_from_raw() {
+        assert_eq!(HealthStatus::from_raw("Pass"), HealthStatus::Ok);
+        assert_eq!(HealthStatus::from_raw("Healthy"), HealthStatus::Ok);
+        assert_eq!(HealthStatus::from_raw("Warning"), HealthStatus::Warning);
+        assert_eq!(HealthStatus::from_raw("Failure"), HealthStatus::Critical);
+        assert_eq!(HealthStatus::from_raw("Fail"), HealthStatus::Critical);
+        assert_eq!(HealthStatus::from_raw("Skip"), HealthStatus::Unknown);
+    }
+    
+    #[test]
+    fn test_parse_diag_results_keeps_raw_status() {
+        let mut result = DcgmDiagResult {
+            test_name: "DCGM Diagnostics Level 1".to_string(),
+            success: true,
+            gpu_results: Vec::new(),
+            status: HealthStatus::Unknown,
+            raw_status: "Unknown".to_string(),
+            time_seconds: None,
+            truncated: false,
+            error: None,
+            raw_output: None,
+        };
+        parse_diag_results("GPU 0\n  Deployment  PASS\nOverall Result: Warning (1 skipped)\n", &mut result);
+        
+        assert_eq!(result.status, HealthStatus::Warning);
+        assert_eq!(result.raw_status, "Warning (1 skipped)");
+        assert_eq!(result.gpu_results.len(), 1);
+        assert_eq!(result.gpu_results[0].status, HealthStatus::Ok);
+        assert_eq!(result.gpu_results[0].raw_status, "Pass");
+    }
+    
+    #[test]
+    fn test_parse_health_check_maps_incidents() {
+        let output = "GPU 0: Healthy\nGPU 1:\n  Warning: PCIe replay rate elevated\nGPU 2:\n  Error: XID 79 detected\n";
+        let results = parse_health_check(output);
+        
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].status, HealthStatus::Ok);
+        assert_eq!(results[1].status, HealthStatus::Warning);
+        assert_eq!(results[2].status, HealthStatus::Critical);
+        assert_eq!(results[2].raw_status, "Failure");
+    }
 }
@@ -0,0 +1,136 @@
+use nvml_wrapper::Nvml;
+use serde::Serialize;
+
+use crate::hardware::tool_on_path;
+use crate::hardware::types::{DiagnosticCheck, DiagnosticReport, HealthStatus};
+use crate::testing::{
+    collect_gpu_health,
+    GpuFilter,
+    collect_hashcat_info,
+    collect_mpi_info,
+    collect_nccl_info,
+    run_dcgm_health_check,
+};
+
+/// Run every non-destructive health and info check (`test all`). Checks whose
+/// prerequisites are missing are reported as skipped and don't affect the
+/// overall status; a check that is present but fails to run counts as Unknown.
+pub fn run_all_checks() -> DiagnosticReport {
+    let checks = vec![
+        gpu_health_check(),
+        dcgm_health_check(),
+        info_check("nccl-info", "NCCL", collect_nccl_info(), |i| i.nccl_available, |i| i.error.clone()),
+        info_check("mpi-info", "MPI", collect_mpi_info(), |i| i.mpi_available, |i| i.error.clone()),
+        info_check("hashcat-info", "hashcat", collect_hashcat_info(), |i| i.hashcat_available, |i| i.error.clone()),
+    ];
+
+    DiagnosticReport { status: overall_status(&checks), checks }
+}
+
+fn gpu_health_check() -> DiagnosticCheck {
+    const NAME: &str = "gpu-health";
+    if let Err(e) = Nvml::init() {
+        return skipped(NAME, format!("NVML unavailable: {}", e));
+    }
+    match collect_gpu_health(&GpuFilter::default()) {
+        Ok(gpus) if gpus.is_empty() => skipped(NAME, "no NVIDIA GPUs found".to_string()),
+        Ok(gpus) => ran(NAME, worst(gpus.iter().map(|g| g.status)), &gpus),
+        Err(e) => errored(NAME, e.to_string()),
+    }
+}
+
+fn dcgm_health_check() -> DiagnosticCheck {
+    const NAME: &str = "dcgm-health";
+    if !tool_on_path("dcgmi") {
+        return skipped(NAME, "dcgmi not found on PATH".to_string());
+    }
+    match run_dcgm_health_check() {
+        Ok(results) => ran(NAME, worst(results.iter().map(|r| r.status)), &results),
+        Err(e) => errored(NAME, e.to_string()),
+    }
+}
+
+/// Installation checks only say whether a tool is usable, so they pass once it is found.
+fn info_check<T: Serialize>(
+    name: &str,
+    tool: &str,
+    info: T,
+    available: impl Fn(&T) -> bool,
+    error: impl Fn(&T) -> Option<String>,
+) -> DiagnosticCheck {
+    if available(&info) {
+        ran(name, HealthStatus::Ok, &info)
+    } else {
+        let reason = error(&info).unwrap_or_else(|| format!("{} not found", tool));
+        DiagnosticCheck { result: serde_json::to_value(&info).ok(), ..skipped(name, reason) }
+    }
+}
+
+fn ran<T: Serialize>(name: &str, status: HealthStatus, result: &T) -> DiagnosticCheck {
+    DiagnosticCheck {
+        name: name.to_string(),
+        status,
+        skipped: false,
+        message: None,
+        result: serde_json::to_value(result).ok(),
+    }
+}
+
+fn skipped(name: &str, reason: String) -> DiagnosticCheck {
+    DiagnosticCheck {
+        name: name.to_string(),
+        status: HealthStatus::Unknown,
+        skipped: true,
+        message: Some(reason),
+        result: None,
+    }
+}
+
+fn errored(name: &str, error: String) -> DiagnosticCheck {
+    DiagnosticCheck {
+        skipped: false,
+        ..skipped(name, error)
+    }
+}
+
+// Ok < Unknown < Warning < Critical
+fn severity(status: HealthStatus) -> u8 {
+    match status {
+        HealthStatus::Ok => 0,
+        HealthStatus::Unknown => 1,
+        HealthStatus::Warning => 2,
+        HealthStatus::Critical => 3,
+    }
+}
+
+fn worst(statuses: impl Iterator<Item = HealthStatus>) -> HealthStatus {
+    statuses.max_by_key(|s| severity(*s)).unwrap_or(HealthStatus::Unknown)
+}
+
+fn overall_status(checks: &[DiagnosticCheck]) -> HealthStatus {
+    worst(checks.iter().filter(|c| !c.skipped).map(|c| c.status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overall_status_ignores_skipped_checks() {
+        let checks = vec![
+            ran("gpu-health", HealthStatus::Warning, &()),
+            ran("nccl-info", HealthStatus::Ok, &()),
+            skipped("dcgm-health", "dcgmi not found on PATH".to_string()),
+        ];
+        assert_eq!(overall_status(&checks), HealthStatus::Warning);
+
+        let checks = vec![
+            ran("gpu-health", HealthStatus::Ok, &()),
+            errored("dcgm-health", "host engine not running".to_string()),
+        ];
+        assert_eq!(overall_status(&checks), HealthStatus::Unknown);
+
+        let checks = vec![skipped("gpu-health", "NVML unavailable".to_string())];
+        assert_eq!(overall_status(&checks), HealthStatus::Unknown);
+    }
+}
@@ -0,0 +1,144 @@
+use nvml_wrapper::enum_wrappers::device::Clock;
+use nvml_wrapper::error::NvmlError;
+use nvml_wrapper::{Device, Nvml};
+use crate::hardware::types::{GpuConfigChange, GpuConfigState};
+
+/// Settings to apply with `test gpu-config`; `None` leaves a setting alone.
+#[derive(Debug, Default)]
+pub struct GpuConfigRequest {
+    pub power_limit_watts: Option<u32>,
+    pub application_clocks_mhz: Option<(u32, u32)>, // (memory, graphics)
+    pub persistence_mode: Option<bool>,
+}
+
+impl GpuConfigRequest {
+    pub fn is_empty(&self) -> bool {
+        self.power_limit_watts.is_none() && self.application_clocks_mhz.is_none() && self.persistence_mode.is_none()
+    }
+
+    /// The requested changes in words, e.g. "power limit 250 W, persistence mode on".
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(watts) = self.power_limit_watts {
+            parts.push(format!("power limit {} W", watts));
+        }
+        if let Some((memory, graphics)) = self.application_clocks_mhz {
+            parts.push(format!("application clocks {} MHz memory / {} MHz graphics", memory, graphics));
+        }
+        if let Some(enabled) = self.persistence_mode {
+            parts.push(format!("persistence mode {}", if enabled { "on" } else { "off" }));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Apply `request` to the selected GPUs (all of them when `gpu_ids` is None),
+/// reading each setting before and after. A GPU that rejects a setting keeps
+/// going with the rest and reports the failure in `error`.
+pub fn configure_gpus(gpu_ids: Option<&[u32]>, request: &GpuConfigRequest) -> Result<Vec<GpuConfigChange>, Box<dyn std::error::Error>> {
+    let nvml = Nvml::init()?;
+    let device_count = nvml.device_count()?;
+    let indices: Vec<u32> = match gpu_ids {
+        Some(ids) => {
+            if let Some(missing) = ids.iter().find(|&&i| i >= device_count) {
+                return Err(format!("GPU {} not found ({} GPU(s) present)", missing, device_count).into());
+            }
+            ids.to_vec()
+        }
+        None => (0..device_count).collect(),
+    };
+
+    let mut changes = Vec::new();
+    for i in indices {
+        let mut device = nvml.device_by_index(i)?;
+        let before = read_gpu_config(&device);
+        let error = apply_gpu_config(&mut device, request).err();
+        changes.push(GpuConfigChange {
+            device_index: i,
+            device_name: device.name().unwrap_or_else(|_| format!("GPU {}", i)),
+            after: read_gpu_config(&device),
+            before,
+            error,
+        });
+    }
+
+    Ok(changes)
+}
+
+fn read_gpu_config(device: &Device) -> GpuConfigState {
+    GpuConfigState {
+        power_limit_watts: device.power_management_limit().ok().map(|mw| mw / 1000),
+        application_clock_graphics_mhz: device.applications_clock(Clock::Graphics).ok(),
+        application_clock_memory_mhz: device.applications_clock(Clock::Memory).ok(),
+        persistence_mode: device.is_in_persistent_mode().ok(),
+    }
+}
+
+fn apply_gpu_config(device: &mut Device, request: &GpuConfigRequest) -> Result<(), String> {
+    if let Some(watts) = request.power_limit_watts {
+        // NVML rejects out-of-range limits with a bare InvalidArg; say what the range is
+        if let Ok(constraints) = device.power_management_limit_constraints() {
+            let (min, max) = (constraints.min_limit / 1000, constraints.max_limit / 1000);
+            if watts < min || watts > max {
+                return Err(format!("power limit {} W is outside the supported range {}-{} W", watts, min, max));
+            }
+        }
+        device
+            .set_power_management_limit(watts * 1000)
+            .map_err(|e| setter_error("power limit", e))?;
+    }
+    if let Some((memory, graphics)) = request.application_clocks_mhz {
+        device
+            .set_applications_clocks(memory, graphics)
+            .map_err(|e| setter_error("application clocks", e))?;
+    }
+    if let Some(enabled) = request.persistence_mode {
+        device
+            .set_persistent(enabled)
+            .map_err(|e| setter_error("persistence mode", e))?;
+    }
+    Ok(())
+}
+
+fn setter_error(setting: &str, e: NvmlError) -> String {
+    match e {
+        NvmlError::NoPermission => format!("permission denied setting {}; NVML needs root for this (re-run with sudo)", setting),
+        NvmlError::NotSupported => format!("{} cannot be changed on this GPU", setting),
+        e => format!("failed to set {}: {}", setting, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_request() {
+        assert!(GpuConfigRequest::default().is_empty());
+        assert_eq!(GpuConfigRequest::default().describe(), "");
+
+        let request = GpuConfigRequest {
+            power_limit_watts: Some(250),
+            application_clocks_mhz: Some((1593, 1410)),
+            persistence_mode: Some(false),
+        };
+        assert!(!request.is_empty());
+        assert_eq!(
+            request.describe(),
+            "power limit 250 W, application clocks 1593 MHz memory / 1410 MHz graphics, persistence mode off"
+        );
+
+        let request = GpuConfigRequest { persistence_mode: Some(true), ..Default::default() };
+        assert_eq!(request.describe(), "persistence mode on");
+    }
+
+    #[test]
+    fn test_setter_error() {
+        assert_eq!(
+            setter_error("power limit", NvmlError::NoPermission),
+            "permission denied setting power limit; NVML needs root for this (re-run with sudo)"
+        );
+        assert_eq!(setter_error("application clocks", NvmlError::NotSupported), "application clocks cannot be changed on this GPU");
+        assert!(setter_error("persistence mode", NvmlError::Uninitialized).starts_with("failed to set persistence mode: "));
+    }
+}
@@ -1,16 +1,51 @@
 use nvml_wrapper::Nvml;
-use nvml_wrapper::enum_wrappers::device::{Clock, TemperatureSensor};
-use crate::hardware::types::{GpuErrorInfo, GpuHealthInfo};
+use nvml_wrapper::enum_wrappers::device::{Clock, PerformancePolicy, TemperatureSensor};
+use crate::hardware::types::{GpuErrorInfo, GpuHealthInfo, HealthStatus};
 use serde::Serialize;
 
+const NVML_SUCCESS: u32 = 0;
+
+/// GPUs to look at, by index and/or UUID. The default selects every GPU.
+#[derive(Debug, Default)]
+pub struct GpuFilter {
+    pub indices: Option<Vec<u32>>,
+    pub uuids: Option<Vec<String>>,
+}
+
+impl GpuFilter {
+    /// Resolve the filter to sorted NVML indices, failing on an index or UUID
+    /// that doesn't exist rather than quietly checking nothing.
+    fn select(&self, nvml: &Nvml) -> Result<Vec<u32>, Box<dyn std::error::Error>> {
+        let device_count = nvml.device_count()?;
+        if self.indices.is_none() && self.uuids.is_none() {
+            return Ok((0..device_count).collect());
+        }
+        
+        let mut selected = Vec::new();
+        for &i in self.indices.iter().flatten() {
+            if i >= device_count {
+                return Err(format!("GPU {} not found ({} GPU(s) present)", i, device_count).into());
+            }
+            selected.push(i);
+        }
+        for uuid in self.uuids.iter().flatten() {
+            let device = nvml.device_by_uuid(uuid.as_str())
+                .map_err(|e| format!("GPU with UUID {} not found: {}", uuid, e))?;
+            selected.push(device.index()?);
+        }
+        selected.sort_unstable();
+        selected.dedup();
+        Ok(selected)
+    }
+}
+
 /// Collect GPU errors and health information using NVML
-pub fn collect_gpu_errors() -> Result<Vec<GpuErrorInfo>, Box<dyn std::error::Error>> {
+pub fn collect_gpu_errors(filter: &GpuFilter) -> Result<Vec<GpuErrorInfo>, Box<dyn std::error::Error>> {
     let nvml = Nvml::init()?;
-    let device_count = nvml.device_count()?;
     
     let mut errors = Vec::new();
     
-    for i in 0..device_count {
+    for i in filter.select(&nvml)? {
         let device = nvml.device_by_index(i)?;
         
         // Get device name and UUID
@@ -28,6 +63,7 @@ pub fn collect_gpu_errors() -> Result<Vec<GpuErrorInfo>, Box<dyn std::error::Err
             thermal_violations: None,
             power_violations: None,
             has_errors: false,
+            status: HealthStatus::Ok,
         };
         
         // Check for ECC errors (if supported)
@@ -36,12 +72,23 @@ pub fn collect_gpu_errors() -> Result<Vec<GpuErrorInfo>, Box<dyn std::error::Err
                 let ecc_errors = collect_ecc_errors(&device);
                 if ecc_errors.has_errors {
                     error_info.has_errors = true;
+                    // Uncorrectable errors corrupt data and a failed remap needs an RMA;
+                    // corrected errors and a pending remap (needs a reset) are warnings
+                    error_info.status = if ecc_errors.volatile_double_bit > 0
+                        || ecc_errors.remapped_rows_uncorrectable.unwrap_or(0) > 0
+                        || ecc_errors.remapping_failure == Some(true)
+                    {
+                        HealthStatus::Critical
+                    } else {
+                        HealthStatus::Warning
+                    };
                 }
                 error_info.ecc_errors = Some(ecc_errors);
             }
         }
         
-        // Check for retired pages (memory errors)
+        // Check for retired pages (memory errors) - pre-Ampere GPUs; newer ones
+        // remap rows instead, which is reported with the ECC counts
         use nvml_wrapper::enum_wrappers::device::RetirementCause;
         
         let mut total_retired = 0u32;
@@ -55,6 +102,9 @@ pub fn collect_gpu_errors() -> Result<Vec<GpuErrorInfo>, Box<dyn std::error::Err
         if total_retired > 0 {
             error_info.has_errors = true;
             error_info.retired_pages = Some(total_retired);
+            if error_info.status == HealthStatus::Ok {
+                error_info.status = HealthStatus::Warning;
+            }
         }
         
         // Check for thermal violations
@@ -66,6 +116,9 @@ pub fn collect_gpu_errors() -> Result<Vec<GpuErrorInfo>, Box<dyn std::error::Err
                     if temp >= threshold as u32 {
                         error_info.thermal_violations = Some(format!("Temperature {}°C exceeds slowdown threshold {}°C", temp, threshold));
                         error_info.has_errors = true;
+                        if error_info.status == HealthStatus::Ok {
+                            error_info.status = HealthStatus::Warning;
+                        }
                     }
                 }
             }
@@ -84,6 +137,10 @@ fn collect_ecc_errors(device: &nvml_wrapper::Device) -> EccErrorCounts {
         volatile_double_bit: 0,
         aggregate_single_bit: 0,
         aggregate_double_bit: 0,
+        remapped_rows_correctable: None,
+        remapped_rows_uncorrectable: None,
+        remapping_pending: None,
+        remapping_failure: None,
         has_errors: false,
     };
     
@@ -113,17 +170,44 @@ fn collect_ecc_errors(device: &nvml_wrapper::Device) -> EccErrorCounts {
         ecc_errors.aggregate_double_bit = dbe;
     }
     
+    collect_remapped_rows(device, &mut ecc_errors);
+    
     ecc_errors
 }
 
+/// Row-remapping counters (Ampere and newer). nvml-wrapper does not wrap this
+/// call, so it goes through the raw library; older GPUs and drivers return an
+/// error and the fields stay None.
+fn collect_remapped_rows(device: &nvml_wrapper::Device, ecc_errors: &mut EccErrorCounts) {
+    let Ok(get_remapped_rows) = device.nvml().lib().nvmlDeviceGetRemappedRows.as_ref() else {
+        return;
+    };
+    
+    let (mut correctable, mut uncorrectable, mut pending, mut failure) = (0, 0, 0, 0);
+    // SAFETY: the handle belongs to a live Device and every out-pointer is a local
+    let ret = unsafe {
+        get_remapped_rows(device.handle(), &mut correctable, &mut uncorrectable, &mut pending, &mut failure)
+    };
+    if ret != NVML_SUCCESS {
+        return;
+    }
+    
+    ecc_errors.remapped_rows_correctable = Some(correctable);
+    ecc_errors.remapped_rows_uncorrectable = Some(uncorrectable);
+    ecc_errors.remapping_pending = Some(pending != 0);
+    ecc_errors.remapping_failure = Some(failure != 0);
+    if uncorrectable > 0 || pending != 0 || failure != 0 {
+        ecc_errors.has_errors = true;
+    }
+}
+
 /// Collect comprehensive GPU health information
-pub fn collect_gpu_health() -> Result<Vec<GpuHealthInfo>, Box<dyn std::error::Error>> {
+pub fn collect_gpu_health(filter: &GpuFilter) -> Result<Vec<GpuHealthInfo>, Box<dyn std::error::Error>> {
     let nvml = Nvml::init()?;
-    let device_count = nvml.device_count()?;
     
     let mut health_info = Vec::new();
     
-    for i in 0..device_count {
+    for i in filter.select(&nvml)? {
         let device = nvml.device_by_index(i)?;
         
         let name = device.name().unwrap_or_else(|_| format!("GPU {}", i));
@@ -136,7 +220,10 @@ pub fn collect_gpu_health() -> Result<Vec<GpuHealthInfo>, Box<dyn std::error::Er
             temperature_celsius: None,
             power_usage_watts: None,
             power_limit_watts: None,
+            power_limit_min_watts: None,
+            power_limit_max_watts: None,
             fan_speed_percent: None,
+            fan_speeds_percent: Vec::new(),
             utilization_gpu_percent: None,
             utilization_memory_percent: None,
             memory_used_mb: None,
@@ -144,7 +231,11 @@ pub fn collect_gpu_health() -> Result<Vec<GpuHealthInfo>, Box<dyn std::error::Er
             clock_graphics_mhz: None,
             clock_memory_mhz: None,
             throttle_reasons: Vec::new(),
+            total_throttle_ms: None,
             performance_state: None,
+            persistence_mode: None,
+            ecc_enabled: None,
+            status: HealthStatus::Unknown,
         };
         
         // Temperature
@@ -161,11 +252,18 @@ pub fn collect_gpu_health() -> Result<Vec<GpuHealthInfo>, Box<dyn std::error::Er
             info.power_limit_watts = Some((power_limit / 1000) as u32);
         }
         
-        // Fan speed
-        if let Ok(fan_speed) = device.fan_speed(0) {
-            info.fan_speed_percent = Some(fan_speed);
+        if let Ok(constraints) = device.power_management_limit_constraints() {
+            info.power_limit_min_watts = Some(constraints.min_limit / 1000);
+            info.power_limit_max_watts = Some(constraints.max_limit / 1000);
         }
         
+        // Fan speeds - multi-fan cards report each fan separately
+        let num_fans = device.num_fans().unwrap_or(1);
+        info.fan_speeds_percent = (0..num_fans)
+            .filter_map(|fan| device.fan_speed(fan).ok())
+            .collect();
+        info.fan_speed_percent = info.fan_speeds_percent.first().copied();
+        
         // Utilization
         if let Ok(utilization) = device.utilization_rates() {
             info.utilization_gpu_percent = Some(utilization.gpu);
@@ -191,6 +289,16 @@ pub fn collect_gpu_health() -> Result<Vec<GpuHealthInfo>, Box<dyn std::error::Er
         if let Ok(throttle_reasons) = device.current_throttle_reasons() {
             use nvml_wrapper::bitmasks::device::ThrottleReasons;
             
+            // Hardware slowdowns mean the board is protecting itself; hitting the
+            // software power cap under load is normal and not a health problem
+            info.status = if throttle_reasons.intersects(ThrottleReasons::HW_THERMAL_SLOWDOWN | ThrottleReasons::HW_POWER_BRAKE_SLOWDOWN) {
+                HealthStatus::Critical
+            } else if throttle_reasons.contains(ThrottleReasons::SW_THERMAL_SLOWDOWN) {
+                HealthStatus::Warning
+            } else {
+                HealthStatus::Ok
+            };
+            
             if throttle_reasons.contains(ThrottleReasons::GPU_IDLE) {
                 info.throttle_reasons.push("GPU Idle".to_string());
             }
@@ -208,11 +316,23 @@ pub fn collect_gpu_health() -> Result<Vec<GpuHealthInfo>, Box<dyn std::error::Er
             }
         }
         
+        // Cumulative throttle time, which catches GPUs that only throttle now
+        // and then; most boards don't report thermal violations separately
+        info.total_throttle_ms = [PerformancePolicy::Power, PerformancePolicy::Thermal]
+            .into_iter()
+            .filter_map(|policy| device.violation_status(policy).ok())
+            .map(|v| v.violation_time / 1_000_000) // ns to ms
+            .reduce(|a, b| a + b);
+        
         // Performance state
         if let Ok(pstate) = device.performance_state() {
             info.performance_state = Some(format!("P{}", pstate as u32));
         }
         
+        // Persistence and ECC mode (unsupported on some consumer boards)
+        info.persistence_mode = device.is_in_persistent_mode().ok();
+        info.ecc_enabled = device.is_ecc_enabled().ok().map(|ecc| ecc.currently_enabled);
+        
         health_info.push(info);
     }
     
@@ -225,5 +345,9 @@ pub struct EccErrorCounts {
     pub volatile_double_bit: u64,
     pub aggregate_single_bit: u64,
     pub aggregate_double_bit: u64,
+    pub remapped_rows_correctable: Option<u32>,   // Ampere+ row remapping
+    pub remapped_rows_uncorrectable: Option<u32>,
+    pub remapping_pending: Option<bool>,          // a reset is needed to apply a remap
+    pub remapping_failure: Option<bool>,          // remap failed; the GPU needs service
     pub has_errors: bool,
 }
@@ -0,0 +1,136 @@
+use nvml_wrapper::enum_wrappers::device::TopologyLevel;
+use nvml_wrapper::Nvml;
+use std::fs;
+
+use crate::hardware::types::{GpuTopology, GpuTopologyDevice};
+
+// NVML_NVLINK_MAX_LINKS
+const MAX_NVLINKS: u32 = 18;
+
+// cpu_affinity() bitmask size in 64-bit words (covers 1024 CPUs)
+const AFFINITY_WORDS: usize = 16;
+
+/// Build the GPU-to-GPU connection matrix (like `nvidia-smi topo -m`) from NVML.
+///
+/// Each cell is "X" for the GPU itself, "NV<n>" for n bonded NVLinks, or the
+/// PCIe path: BOARD (same multi-GPU board), PIX (single switch), PXB (multiple
+/// switches), PHB (host bridge), NODE (same NUMA node) or SYS (across sockets).
+pub fn collect_gpu_topology() -> Result<GpuTopology, Box<dyn std::error::Error>> {
+    let nvml = Nvml::init()?;
+    let device_count = nvml.device_count()?;
+
+    let mut devices = Vec::new();
+    let mut bus_ids = Vec::new();
+    for i in 0..device_count {
+        let device = nvml.device_by_index(i)?;
+        let bus_id = device.pci_info().map(|p| p.bus_id).unwrap_or_default();
+
+        devices.push(GpuTopologyDevice {
+            index: i,
+            name: device.name().unwrap_or_else(|_| format!("GPU {}", i)),
+            pci_bus_id: bus_id.clone(),
+            cpu_affinity: device.cpu_affinity(AFFINITY_WORDS).ok().map(|mask| cpu_list(&mask)).filter(|l| !l.is_empty()),
+            numa_node: read_numa_node(&bus_id),
+        });
+        bus_ids.push(bus_id);
+    }
+
+    let mut matrix = Vec::new();
+    for i in 0..device_count {
+        let device = nvml.device_by_index(i)?;
+
+        // Count active NVLinks landing on each peer GPU
+        let mut nvlinks = vec![0u32; device_count as usize];
+        for link in 0..MAX_NVLINKS {
+            let link = device.link_wrapper_for(link);
+            if !link.is_active().unwrap_or(false) {
+                continue;
+            }
+            if let Ok(remote) = link.remote_pci_info() {
+                if let Some(peer) = bus_ids.iter().position(|b| b.eq_ignore_ascii_case(&remote.bus_id)) {
+                    nvlinks[peer] += 1;
+                }
+            }
+        }
+
+        let mut row = Vec::new();
+        for j in 0..device_count {
+            let cell = if i == j {
+                "X".to_string()
+            } else if nvlinks[j as usize] > 0 {
+                format!("NV{}", nvlinks[j as usize])
+            } else {
+                nvml.device_by_index(j)
+                    .and_then(|peer| device.topology_common_ancestor(peer))
+                    .map(|level| topology_label(&level).to_string())
+                    .unwrap_or_else(|_| "N/A".to_string())
+            };
+            row.push(cell);
+        }
+        matrix.push(row);
+    }
+
+    Ok(GpuTopology { devices, matrix })
+}
+
+fn topology_label(level: &TopologyLevel) -> &'static str {
+    match level {
+        TopologyLevel::Internal => "BOARD",
+        TopologyLevel::Single => "PIX",
+        TopologyLevel::Multiple => "PXB",
+        TopologyLevel::HostBridge => "PHB",
+        TopologyLevel::Node => "NODE",
+        TopologyLevel::System => "SYS",
+    }
+}
+
+/// Turn an NVML affinity bitmask into a CPU list such as "0-15,32-47".
+fn cpu_list(mask: &[std::os::raw::c_ulong]) -> String {
+    let bits = std::mem::size_of::<std::os::raw::c_ulong>() * 8;
+    let cpus: Vec<usize> = mask
+        .iter()
+        .enumerate()
+        .flat_map(|(word_idx, word)| (0..bits).filter(move |bit| word & (1 << bit) != 0).map(move |bit| word_idx * bits + bit))
+        .collect();
+
+    let mut ranges: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < cpus.len() {
+        let start = cpus[i];
+        let mut end = start;
+        while i + 1 < cpus.len() && cpus[i + 1] == end + 1 {
+            i += 1;
+            end = cpus[i];
+        }
+        ranges.push(if start == end { start.to_string() } else { format!("{}-{}", start, end) });
+        i += 1;
+    }
+    ranges.join(",")
+}
+
+/// NVML bus ids use an 8-digit domain ("00000000:3B:00.0"); sysfs uses 4 ("0000:3b:00.0").
+fn read_numa_node(bus_id: &str) -> Option<i32> {
+    if bus_id.len() < 12 {
+        return None;
+    }
+    let sysfs_id = bus_id[bus_id.len() - 12..].to_lowercase();
+    fs::read_to_string(format!("/sys/bus/pci/devices/{}/numa_node", sysfs_id))
+        .ok()
+        .and_then(|s| s.trim().parse::<i32>().ok())
+        .filter(|n| *n >= 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_list() {
+        assert_eq!(cpu_list(&[0x0000_ffff_0000_ffff]), "0-15,32-47");
+        assert_eq!(cpu_list(&[0b1011_0101]), "0,2,4-5,7");
+        // Ranges carry across mask words
+        assert_eq!(cpu_list(&[1 << 63, 0b11]), "63-65");
+        assert_eq!(cpu_list(&[0, 1]), "64");
+        assert_eq!(cpu_list(&[0, 0]), "");
+    }
+}
@@ -1,5 +1,78 @@
-use crate::hardware::types::{HashcatInfo, HashcatTestResult, HashcatDevice};
+use crate::hardware::types::{GpuHealthInfo, HashcatInfo, HashcatTestResult, HashcatDevice, HashcatStressResult};
+use crate::hardware::tool_on_path;
+use crate::testing::gpu_errors::{collect_gpu_health, GpuFilter};
+use crate::util::{run_capture, sample_while};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
 use std::process::Command;
+use std::time::{Duration, Instant};
+
+const HASHCAT_EXIT_RUNTIME_ABORT: i32 = 4;
+
+/// Hashcat flags shared by benchmark, stress and dictionary runs. The default
+/// leaves hashcat's own behavior unchanged.
+#[derive(Debug, Clone, Default)]
+pub struct HashcatOptions {
+    pub workload: Option<u8>, // -w profile: 1 = low, 2 = default, 3 = high, 4 = nightmare
+    pub force: bool,          // ignore hashcat's driver and device warnings
+    pub potfile_disable: bool, // neither read nor write cracked hashes
+    pub potfile_path: Option<PathBuf>,
+    pub runtime_secs: Option<u64>, // hashcat --runtime: abort the attack after this long
+}
+
+impl HashcatOptions {
+    fn apply(&self, cmd: &mut Command) {
+        if let Some(workload) = self.workload {
+            cmd.arg("-w");
+            cmd.arg(workload.to_string());
+        }
+        if self.force {
+            cmd.arg("--force");
+        }
+        if self.potfile_disable {
+            cmd.arg("--potfile-disable");
+        } else if let Some(path) = &self.potfile_path {
+            cmd.arg("--potfile-path");
+            cmd.arg(path);
+        }
+        if let Some(runtime) = self.runtime_secs {
+            cmd.arg("--runtime");
+            cmd.arg(runtime.to_string());
+        }
+    }
+}
+
+/// A named hashcat session whose restore file lives in `dir`, so an
+/// interrupted dictionary run can be resumed with `--restore`.
+#[derive(Debug, Clone)]
+pub struct HashcatSession {
+    pub name: String,
+    pub dir: PathBuf,
+}
+
+impl HashcatSession {
+    fn restore_file(&self) -> PathBuf {
+        self.dir.join(format!("{}.restore", self.name))
+    }
+    
+    fn apply(&self, cmd: &mut Command) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Cannot create session directory {}: {}", self.dir.display(), e))?;
+        cmd.arg("--session");
+        cmd.arg(&self.name);
+        cmd.arg("--restore-file-path");
+        cmd.arg(self.restore_file());
+        Ok(())
+    }
+}
+
+/// Where session restore files go when no directory is given.
+pub fn default_session_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("farm-manager")
+        .join("hashcat-sessions")
+}
 
 /// Get Hashcat installation information and version
 pub fn collect_hashcat_info() -> HashcatInfo {
@@ -14,27 +87,23 @@ pub fn collect_hashcat_info() -> HashcatInfo {
     };
     
     // Check if hashcat is available
-    if let Ok(output) = Command::new("which").arg("hashcat").output() {
-        if !output.status.success() {
-            info.error = Some("hashcat not found. Please install hashcat.".to_string());
-            return info;
-        }
-        info.hashcat_available = true;
+    if !tool_on_path("hashcat") {
+        info.error = Some("hashcat not found. Please install hashcat.".to_string());
+        return info;
     }
+    info.hashcat_available = true;
     
     // Get hashcat version
-    if let Ok(output) = Command::new("hashcat").arg("--version").output() {
-        if output.status.success() {
-            let version_str = String::from_utf8_lossy(&output.stdout);
-            info.hashcat_version = Some(version_str.trim().to_string());
+    if let Ok(output) = run_capture(Command::new("hashcat").arg("--version")) {
+        if output.success {
+            info.hashcat_version = Some(output.stdout.trim().to_string());
         }
     }
     
     // Get device information using hashcat -I
-    if let Ok(output) = Command::new("hashcat").arg("-I").output() {
-        if output.status.success() {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            parse_hashcat_devices(&output_str, &mut info);
+    if let Ok(output) = run_capture(Command::new("hashcat").arg("-I")) {
+        if output.success {
+            parse_hashcat_devices(&output.stdout, &mut info);
         }
     }
     
@@ -102,23 +171,19 @@ fn parse_hashcat_devices(output: &str, info: &mut HashcatInfo) {
 }
 
 /// Run a hashcat benchmark
-pub fn run_hashcat_benchmark(hash_types: Vec<String>, device_ids: Option<Vec<u32>>) 
+pub fn run_hashcat_benchmark(hash_types: Vec<String>, device_ids: Option<Vec<u32>>, options: &HashcatOptions) 
     -> Result<Vec<HashcatTestResult>, Box<dyn std::error::Error>> {
     
     let mut results = Vec::new();
     
     // Check if hashcat is available
-    if !Command::new("which")
-        .arg("hashcat")
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+    if !tool_on_path("hashcat")
     {
         return Err("hashcat not found. Please install hashcat.".into());
     }
     
     for hash_type in hash_types {
-        let result = run_single_benchmark(&hash_type, device_ids.as_ref())?;
+        let result = run_single_benchmark(&hash_type, device_ids.as_ref(), options)?;
         results.push(result);
     }
     
@@ -126,21 +191,10 @@ pub fn run_hashcat_benchmark(hash_types: Vec<String>, device_ids: Option<Vec<u32
 }
 
 /// Run a single hashcat benchmark for a specific hash type
-fn run_single_benchmark(hash_type: &str, device_ids: Option<&Vec<u32>>) 
+fn run_single_benchmark(hash_type: &str, device_ids: Option<&Vec<u32>>, options: &HashcatOptions) 
     -> Result<HashcatTestResult, Box<dyn std::error::Error>> {
     
-    let mut result = HashcatTestResult {
-        test_type: "benchmark".to_string(),
-        hash_type: Some(hash_type.to_string()),
-        device_ids: device_ids.map(|v| v.clone()).unwrap_or_default(),
-        success: false,
-        hash_speed: None,
-        time_seconds: None,
-        recovered: None,
-        total: None,
-        error: None,
-        raw_output: None,
-    };
+    let mut result = HashcatTestResult::new("benchmark", Some(hash_type.to_string()), device_ids.cloned().unwrap_or_default());
     
     // Build command
     let mut cmd = Command::new("hashcat");
@@ -159,59 +213,143 @@ fn run_single_benchmark(hash_type: &str, device_ids: Option<&Vec<u32>>)
             cmd.arg(device_str);
         }
     }
+    options.apply(&mut cmd);
     
     // Run the benchmark
     let start_time = std::time::Instant::now();
-    let output = cmd.output()?;
+    let output = run_capture(&mut cmd)?;
     let elapsed = start_time.elapsed().as_secs_f64();
     
     result.time_seconds = Some(elapsed);
     
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    result.raw_output = Some(output_str.to_string());
+    let output_str = &output.stdout;
+    result.raw_output = Some(output_str.clone());
     
-    if output.status.success() {
+    if output.success {
         result.success = true;
         
         // Parse benchmark results for hash speed
-        if let Some(speed) = parse_benchmark_speed(&output_str) {
+        if let Some(speed) = parse_benchmark_speed(output_str) {
             result.hash_speed = Some(speed);
         }
     } else {
-        let error_str = String::from_utf8_lossy(&output.stderr);
-        result.error = Some(format!("Benchmark failed: {}", error_str));
+        result.error = Some(format!("Benchmark failed: {}", output.stderr));
     }
     
     Ok(result)
 }
 
+/// Run hashcat benchmarks back to back for `duration_secs` while sampling GPU
+/// temperature, power and throttling through NVML every `sample_interval_secs`.
+pub fn run_hashcat_stress(
+    hash_type: &str,
+    duration_secs: u64,
+    device_ids: Option<Vec<u32>>,
+    sample_interval_secs: u64,
+    options: &HashcatOptions,
+) -> Result<HashcatStressResult, Box<dyn std::error::Error>> {
+    let mut result = HashcatStressResult {
+        hash_type: hash_type.to_string(),
+        device_ids: device_ids.clone().unwrap_or_default(),
+        requested_seconds: duration_secs,
+        elapsed_seconds: 0.0,
+        iterations: 0,
+        min_hash_speed: None,
+        max_hash_speed: None,
+        samples: 0,
+        peak_temperature_celsius: None,
+        peak_power_watts: None,
+        thermal_throttling: false,
+        throttle_reasons: Vec::new(),
+        error: None,
+    };
+    
+    let duration = Duration::from_secs(duration_secs);
+    let interval = Duration::from_secs(sample_interval_secs.max(1));
+    let start_time = Instant::now();
+    let mut peaks = HealthPeaks::default();
+    
+    let benchmarks = sample_while(
+        interval,
+        || {
+            if let Ok(gpus) = collect_gpu_health(&GpuFilter::default()) {
+                peaks.record(&gpus);
+            }
+        },
+        || {
+            let mut benchmarks = Vec::new();
+            let mut failure = None;
+            while start_time.elapsed() < duration {
+                match run_hashcat_benchmark(vec![hash_type.to_string()], device_ids.clone(), options) {
+                    Ok(mut passes) => {
+                        let pass = passes.remove(0);
+                        if !pass.success {
+                            failure = Some(pass.error.unwrap_or_else(|| "Benchmark failed".to_string()));
+                            break;
+                        }
+                        benchmarks.push(pass);
+                    }
+                    Err(e) => {
+                        failure = Some(e.to_string());
+                        break;
+                    }
+                }
+            }
+        
+            result.error = failure;
+            benchmarks
+        },
+    );
+    
+    result.elapsed_seconds = start_time.elapsed().as_secs_f64();
+    result.iterations = benchmarks.len() as u32;
+    let speeds = benchmarks.iter().filter_map(|b| b.hash_speed);
+    result.min_hash_speed = speeds.clone().reduce(f64::min);
+    result.max_hash_speed = speeds.reduce(f64::max);
+    
+    result.samples = peaks.samples;
+    result.peak_temperature_celsius = peaks.temperature_celsius;
+    result.peak_power_watts = peaks.power_watts;
+    result.thermal_throttling = peaks.throttle_reasons.iter().any(|r| r.contains("Thermal"));
+    result.throttle_reasons = peaks.throttle_reasons.into_iter().collect();
+    
+    Ok(result)
+}
+
+/// Worst-case GPU readings across every sample of a stress run.
+#[derive(Default)]
+struct HealthPeaks {
+    samples: u32,
+    temperature_celsius: Option<u32>,
+    power_watts: Option<u32>,
+    throttle_reasons: BTreeSet<String>,
+}
+
+impl HealthPeaks {
+    fn record(&mut self, gpus: &[GpuHealthInfo]) {
+        self.samples += 1;
+        for gpu in gpus {
+            self.temperature_celsius = self.temperature_celsius.max(gpu.temperature_celsius);
+            self.power_watts = self.power_watts.max(gpu.power_usage_watts);
+            self.throttle_reasons.extend(gpu.throttle_reasons.iter().cloned());
+        }
+    }
+}
+
 /// Run a hashcat dictionary attack test
 pub fn run_hashcat_test(
     hash_type: &str,
     hash_file: &str,
     wordlist: &str,
     device_ids: Option<Vec<u32>>,
+    options: &HashcatOptions,
+    session: Option<&HashcatSession>,
 ) -> Result<HashcatTestResult, Box<dyn std::error::Error>> {
     
-    let mut result = HashcatTestResult {
-        test_type: "dictionary".to_string(),
-        hash_type: Some(hash_type.to_string()),
-        device_ids: device_ids.clone().unwrap_or_default(),
-        success: false,
-        hash_speed: None,
-        time_seconds: None,
-        recovered: None,
-        total: None,
-        error: None,
-        raw_output: None,
-    };
+    let mut result = HashcatTestResult::new("dictionary", Some(hash_type.to_string()), device_ids.clone().unwrap_or_default());
     
     // Check if hashcat is available
-    if !Command::new("which")
-        .arg("hashcat")
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+    if !tool_on_path("hashcat")
     {
         result.error = Some("hashcat not found. Please install hashcat.".to_string());
         return Ok(result);
@@ -248,39 +386,90 @@ pub fn run_hashcat_test(
             cmd.arg(device_str);
         }
     }
+    options.apply(&mut cmd);
+    if let Some(session) = session {
+        session.apply(&mut cmd)?;
+        result.session = Some(session.name.clone());
+    }
     
     // Add --show flag to display results
     cmd.arg("--quiet");
     
+    finish_dictionary_run(cmd, result)
+}
+
+/// Resume an interrupted dictionary test from its session restore file. The
+/// hash file, wordlist and devices come from the original run.
+pub fn restore_hashcat_test(session: &HashcatSession) -> Result<HashcatTestResult, Box<dyn std::error::Error>> {
+    let mut result = HashcatTestResult {
+        session: Some(session.name.clone()),
+        ..HashcatTestResult::new("dictionary", None, Vec::new())
+    };
+    
+    if !session.restore_file().exists() {
+        result.error = Some(format!(
+            "No restore file for session '{}' at {} (the run may already have finished)",
+            session.name,
+            session.restore_file().display()
+        ));
+        return Ok(result);
+    }
+    
+    let mut cmd = Command::new("hashcat");
+    session.apply(&mut cmd)?;
+    cmd.arg("--restore");
+    
+    finish_dictionary_run(cmd, result)
+}
+
+/// Run a dictionary attack command and fill in what its output reports.
+fn finish_dictionary_run(mut cmd: Command, mut result: HashcatTestResult) -> Result<HashcatTestResult, Box<dyn std::error::Error>> {
     // Run the test
     let start_time = std::time::Instant::now();
-    let output = cmd.output()?;
+    let output = run_capture(&mut cmd)?;
     let elapsed = start_time.elapsed().as_secs_f64();
     
     result.time_seconds = Some(elapsed);
     
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    let error_str = String::from_utf8_lossy(&output.stderr);
+    let output_str = &output.stdout;
+    let error_str = &output.stderr;
     result.raw_output = Some(format!("{}\n{}", output_str, error_str));
     
-    // Parse results
-    if output.status.success() || output_str.contains("Recovered") {
+    // Parse results. A rerun against an already-cracked hash file stops before
+    // attacking anything, which is a result rather than a failure.
+    if all_hashes_in_potfile(output_str) || all_hashes_in_potfile(error_str) {
+        result.success = true;
+        result.all_in_potfile = true;
+        result.hashcat_status = Some("Cracked (potfile)".to_string());
+        return Ok(result);
+    }
+    
+    // hashcat exits with 4 when --runtime stopped the attack
+    result.truncated = output.code == Some(HASHCAT_EXIT_RUNTIME_ABORT);
+    
+    if output.success || result.truncated || output_str.contains("Recovered") {
         result.success = true;
         
         // Parse recovered/total hashes
-        if let Some((recovered, total)) = parse_recovered_hashes(&output_str) {
+        if let Some((recovered, total)) = parse_recovered_hashes(output_str) {
             result.recovered = Some(recovered);
             result.total = Some(total);
         }
         
         // Parse hash speed
-        if let Some(speed) = parse_hash_speed(&output_str) {
+        if let Some(speed) = parse_hash_speed(output_str) {
             result.hash_speed = Some(speed);
         }
     } else {
         result.error = Some(format!("Test failed: {}", error_str));
     }
     
+    result.hashcat_status = status_field(output_str, "Status").map(|s| s.to_string());
+    if let Some((point, keyspace)) = parse_restore_point(output_str) {
+        result.restore_point = Some(point);
+        result.restore_keyspace = Some(keyspace);
+    }
+    
     Ok(result)
 }
 
@@ -337,3 +526,108 @@ fn parse_recovered_hashes(output: &str) -> Option<(u32, u32)> {
     }
     None
 }
+
+/// Whether hashcat stopped because the potfile already held every hash, e.g.
+/// "INFO: All hashes found as potfile and/or empty entries! Use --show to display them."
+/// (older releases say "All hashes found in potfile!").
+fn all_hashes_in_potfile(output: &str) -> bool {
+    output.lines().any(|line| line.contains("All hashes found") && line.contains("potfile"))
+}
+
+/// Value of a hashcat status line such as "Status...........: Exhausted".
+fn status_field<'a>(output: &'a str, name: &str) -> Option<&'a str> {
+    output.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim_end_matches('.').trim() == name).then(|| value.trim())
+    })
+}
+
+/// Parse "Restore.Point....: 4194304/14344385 (29.24%)" into (position, keyspace).
+fn parse_restore_point(output: &str) -> Option<(u64, u64)> {
+    let value = status_field(output, "Restore.Point")?;
+    let (point, rest) = value.split_once('/')?;
+    let keyspace = rest.split_whitespace().next()?;
+    Some((point.trim().parse().ok()?, keyspace.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATUS: &str = "\
+Session..........: nightly
+Status...........: Exhausted
+Hash.Mode........: 1000 (NTLM)
+Speed.#1.........:  1234.5 MH/s (0.52ms) @ Accel:512 Loops:1 Thr:64 Vec:1
+Recovered........: 3/10 (30.00%) Digests
+Progress.........: 14344385/14344385 (100.00%)
+Restore.Point....: 4194304/14344385 (29.24%)
+";
+
+    #[test]
+    fn test_parses_status_and_restore_point() {
+        assert_eq!(status_field(STATUS, "Status"), Some("Exhausted"));
+        assert_eq!(status_field(STATUS, "Session"), Some("nightly"));
+        assert_eq!(parse_restore_point(STATUS), Some((4194304, 14344385)));
+        assert_eq!(parse_recovered_hashes(STATUS), Some((3, 10)));
+    }
+
+    #[test]
+    fn test_detects_all_hashes_in_potfile() {
+        assert!(all_hashes_in_potfile("INFO: All hashes found as potfile and/or empty entries! Use --show to display them.\n"));
+        assert!(all_hashes_in_potfile("INFO: All hashes found in potfile! You can use --show to display them.\n"));
+        assert!(!all_hashes_in_potfile(STATUS));
+    }
+
+    #[test]
+    fn test_missing_restore_point_is_none() {
+        assert_eq!(parse_restore_point("Status...........: Cracked\n"), None);
+    }
+
+    #[test]
+    fn test_health_peaks_keep_the_worst_reading() {
+        let mut peaks = HealthPeaks::default();
+        peaks.record(&[
+            GpuHealthInfo { device_index: 0, temperature_celsius: Some(61), power_usage_watts: Some(540), ..Default::default() },
+            GpuHealthInfo { device_index: 1, ..Default::default() },
+        ]);
+        peaks.record(&[
+            GpuHealthInfo {
+                device_index: 0,
+                temperature_celsius: Some(83),
+                power_usage_watts: Some(498),
+                throttle_reasons: vec!["SW Thermal Slowdown".to_string()],
+                ..Default::default()
+            },
+            GpuHealthInfo {
+                device_index: 1,
+                temperature_celsius: Some(70),
+                power_usage_watts: Some(690),
+                throttle_reasons: vec!["SW Power Cap".to_string()],
+                ..Default::default()
+            },
+        ]);
+        peaks.record(&[GpuHealthInfo {
+            device_index: 0,
+            temperature_celsius: Some(79),
+            power_usage_watts: Some(512),
+            throttle_reasons: vec!["SW Thermal Slowdown".to_string()],
+            ..Default::default()
+        }]);
+
+        assert_eq!(peaks.samples, 3);
+        assert_eq!(peaks.temperature_celsius, Some(83));
+        assert_eq!(peaks.power_watts, Some(690));
+        assert_eq!(peaks.throttle_reasons.into_iter().collect::<Vec<_>>(), vec!["SW Power Cap", "SW Thermal Slowdown"]);
+    }
+
+    #[test]
+    fn test_health_peaks_without_readings() {
+        let mut peaks = HealthPeaks::default();
+        peaks.record(&[]);
+
+        assert_eq!(peaks.samples, 1);
+        assert_eq!(peaks.temperature_celsius, None);
+        assert!(peaks.throttle_reasons.is_empty());
+    }
+}
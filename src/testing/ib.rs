@@ -0,0 +1,146 @@
+use crate::hardware::tool_on_path;
+use crate::hardware::types::IbBwResult;
+use crate::testing::units::{mib_per_sec_to_bytes, parse_size};
+use crate::util::run_capture;
+use std::process::Command;
+
+/// Run one side of a perftest bandwidth test. With `server` this is the client
+/// and connects to it; without, it listens until a client has run against it.
+pub fn run_ib_bw_test(
+    test_type: &str,
+    server: Option<&str>,
+    device: Option<&str>,
+    size: &str,
+    iterations: u32,
+    port: Option<u16>,
+) -> Result<IbBwResult, Box<dyn std::error::Error>> {
+    let size_bytes = parse_size(size)?;
+    let binary = format!("ib_{}_bw", test_type);
+    
+    let mut result = IbBwResult {
+        test_type: test_type.to_string(),
+        role: if server.is_some() { "client" } else { "server" }.to_string(),
+        server: server.map(str::to_string),
+        device: device.map(str::to_string),
+        size_bytes,
+        iterations,
+        success: false,
+        bw_peak_mbps: None,
+        bw_average_mbps: None,
+        bandwidth_bytes_per_sec: None,
+        msg_rate_mpps: None,
+        error: None,
+        raw_output: None,
+    };
+    
+    if !tool_on_path(&binary) {
+        result.error = Some(format!(
+            "{} not found. Install perftest (apt install perftest / dnf install perftest) \
+            or build it from https://github.com/linux-rdma/perftest",
+            binary
+        ));
+        return Ok(result);
+    }
+    
+    let mut cmd = Command::new(&binary);
+    cmd.args(["-s", &size_bytes.to_string(), "-n", &iterations.to_string()]);
+    // -F: don't abort on CPU frequency scaling warnings; they only skew latency numbers
+    cmd.arg("-F");
+    if let Some(device) = device {
+        cmd.args(["-d", device]);
+    }
+    if let Some(port) = port {
+        cmd.args(["-p", &port.to_string()]);
+    }
+    if let Some(server) = server {
+        cmd.arg(server);
+    }
+    
+    let output = run_capture(&mut cmd)?;
+    result.raw_output = Some(output.combined());
+    
+    if !output.success {
+        result.error = Some(format!("{} failed: {}", binary, output.stderr.trim()));
+        return Ok(result);
+    }
+    
+    match parse_perftest_bw(&output.stdout) {
+        Some(bw) => {
+            result.success = true;
+            result.bw_peak_mbps = Some(bw.peak_mbps);
+            result.bw_average_mbps = Some(bw.average_mbps);
+            result.bandwidth_bytes_per_sec = Some(mib_per_sec_to_bytes(bw.average_mbps));
+            result.msg_rate_mpps = Some(bw.msg_rate_mpps);
+        }
+        None => result.error = Some(format!("No bandwidth results in {} output", binary)),
+    }
+    
+    Ok(result)
+}
+
+#[derive(Debug, PartialEq)]
+struct PerftestBw {
+    peak_mbps: f64,
+    average_mbps: f64,
+    msg_rate_mpps: f64,
+}
+
+/// Parse the results table of ib_write_bw / ib_read_bw:
+///  #bytes     #iterations    BW peak[MB/sec]    BW average[MB/sec]   MsgRate[Mpps]
+///  65536      5000             11758.21            11757.96              0.188127
+fn parse_perftest_bw(output: &str) -> Option<PerftestBw> {
+    output
+        .lines()
+        .rev()
+        .find_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 5 || fields[0].parse::<u64>().is_err() || fields[1].parse::<u64>().is_err() {
+                return None;
+            }
+            Some(PerftestBw {
+                peak_mbps: fields[2].parse().ok()?,
+                average_mbps: fields[3].parse().ok()?,
+                msg_rate_mpps: fields[4].parse().ok()?,
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_perftest_bw() {
+        let output = "
+---------------------------------------------------------------------------------------
+                    RDMA_Write BW Test
+ Dual-port       : OFF\t\tDevice         : mlx5_0
+ Number of qps   : 1\t\tTransport type : IB
+ Connection type : RC\t\tUsing SRQ      : OFF
+ TX depth        : 128
+ CQ Moderation   : 1
+ Mtu             : 4096[B]
+ Link type       : IB
+ Max inline data : 0[B]
+ rdma_cm QPs\t : OFF
+ Data ex. method : Ethernet
+---------------------------------------------------------------------------------------
+ local address: LID 0x0b QPN 0x0127 PSN 0x6c1f7a RKey 0x1fff00 VAddr 0x007f5e1c1ff000
+ remote address: LID 0x0c QPN 0x0128 PSN 0x2b9a8d RKey 0x1fff00 VAddr 0x007f0f3a5ff000
+---------------------------------------------------------------------------------------
+ #bytes     #iterations    BW peak[MB/sec]    BW average[MB/sec]   MsgRate[Mpps]
+ 65536      5000             11758.21            11757.96  \t\t   0.188127
+---------------------------------------------------------------------------------------
+";
+        assert_eq!(parse_perftest_bw(output), Some(PerftestBw {
+            peak_mbps: 11758.21,
+            average_mbps: 11757.96,
+            msg_rate_mpps: 0.188127,
+        }));
+    }
+
+    #[test]
+    fn test_parse_perftest_bw_without_results() {
+        assert_eq!(parse_perftest_bw(" Couldn't connect to 10.0.0.2:18515\n Unable to open file descriptor for socket connection\n"), None);
+    }
+}
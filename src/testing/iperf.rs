@@ -0,0 +1,170 @@
+use crate::hardware::tool_on_path;
+use crate::hardware::types::NetBwResult;
+use crate::util::run_capture;
+use serde_json::Value;
+use std::process::Command;
+
+/// Run one side of an iperf3 throughput test. With `server` this is the client
+/// and connects to it; without, it listens (`iperf3 -s -1`) for a single client.
+pub fn run_net_bw_test(
+    server: Option<&str>,
+    parallel: u32,
+    udp: bool,
+    bitrate: Option<&str>,
+    duration_secs: u32,
+    port: Option<u16>,
+) -> Result<NetBwResult, Box<dyn std::error::Error>> {
+    let mut result = NetBwResult {
+        protocol: if udp { "udp" } else { "tcp" }.to_string(),
+        role: if server.is_some() { "client" } else { "server" }.to_string(),
+        server: server.map(str::to_string),
+        parallel,
+        duration_secs,
+        success: false,
+        sent_bits_per_sec: None,
+        received_bits_per_sec: None,
+        retransmits: None,
+        jitter_ms: None,
+        lost_percent: None,
+        error: None,
+    };
+    
+    if !tool_on_path("iperf3") {
+        result.error = Some(
+            "iperf3 not found. Install it with: apt install iperf3 / dnf install iperf3".to_string()
+        );
+        return Ok(result);
+    }
+    
+    let mut cmd = Command::new("iperf3");
+    cmd.arg("--json");
+    if let Some(port) = port {
+        cmd.args(["-p", &port.to_string()]);
+    }
+    match server {
+        Some(server) => {
+            cmd.args(["-c", server, "-t", &duration_secs.to_string(), "-P", &parallel.to_string()]);
+            if udp {
+                cmd.arg("-u");
+            }
+            if let Some(bitrate) = bitrate {
+                cmd.args(["-b", bitrate]);
+            }
+        }
+        // The client picks protocol, streams and duration; serve exactly one test
+        None => {
+            cmd.args(["-s", "-1"]);
+        }
+    }
+    
+    let output = run_capture(&mut cmd)?;
+    let json: Value = match serde_json::from_str(&output.stdout) {
+        Ok(json) => json,
+        Err(_) => {
+            result.error = Some(format!("iperf3 failed: {}", output.combined()));
+            return Ok(result);
+        }
+    };
+    
+    // iperf3 reports failures inside its JSON, e.g. "unable to connect to server"
+    if let Some(error) = json.get("error").and_then(Value::as_str) {
+        result.error = Some(format!("iperf3: {}", error));
+        return Ok(result);
+    }
+    
+    parse_iperf3_json(&json, &mut result);
+    result.success = result.sent_bits_per_sec.is_some() || result.received_bits_per_sec.is_some();
+    if !result.success {
+        result.error = Some("No throughput summary in iperf3 output".to_string());
+    }
+    
+    Ok(result)
+}
+
+/// Fill in throughput from the `end` summary of `iperf3 --json`. TCP reports
+/// sender and receiver totals; UDP a single sum with jitter and loss.
+fn parse_iperf3_json(json: &Value, result: &mut NetBwResult) {
+    let f64_at = |pointer: &str| json.pointer(pointer).and_then(Value::as_f64);
+    
+    // A listening server learns the protocol, stream count and duration from
+    // the client
+    if json.pointer("/start/test_start/protocol").and_then(Value::as_str) == Some("UDP") {
+        result.protocol = "udp".to_string();
+    }
+    if let Some(streams) = json.pointer("/start/test_start/num_streams").and_then(Value::as_u64) {
+        result.parallel = streams as u32;
+    }
+    if let Some(duration) = json.pointer("/start/test_start/duration").and_then(Value::as_u64) {
+        result.duration_secs = duration as u32;
+    }
+    
+    if result.protocol == "udp" {
+        result.sent_bits_per_sec = f64_at("/end/sum/bits_per_second");
+        result.received_bits_per_sec = f64_at("/end/sum_received/bits_per_second").or(result.sent_bits_per_sec);
+        result.jitter_ms = f64_at("/end/sum/jitter_ms");
+        result.lost_percent = f64_at("/end/sum/lost_percent");
+    } else {
+        result.sent_bits_per_sec = f64_at("/end/sum_sent/bits_per_second");
+        result.received_bits_per_sec = f64_at("/end/sum_received/bits_per_second");
+        result.retransmits = json.pointer("/end/sum_sent/retransmits").and_then(Value::as_u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn empty_result(protocol: &str) -> NetBwResult {
+        NetBwResult {
+            protocol: protocol.to_string(),
+            role: "client".to_string(),
+            server: Some("10.0.0.2".to_string()),
+            parallel: 4,
+            duration_secs: 10,
+            success: false,
+            sent_bits_per_sec: None,
+            received_bits_per_sec: None,
+            retransmits: None,
+            jitter_ms: None,
+            lost_percent: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_iperf3_tcp() {
+        let json = json!({
+            "start": { "test_start": { "protocol": "TCP", "num_streams": 4 } },
+            "end": {
+                "sum_sent": { "bytes": 116_425_000_000u64, "bits_per_second": 93_140_000_000.0, "retransmits": 12 },
+                "sum_received": { "bytes": 116_400_000_000u64, "bits_per_second": 93_120_000_000.0 }
+            }
+        });
+        let mut result = empty_result("tcp");
+        parse_iperf3_json(&json, &mut result);
+        assert_eq!(result.sent_bits_per_sec, Some(93_140_000_000.0));
+        assert_eq!(result.received_bits_per_sec, Some(93_120_000_000.0));
+        assert_eq!(result.retransmits, Some(12));
+        assert_eq!(result.jitter_ms, None);
+    }
+
+    #[test]
+    fn test_parse_iperf3_udp_on_listening_server() {
+        let json = json!({
+            "start": { "test_start": { "protocol": "UDP", "num_streams": 1, "duration": 30 } },
+            "end": {
+                "sum": { "bits_per_second": 9_876_000_000.0, "jitter_ms": 0.012, "lost_packets": 3, "lost_percent": 0.0004 }
+            }
+        });
+        let mut result = empty_result("tcp");
+        parse_iperf3_json(&json, &mut result);
+        assert_eq!(result.protocol, "udp");
+        assert_eq!(result.parallel, 1);
+        assert_eq!(result.duration_secs, 30);
+        assert_eq!(result.received_bits_per_sec, Some(9_876_000_000.0));
+        assert_eq!(result.jitter_ms, Some(0.012));
+        assert_eq!(result.lost_percent, Some(0.0004));
+        assert_eq!(result.retransmits, None);
+    }
+}
@@ -1,13 +1,26 @@
 // Testing and diagnostics modules
+pub mod cpu;
 pub mod gpu_errors;
+pub mod gpu_config;
+pub mod gpu_topo;
 pub mod nccl;
 pub mod mpi;
+pub mod ib;
+pub mod iperf;
 pub mod hashcat;
 pub mod dcgm;
+pub mod diagnostics;
+pub mod units;
 
 // Re-export main collection functions
-pub use gpu_errors::{collect_gpu_errors, collect_gpu_health};
+pub use cpu::run_cpu_test;
+pub use gpu_errors::{collect_gpu_errors, collect_gpu_health, GpuFilter};
+pub use gpu_config::{configure_gpus, GpuConfigRequest};
+pub use gpu_topo::collect_gpu_topology;
 pub use nccl::{collect_nccl_info, run_nccl_test};
 pub use mpi::{collect_mpi_info, run_mpi_test};
-pub use hashcat::{collect_hashcat_info, run_hashcat_benchmark, run_hashcat_test};
+pub use ib::run_ib_bw_test;
+pub use iperf::run_net_bw_test;
+pub use hashcat::{collect_hashcat_info, default_session_dir, restore_hashcat_test, run_hashcat_benchmark, run_hashcat_stress, run_hashcat_test, HashcatOptions, HashcatSession};
 pub use dcgm::{collect_dcgm_info, run_dcgm_diag, run_dcgm_health_check};
+pub use diagnostics::run_all_checks;
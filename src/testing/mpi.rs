@@ -1,4 +1,7 @@
+use crate::hardware::tool_on_path;
 use crate::hardware::types::{MpiInfo, MpiTestResult};
+use crate::testing::units::{mb_per_sec_to_bytes, mib_per_sec_to_bytes, parse_size};
+use crate::util::run_capture;
 use std::process::Command;
 use sysinfo::System;
 
@@ -20,23 +23,14 @@ pub fn collect_mpi_info() -> MpiInfo {
     info.num_cpus = sys.cpus().len() as u32;
     
     // Check for mpirun/mpiexec
-    for binary in &["mpirun", "mpiexec"] {
-        if let Ok(output) = Command::new("which").arg(binary).output() {
-            if output.status.success() {
-                info.mpirun_available = true;
-                break;
-            }
-        }
-    }
+    info.mpirun_available = ["mpirun", "mpiexec"].iter().any(|binary| tool_on_path(binary));
     
     // Get MPI version from mpirun
     if info.mpirun_available {
-        if let Ok(output) = Command::new("mpirun").arg("--version").output() {
-            if output.status.success() {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                
+        if let Ok(output) = run_capture(Command::new("mpirun").arg("--version")) {
+            if output.success {
                 // Parse implementation and version
-                let (implementation, version) = parse_mpi_version(&output_str);
+                let (implementation, version) = parse_mpi_version(&output.stdout);
                 info.mpi_implementation = implementation;
                 info.mpi_version = version;
                 info.mpi_available = true;
@@ -53,14 +47,7 @@ pub fn collect_mpi_info() -> MpiInfo {
         "mpptest",
     ];
     
-    for binary in &benchmark_binaries {
-        if let Ok(output) = Command::new("which").arg(binary).output() {
-            if output.status.success() {
-                info.mpi_benchmark_available = true;
-                break;
-            }
-        }
-    }
+    info.mpi_benchmark_available = benchmark_binaries.iter().any(|binary| tool_on_path(binary));
     
     info
 }
@@ -82,6 +69,7 @@ pub fn run_mpi_test(
         success: false,
         latency_us: None,
         bandwidth_mbps: None,
+        bandwidth_bytes_per_sec: None,
         min_latency_us: None,
         max_latency_us: None,
         avg_latency_us: None,
@@ -90,11 +78,7 @@ pub fn run_mpi_test(
     };
     
     // Check if mpirun is available
-    if !Command::new("which")
-        .arg("mpirun")
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+    if !tool_on_path("mpirun")
     {
         result.error = Some("mpirun not found. Please install an MPI implementation (OpenMPI, MPICH, Intel MPI, etc.)".to_string());
         return Ok(result);
@@ -131,23 +115,18 @@ fn try_osu_benchmark(
     };
     
     // Check if benchmark exists
-    if !Command::new("which")
-        .arg(benchmark_name)
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+    if !tool_on_path(benchmark_name)
     {
         return None;
     }
     
     // Run the benchmark
-    let output = Command::new("mpirun")
-        .args(&[
+    let output = run_capture(Command::new("mpirun")
+        .args([
             "-n", &num_processes.to_string(),
             "--allow-run-as-root",  // Some systems require this
             benchmark_name,
-        ])
-        .output()
+        ]))
         .ok()?;
     
     let mut result = MpiTestResult {
@@ -155,9 +134,10 @@ fn try_osu_benchmark(
         num_processes,
         size_bytes,
         iterations,
-        success: output.status.success(),
+        success: output.success,
         latency_us: None,
         bandwidth_mbps: None,
+        bandwidth_bytes_per_sec: None,
         min_latency_us: None,
         max_latency_us: None,
         avg_latency_us: None,
@@ -165,14 +145,14 @@ fn try_osu_benchmark(
         raw_output: None,
     };
     
-    if output.status.success() {
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        result.raw_output = Some(output_str.to_string());
+    if output.success {
+        let output_str = &output.stdout;
+        result.raw_output = Some(output_str.clone());
         
         // Parse OSU benchmark output
-        parse_osu_output(&output_str, &mut result, size_bytes);
+        parse_osu_output(output_str, &mut result, size_bytes);
     } else {
-        result.error = Some(String::from_utf8_lossy(&output.stderr).to_string());
+        result.error = Some(output.stderr.clone());
     }
     
     Some(result)
@@ -188,11 +168,7 @@ fn try_imb_benchmark(
     let benchmark_name = "IMB-MPI1";
     
     // Check if benchmark exists
-    if !Command::new("which")
-        .arg(benchmark_name)
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+    if !tool_on_path(benchmark_name)
     {
         return None;
     }
@@ -206,14 +182,13 @@ fn try_imb_benchmark(
     };
     
     // Run IMB
-    let output = Command::new("mpirun")
-        .args(&[
+    let output = run_capture(Command::new("mpirun")
+        .args([
             "-n", &num_processes.to_string(),
             "--allow-run-as-root",
             benchmark_name,
             imb_test,
-        ])
-        .output()
+        ]))
         .ok()?;
     
     let mut result = MpiTestResult {
@@ -221,9 +196,10 @@ fn try_imb_benchmark(
         num_processes,
         size_bytes: 0,
         iterations: 0,
-        success: output.status.success(),
+        success: output.success,
         latency_us: None,
         bandwidth_mbps: None,
+        bandwidth_bytes_per_sec: None,
         min_latency_us: None,
         max_latency_us: None,
         avg_latency_us: None,
@@ -231,12 +207,12 @@ fn try_imb_benchmark(
         raw_output: None,
     };
     
-    if output.status.success() {
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        result.raw_output = Some(output_str.to_string());
-        parse_imb_output(&output_str, &mut result);
+    if output.success {
+        let output_str = &output.stdout;
+        result.raw_output = Some(output_str.clone());
+        parse_imb_output(output_str, &mut result);
     } else {
-        result.error = Some(String::from_utf8_lossy(&output.stderr).to_string());
+        result.error = Some(output.stderr.clone());
     }
     
     Some(result)
@@ -257,6 +233,7 @@ fn run_custom_mpi_test(
         success: false,
         latency_us: None,
         bandwidth_mbps: None,
+        bandwidth_bytes_per_sec: None,
         min_latency_us: None,
         max_latency_us: None,
         avg_latency_us: None,
@@ -281,24 +258,6 @@ fn run_custom_mpi_test(
     Ok(result)
 }
 
-/// Parse size string to bytes
-fn parse_size(size: &str) -> Result<u64, Box<dyn std::error::Error>> {
-    let size = size.trim().to_uppercase();
-    
-    let (number, multiplier) = if size.ends_with('K') {
-        (size.trim_end_matches('K'), 1024u64)
-    } else if size.ends_with('M') {
-        (size.trim_end_matches('M'), 1024u64 * 1024)
-    } else if size.ends_with('G') {
-        (size.trim_end_matches('G'), 1024u64 * 1024 * 1024)
-    } else {
-        (size.as_str(), 1u64)
-    };
-    
-    let num: u64 = number.parse()?;
-    Ok(num * multiplier)
-}
-
 /// Parse OSU benchmark output
 fn parse_osu_output(output: &str, result: &mut MpiTestResult, target_size: u64) {
     // OSU output format:
@@ -329,6 +288,7 @@ fn parse_osu_output(output: &str, result: &mut MpiTestResult, target_size: u64)
                 if parts.len() >= 2 && result.test_type.contains("bandwidth") {
                     if let Ok(bw) = parts[1].parse::<f64>() {
                         result.bandwidth_mbps = Some(bw);
+                        result.bandwidth_bytes_per_sec = Some(mb_per_sec_to_bytes(bw));
                     }
                 }
             }
@@ -346,6 +306,7 @@ fn parse_imb_output(output: &str, result: &mut MpiTestResult) {
                 if let Ok(bw) = part.parse::<f64>() {
                     if bw > 0.0 && bw < 1000000.0 {
                         result.bandwidth_mbps = Some(bw);
+                        result.bandwidth_bytes_per_sec = Some(mib_per_sec_to_bytes(bw));
                         break;
                     }
                 }
@@ -1,5 +1,9 @@
 use nvml_wrapper::Nvml;
-use crate::hardware::types::{NcclInfo, NcclTestResult, NcclGpuResult};
+use crate::hardware::tool_on_path;
+use crate::hardware::types::{NcclInfo, NcclTestResult, NcclGpuResult, NcclP2pLink};
+use crate::testing::units::{gb_per_sec_to_bytes, parse_size};
+use crate::util::run_capture;
+use std::collections::HashMap;
 use std::process::Command;
 
 /// Get NCCL installation information and version
@@ -10,6 +14,8 @@ pub fn collect_nccl_info() -> NcclInfo {
         num_gpus: 0,
         nccl_available: false,
         nccl_tests_available: false,
+        env: collect_nccl_env(),
+        ib_hcas: collect_ib_hcas(),
         error: None,
     };
     
@@ -29,20 +35,12 @@ pub fn collect_nccl_info() -> NcclInfo {
         "reduce_scatter_perf",
     ];
     
-    for binary in &nccl_test_binaries {
-        if let Ok(output) = Command::new("which").arg(binary).output() {
-            if output.status.success() {
-                info.nccl_tests_available = true;
-                break;
-            }
-        }
-    }
+    info.nccl_tests_available = nccl_test_binaries.iter().any(|binary| tool_on_path(binary));
     
     // Try to get CUDA version
-    if let Ok(output) = Command::new("nvcc").arg("--version").output() {
-        if output.status.success() {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            for line in output_str.lines() {
+    if let Ok(output) = run_capture(Command::new("nvcc").arg("--version")) {
+        if output.success {
+            for line in output.stdout.lines() {
                 if line.contains("release") {
                     if let Some(version) = extract_cuda_version(line) {
                         info.cuda_version = Some(version);
@@ -75,12 +73,9 @@ pub fn collect_nccl_info() -> NcclInfo {
     
     // Alternative: try to get version from pkg-config
     if info.nccl_version.is_none() {
-        if let Ok(output) = Command::new("pkg-config")
-            .args(&["--modversion", "nccl"])
-            .output()
-        {
-            if output.status.success() {
-                info.nccl_version = Some(String::from_utf8_lossy(&output.stdout).trim().to_string());
+        if let Ok(output) = run_capture(Command::new("pkg-config").args(["--modversion", "nccl"])) {
+            if output.success {
+                info.nccl_version = Some(output.stdout.trim().to_string());
             }
         }
     }
@@ -88,6 +83,27 @@ pub fn collect_nccl_info() -> NcclInfo {
     info
 }
 
+/// Capture NCCL_* environment variables (NCCL_DEBUG, NCCL_IB_HCA, NCCL_SOCKET_IFNAME, ...)
+fn collect_nccl_env() -> HashMap<String, String> {
+    std::env::vars()
+        .filter(|(key, _)| key.starts_with("NCCL_"))
+        .collect()
+}
+
+/// List InfiniBand HCAs visible to NCCL's IB transport
+fn collect_ib_hcas() -> Vec<String> {
+    let mut hcas: Vec<String> = std::fs::read_dir("/sys/class/infiniband")
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| e.file_name().into_string().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    hcas.sort();
+    hcas
+}
+
 /// Run NCCL test
 pub fn run_nccl_test(test_type: &str, size: &str, iterations: u32) -> Result<NcclTestResult, Box<dyn std::error::Error>> {
     let nvml = Nvml::init()?;
@@ -108,8 +124,10 @@ pub fn run_nccl_test(test_type: &str, size: &str, iterations: u32) -> Result<Ncc
         time_us: None,
         bandwidth_gbps: None,
         bus_bandwidth_gbps: None,
+        bandwidth_bytes_per_sec: None,
         error: None,
         gpu_results: Vec::new(),
+        p2p_matrix: None,
     };
     
     // Collect GPU information
@@ -138,33 +156,29 @@ pub fn run_nccl_test(test_type: &str, size: &str, iterations: u32) -> Result<Ncc
     };
     
     // Try to run the NCCL test binary
-    let test_result = Command::new(test_binary)
-        .args(&[
+    let test_result = run_capture(Command::new(test_binary)
+        .args([
             "-b", &size,  // min size
             "-e", &size,  // max size
             "-f", "2",    // size multiplication factor
             "-g", &device_count.to_string(),  // number of GPUs
             "-n", &iterations.to_string(),    // number of iterations
-        ])
-        .output();
+        ]));
     
     match test_result {
         Ok(output) => {
-            if output.status.success() {
+            if output.success {
                 result.success = true;
-                let output_str = String::from_utf8_lossy(&output.stdout);
                 
                 // Parse the output for bandwidth and timing information
-                if let Some((time, bandwidth, bus_bw)) = parse_nccl_output(&output_str) {
+                if let Some((time, bandwidth, bus_bw)) = parse_nccl_output(&output.stdout) {
                     result.time_us = Some(time);
                     result.bandwidth_gbps = Some(bandwidth);
+                    result.bandwidth_bytes_per_sec = Some(gb_per_sec_to_bytes(bandwidth));
                     result.bus_bandwidth_gbps = Some(bus_bw);
                 }
             } else {
-                result.error = Some(format!(
-                    "Test failed: {}",
-                    String::from_utf8_lossy(&output.stderr)
-                ));
+                result.error = Some(format!("Test failed: {}", output.stderr));
             }
         }
         Err(e) => {
@@ -176,8 +190,9 @@ pub fn run_nccl_test(test_type: &str, size: &str, iterations: u32) -> Result<Ncc
                     test_binary
                 ));
                 
-                // Still provide basic info about GPUs
+                // Still provide basic info about GPUs and how they can reach each other
                 result.success = false;
+                result.p2p_matrix = Some(collect_p2p_matrix(&nvml, device_count));
             } else {
                 result.error = Some(format!("Failed to run test: {}", e));
             }
@@ -187,22 +202,58 @@ pub fn run_nccl_test(test_type: &str, size: &str, iterations: u32) -> Result<Ncc
     Ok(result)
 }
 
-/// Parse size string (e.g., "32M", "1G", "512K") to bytes
-fn parse_size(size: &str) -> Result<u64, Box<dyn std::error::Error>> {
-    let size = size.trim().to_uppercase();
+/// Build the GPU peer-to-peer access matrix from NVML for every ordered GPU pair
+fn collect_p2p_matrix(nvml: &Nvml, device_count: u32) -> Vec<NcclP2pLink> {
+    use nvml_wrapper::enum_wrappers::device::{P2pCapabilitiesIndex, P2pStatus, TopologyLevel};
     
-    let (number, multiplier) = if size.ends_with('K') {
-        (size.trim_end_matches('K'), 1024u64)
-    } else if size.ends_with('M') {
-        (size.trim_end_matches('M'), 1024u64 * 1024)
-    } else if size.ends_with('G') {
-        (size.trim_end_matches('G'), 1024u64 * 1024 * 1024)
-    } else {
-        (size.as_str(), 1u64)
-    };
+    let mut links = Vec::new();
+    
+    for i in 0..device_count {
+        let source = match nvml.device_by_index(i) {
+            Ok(d) => d,
+            Err(_) => continue,
+        };
+        
+        for j in 0..device_count {
+            if i == j {
+                continue;
+            }
+            let peer = match nvml.device_by_index(j) {
+                Ok(d) => d,
+                Err(_) => continue,
+            };
+            
+            let supported = |index| matches!(source.p2p_status(&peer, index), Ok(P2pStatus::Ok));
+            let nvlink = supported(P2pCapabilitiesIndex::NvLink);
+            
+            let link_type = if nvlink {
+                Some("NV".to_string())
+            } else {
+                nvml.device_by_index(j)
+                    .and_then(|peer| source.topology_common_ancestor(peer))
+                    .ok()
+                    .map(|level| match level {
+                        TopologyLevel::Internal => "INTERNAL",
+                        TopologyLevel::Single => "PIX",
+                        TopologyLevel::Multiple => "PXB",
+                        TopologyLevel::HostBridge => "PHB",
+                        TopologyLevel::Node => "NODE",
+                        TopologyLevel::System => "SYS",
+                    }.to_string())
+            };
+            
+            links.push(NcclP2pLink {
+                source_index: i,
+                peer_index: j,
+                p2p_read: supported(P2pCapabilitiesIndex::Read),
+                p2p_write: supported(P2pCapabilitiesIndex::Write),
+                nvlink,
+                link_type,
+            });
+        }
+    }
     
-    let num: u64 = number.parse()?;
-    Ok(num * multiplier)
+    links
 }
 
 /// Parse NCCL test output to extract performance metrics
@@ -248,13 +299,9 @@ fn extract_cuda_version(line: &str) -> Option<String> {
 
 /// Get NCCL version from library using strings command
 fn get_nccl_version_from_lib(lib_path: &str) -> Option<String> {
-    if let Ok(output) = Command::new("strings")
-        .arg(lib_path)
-        .output()
-    {
-        if output.status.success() {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            for line in output_str.lines() {
+    if let Ok(output) = run_capture(Command::new("strings").arg(lib_path)) {
+        if output.success {
+            for line in output.stdout.lines() {
                 // Look for version strings like "2.18.3" or "NCCL version 2.18.3"
                 if line.contains("NCCL") && line.contains("version") {
                     if let Some(version) = extract_version_number(line) {
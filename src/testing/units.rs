@@ -0,0 +1,77 @@
+// Bandwidth unit normalization
+//
+// Benchmarks disagree on units:
+//   nccl-tests  "GB/s"       = 1e9 bytes/s
+//   OSU         "MB/s"       = 1e6 bytes/s
+//   IMB         "Mbytes/sec" = 2^20 bytes/s
+//   perftest    "MB/sec"     = 2^20 bytes/s
+// Results carry the tool's own unit plus bytes/s so they can be compared directly.
+
+/// Parse a message size such as "32M", "1G" or "512K" (binary multiples) to bytes
+pub fn parse_size(size: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let size = size.trim().to_uppercase();
+    
+    let (number, multiplier) = if size.ends_with('K') {
+        (size.trim_end_matches('K'), 1024u64)
+    } else if size.ends_with('M') {
+        (size.trim_end_matches('M'), 1024u64 * 1024)
+    } else if size.ends_with('G') {
+        (size.trim_end_matches('G'), 1024u64 * 1024 * 1024)
+    } else {
+        (size.as_str(), 1u64)
+    };
+    
+    let num: u64 = number.parse()?;
+    Ok(num * multiplier)
+}
+
+pub fn gb_per_sec_to_bytes(gb_per_sec: f64) -> u64 {
+    (gb_per_sec * 1e9).round() as u64
+}
+
+pub fn mb_per_sec_to_bytes(mb_per_sec: f64) -> u64 {
+    (mb_per_sec * 1e6).round() as u64
+}
+
+pub fn mib_per_sec_to_bytes(mib_per_sec: f64) -> u64 {
+    (mib_per_sec * 1024.0 * 1024.0).round() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nccl_gb_per_sec() {
+        assert_eq!(gb_per_sec_to_bytes(1.0), 1_000_000_000);
+        assert_eq!(gb_per_sec_to_bytes(187.45), 187_450_000_000);
+    }
+
+    #[test]
+    fn test_osu_mb_per_sec() {
+        assert_eq!(mb_per_sec_to_bytes(12345.67), 12_345_670_000);
+        // Same rate expressed by NCCL and OSU normalizes to the same value
+        assert_eq!(mb_per_sec_to_bytes(25000.0), gb_per_sec_to_bytes(25.0));
+    }
+
+    #[test]
+    fn test_imb_mib_per_sec() {
+        assert_eq!(mib_per_sec_to_bytes(1.0), 1_048_576);
+        assert_eq!(mib_per_sec_to_bytes(0.5), 524_288);
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("64k").unwrap(), 65_536);
+        assert_eq!(parse_size("32M").unwrap(), 33_554_432);
+        assert_eq!(parse_size(" 1G ").unwrap(), 1_073_741_824);
+        assert!(parse_size("lots").is_err());
+    }
+
+    #[test]
+    fn test_invalid_values_clamp_to_zero() {
+        assert_eq!(gb_per_sec_to_bytes(-1.0), 0);
+        assert_eq!(mb_per_sec_to_bytes(f64::NAN), 0);
+    }
+}
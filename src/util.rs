@@ -0,0 +1,298 @@
+// Small helpers shared across collectors
+use std::io::{self, Read};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Overrides how many times flaky external tools (ipmitool, Redfish via curl) are tried.
+pub const TOOL_ATTEMPTS_ENV: &str = "FARM_MANAGER_TOOL_ATTEMPTS";
+
+const DEFAULT_TOOL_ATTEMPTS: u32 = 3;
+const TOOL_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Whether the process runs as root (effective uid 0).
+pub fn is_root() -> bool {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// Call `attempt` until it returns `Some`, at most `attempts` times (at least once),
+/// sleeping `delay` between tries.
+pub fn retry<T>(attempts: u32, delay: Duration, mut attempt: impl FnMut() -> Option<T>) -> Option<T> {
+    for n in 0..attempts.max(1) {
+        if n > 0 {
+            thread::sleep(delay);
+        }
+        if let Some(value) = attempt() {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// [`retry`] with the attempt count from `FARM_MANAGER_TOOL_ATTEMPTS` (default 3).
+pub fn retry_tool<T>(attempt: impl FnMut() -> Option<T>) -> Option<T> {
+    let attempts = parse_attempts(std::env::var(TOOL_ATTEMPTS_ENV).ok().as_deref());
+    retry(attempts, TOOL_RETRY_DELAY, attempt)
+}
+
+/// Run `load` on this thread while another thread calls `sample` every
+/// `interval`, and return what `load` returned once both have stopped.
+pub fn sample_while<R>(interval: Duration, mut sample: impl FnMut() + Send, load: impl FnOnce() -> R) -> R {
+    let done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            while !done.load(Ordering::Relaxed) {
+                sample();
+                // Sleep in short steps so the sampler stops soon after the load does
+                let next_sample = Instant::now() + interval;
+                while !done.load(Ordering::Relaxed) && Instant::now() < next_sample {
+                    thread::sleep(Duration::from_millis(200));
+                }
+            }
+        });
+
+        let outcome = load();
+        done.store(true, Ordering::Relaxed);
+        outcome
+    })
+}
+
+fn parse_attempts(value: Option<&str>) -> u32 {
+    value
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_TOOL_ATTEMPTS)
+}
+
+// Set from --verbose; run_capture then echoes a failing tool's stderr
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+/// What an external tool printed and whether it exited successfully.
+#[derive(Debug)]
+pub struct Capture {
+    pub success: bool,
+    pub timed_out: bool,
+    pub code: Option<i32>, // None when killed by a signal
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Capture {
+    fn from_output(output: Output, timed_out: bool) -> Self {
+        Capture {
+            success: output.status.success() && !timed_out,
+            timed_out,
+            code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }
+    }
+
+    /// stdout followed by stderr, for tools that report problems on either stream.
+    pub fn combined(&self) -> String {
+        match (self.stdout.trim_end(), self.stderr.trim_end()) {
+            (out, "") => out.to_string(),
+            ("", err) => err.to_string(),
+            (out, err) => format!("{}\n{}", out, err),
+        }
+    }
+}
+
+/// Run `cmd` to completion and capture both streams. Errs only when the tool
+/// could not be started (usually: not installed). With --verbose, a tool that
+/// exits non-zero has its stderr echoed so the cause isn't lost.
+pub fn run_capture(cmd: &mut Command) -> io::Result<Capture> {
+    let capture = Capture::from_output(cmd.output()?, false);
+    report_failure(cmd, &capture);
+    Ok(capture)
+}
+
+/// [`run_capture`] with a deadline; a tool still running after `timeout` is
+/// killed and comes back with `timed_out` set and whatever it printed so far.
+/// A non-zero `grace` first sends SIGINT and waits that long, for tools that
+/// clean up on Ctrl-C.
+pub fn run_capture_timeout(cmd: &mut Command, timeout: Duration, grace: Duration) -> io::Result<Capture> {
+    let (output, timed_out) = output_with_timeout(cmd, timeout, grace)?;
+    let capture = Capture::from_output(output, timed_out);
+    report_failure(cmd, &capture);
+    Ok(capture)
+}
+
+fn report_failure(cmd: &Command, capture: &Capture) {
+    if capture.success || !VERBOSE.load(Ordering::Relaxed) {
+        return;
+    }
+    let program = cmd.get_program().to_string_lossy();
+    let reason = if capture.timed_out { "timed out" } else { "failed" };
+    match capture.stderr.trim() {
+        "" => eprintln!("  {} {}", program, reason),
+        stderr => eprintln!("  {} {}: {}", program, reason, stderr),
+    }
+}
+
+/// Like [`Command::output`], but kill the process if it is still running after
+/// `timeout` (see [`run_capture_timeout`] for `grace`). Returns whatever output
+/// was captured and whether it was killed.
+fn output_with_timeout(cmd: &mut Command, timeout: Duration, grace: Duration) -> io::Result<(Output, bool)> {
+    // Own process group, so a timeout also takes down anything the tool
+    // spawned; orphaned grandchildren would keep running and hold the pipes
+    let mut child = cmd
+        .process_group(0)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Drain both pipes while waiting so a chatty process can't block on a full pipe
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            timed_out = true;
+            let pgid = child.id() as libc::pid_t;
+            if !grace.is_zero() {
+                unsafe { libc::kill(pgid, libc::SIGINT) };
+                let grace_deadline = Instant::now() + grace;
+                while child.try_wait()?.is_none() && Instant::now() < grace_deadline {
+                    thread::sleep(Duration::from_millis(100));
+                }
+            }
+            unsafe { libc::kill(-pgid, libc::SIGKILL) };
+            break child.wait()?;
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+
+    let output = Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    };
+    Ok((output, timed_out))
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_capture_keeps_both_streams() {
+        let capture = run_capture(Command::new("sh").args(["-c", "echo out; echo err >&2; exit 3"])).unwrap();
+
+        assert!(!capture.success);
+        assert_eq!(capture.stdout, "out\n");
+        assert_eq!(capture.stderr, "err\n");
+        assert_eq!(capture.combined(), "out\nerr");
+    }
+
+    #[test]
+    fn test_retry_returns_first_success() {
+        let mut calls = 0;
+        let result = retry(5, Duration::ZERO, || {
+            calls += 1;
+            (calls == 3).then_some(calls)
+        });
+
+        assert_eq!(result, Some(3));
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_sample_while_samples_until_load_returns() {
+        let samples = std::sync::atomic::AtomicU32::new(0);
+        let result = sample_while(Duration::from_secs(1), || {
+            samples.fetch_add(1, Ordering::Relaxed);
+        }, || {
+            while samples.load(Ordering::Relaxed) == 0 {
+                thread::sleep(Duration::from_millis(10));
+            }
+            "done"
+        });
+
+        assert_eq!(result, "done");
+        assert_eq!(samples.into_inner(), 1);
+    }
+
+    #[test]
+    fn test_retry_gives_up_after_attempts() {
+        let mut calls = 0;
+        let result: Option<()> = retry(3, Duration::ZERO, || {
+            calls += 1;
+            None
+        });
+
+        assert_eq!(result, None);
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_retry_zero_attempts_still_tries_once() {
+        let mut calls = 0;
+        let result = retry(0, Duration::ZERO, || {
+            calls += 1;
+            Some("ok")
+        });
+
+        assert_eq!(result, Some("ok"));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_parse_attempts() {
+        assert_eq!(parse_attempts(None), DEFAULT_TOOL_ATTEMPTS);
+        assert_eq!(parse_attempts(Some("5")), 5);
+        assert_eq!(parse_attempts(Some(" 1 ")), 1);
+        assert_eq!(parse_attempts(Some("0")), DEFAULT_TOOL_ATTEMPTS);
+        assert_eq!(parse_attempts(Some("many")), DEFAULT_TOOL_ATTEMPTS);
+    }
+
+    #[test]
+    fn test_output_with_timeout_kills_slow_process() {
+        let (output, timed_out) = output_with_timeout(Command::new("sleep").arg("5"), Duration::from_millis(200), Duration::ZERO).unwrap();
+
+        assert!(timed_out);
+        assert!(!output.status.success());
+    }
+
+    #[test]
+    fn test_output_with_timeout_kills_process_group() {
+        // The background sleep inherits stdout; if it survived, draining the
+        // pipe would block for the full 100 seconds
+        let start = Instant::now();
+        let (_, timed_out) = output_with_timeout(Command::new("sh").args(["-c", "sleep 100 & sleep 100"]), Duration::from_millis(200), Duration::ZERO).unwrap();
+
+        assert!(timed_out);
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_output_with_timeout_captures_output() {
+        let (output, timed_out) = output_with_timeout(Command::new("echo").arg("hello"), Duration::from_secs(5), Duration::ZERO).unwrap();
+
+        assert!(!timed_out);
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "hello");
+    }
+}
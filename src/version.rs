@@ -0,0 +1,35 @@
+use serde::Serialize;
+
+// Build metadata, injected by build.rs
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_COMMIT: &str = env!("FARM_MANAGER_GIT_COMMIT");
+pub const BUILD_DATE: &str = env!("FARM_MANAGER_BUILD_DATE");
+
+// Shown by `farm-manager --version`
+pub const LONG_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("FARM_MANAGER_GIT_COMMIT"),
+    ", built ",
+    env!("FARM_MANAGER_BUILD_DATE"),
+    ")"
+);
+
+#[derive(Debug, Serialize)]
+pub struct VersionInfo {
+    pub version: String,
+    pub git_commit: String,
+    pub build_date: String,
+    pub target_os: String,
+    pub target_arch: String,
+}
+
+pub fn version_info() -> VersionInfo {
+    VersionInfo {
+        version: VERSION.to_string(),
+        git_commit: GIT_COMMIT.to_string(),
+        build_date: BUILD_DATE.to_string(),
+        target_os: std::env::consts::OS.to_string(),
+        target_arch: std::env::consts::ARCH.to_string(),
+    }
+}